@@ -1,11 +1,15 @@
 use sha1::{Digest as Sha1Digest, Sha1};
 use sha2::{Sha224, Sha256, Sha384, Sha512};
 use sha3::{Sha3_224, Sha3_256, Sha3_384, Sha3_512};
-use libsm::sm3::hash::Sm3Hash;
 use std::fmt::Write;
-use hex;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use super::sm3::Sm3;
+use crate::error::{Error, Result};
 
-#[derive(PartialEq, Clone, Copy)]
+/// Hash algorithms of the Hash Calculator
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum HashType {
     Sha1,
     Sha224,
@@ -19,88 +23,382 @@ pub enum HashType {
     Sm3,
 }
 
-#[derive(PartialEq, Clone, Copy)]
+impl HashType {
+    /// All supported hash types
+    pub fn all() -> [HashType; 10] {
+        [
+            HashType::Sha1,
+            HashType::Sha224,
+            HashType::Sha256,
+            HashType::Sha384,
+            HashType::Sha512,
+            HashType::Sha3_224,
+            HashType::Sha3_256,
+            HashType::Sha3_384,
+            HashType::Sha3_512,
+            HashType::Sm3,
+        ]
+    }
+
+    /// Get display name
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashType::Sha1 => "SHA-1",
+            HashType::Sha224 => "SHA-224",
+            HashType::Sha256 => "SHA-256",
+            HashType::Sha384 => "SHA-384",
+            HashType::Sha512 => "SHA-512",
+            HashType::Sha3_224 => "SHA3-224",
+            HashType::Sha3_256 => "SHA3-256",
+            HashType::Sha3_384 => "SHA3-384",
+            HashType::Sha3_512 => "SHA3-512",
+            HashType::Sm3 => "SM3",
+        }
+    }
+
+    /// Digest size in bytes
+    pub fn output_size(&self) -> usize {
+        match self {
+            HashType::Sha1 => 20,
+            HashType::Sha224 | HashType::Sha3_224 => 28,
+            HashType::Sha256 | HashType::Sha3_256 | HashType::Sm3 => 32,
+            HashType::Sha384 | HashType::Sha3_384 => 48,
+            HashType::Sha512 | HashType::Sha3_512 => 64,
+        }
+    }
+
+    /// Hash types producing digests of `size` bytes, in [`HashType::all`] order
+    pub fn for_output_size(size: usize) -> Vec<HashType> {
+        HashType::all()
+            .into_iter()
+            .filter(|hash_type| hash_type.output_size() == size)
+            .collect()
+    }
+
+    /// Input block size in bytes, as used by HMAC
+    pub fn block_size(&self) -> usize {
+        match self {
+            HashType::Sha1 | HashType::Sha224 | HashType::Sha256 | HashType::Sm3 => 64,
+            HashType::Sha384 | HashType::Sha512 => 128,
+            HashType::Sha3_224 => 144,
+            HashType::Sha3_256 => 136,
+            HashType::Sha3_384 => 104,
+            HashType::Sha3_512 => 72,
+        }
+    }
+
+    /// Look up a hash type by name, ignoring case, dashes and underscores (e.g. "sha3-256")
+    pub fn from_name(name: &str) -> Option<Self> {
+        let normalized: String = name
+            .chars()
+            .filter(|c| *c != '-' && *c != '_')
+            .collect::<String>()
+            .to_lowercase();
+        HashType::all()
+            .into_iter()
+            .find(|hash_type| hash_type.name().replace('-', "").to_lowercase() == normalized)
+    }
+}
+
+/// Interpretation of the input of [`calculate_hash`]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum InputType {
     Text,
     Hex,
 }
 
-pub fn calculate_hash(input: &str, input_type: InputType, hash_type: HashType) -> Result<String, String> {
-    let input_bytes = match input_type {
-        InputType::Text => input.as_bytes().to_vec(),
-        InputType::Hex => match hex::decode(input.replace(" ", "")) {
-            Ok(bytes) => bytes,
-            Err(_) => return Err("Invalid Hex Input".to_string()),
-        },
-    };
-
-    let result = match hash_type {
-        HashType::Sha1 => {
-            let mut hasher = Sha1::new();
-            hasher.update(&input_bytes);
-            let result = hasher.finalize();
-            to_hex_string(&result)
-        }
-        HashType::Sha224 => {
-            let mut hasher = Sha224::new();
-            hasher.update(&input_bytes);
-            let result = hasher.finalize();
-            to_hex_string(&result)
-        }
-        HashType::Sha256 => {
-            let mut hasher = Sha256::new();
-            hasher.update(&input_bytes);
-            let result = hasher.finalize();
-            to_hex_string(&result)
+impl InputType {
+    /// Bytes represented by `input`
+    pub fn decode(&self, input: &str) -> Result<Vec<u8>> {
+        match self {
+            InputType::Text => Ok(input.as_bytes().to_vec()),
+            InputType::Hex => hex::decode(input.replace(" ", "")).map_err(Error::from),
         }
-        HashType::Sha384 => {
-            let mut hasher = Sha384::new();
-            hasher.update(&input_bytes);
-            let result = hasher.finalize();
-            to_hex_string(&result)
-        }
-        HashType::Sha512 => {
-            let mut hasher = Sha512::new();
-            hasher.update(&input_bytes);
-            let result = hasher.finalize();
-            to_hex_string(&result)
-        }
-        HashType::Sha3_224 => {
-            let mut hasher = Sha3_224::new();
-            hasher.update(&input_bytes);
-            let result = hasher.finalize();
-            to_hex_string(&result)
-        }
-        HashType::Sha3_256 => {
-            let mut hasher = Sha3_256::new();
-            hasher.update(&input_bytes);
-            let result = hasher.finalize();
-            to_hex_string(&result)
-        }
-        HashType::Sha3_384 => {
-            let mut hasher = Sha3_384::new();
-            hasher.update(&input_bytes);
-            let result = hasher.finalize();
-            to_hex_string(&result)
+    }
+}
+
+/// Hash text, or hex-encoded bytes, returning the lowercase hex digest
+pub fn calculate_hash(input: &str, input_type: InputType, hash_type: HashType) -> Result<String> {
+    let input_bytes = input_type.decode(input)?;
+
+    Ok(hash_bytes(&input_bytes, hash_type))
+}
+
+/// HMAC of text, or hex-encoded bytes, returning the lowercase hex digest
+pub fn calculate_hmac(input: &str, input_type: InputType, key: &[u8], hash_type: HashType) -> Result<String> {
+    let input_bytes = input_type.decode(input)?;
+
+    Ok(hmac_bytes(key, &input_bytes, hash_type))
+}
+
+/// Hash raw bytes, returning the lowercase hex digest
+pub fn hash_bytes(input_bytes: &[u8], hash_type: HashType) -> String {
+    let mut hasher = Hasher::new(hash_type);
+    hasher.update(input_bytes);
+    to_hex_string(hasher.finalize())
+}
+
+/// Size of the chunks read by [`hash_reader`]
+pub const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Hash everything read from `reader` in chunks, returning the lowercase hex digest
+///
+/// `progress` is called with the number of bytes hashed so far after every chunk;
+/// returning `false` stops hashing with [`Error::Cancelled`].
+pub fn hash_reader<R: Read>(
+    reader: R,
+    hash_type: HashType,
+    progress: impl FnMut(u64) -> bool,
+) -> Result<String> {
+    let mut hasher = Hasher::new(hash_type);
+    read_chunks(reader, progress, |chunk| hasher.update(chunk))?;
+    Ok(to_hex_string(hasher.finalize()))
+}
+
+/// Hash a file without loading it into memory, see [`hash_reader`]
+pub fn hash_file<P: AsRef<Path>>(
+    path: P,
+    hash_type: HashType,
+    progress: impl FnMut(u64) -> bool,
+) -> Result<String> {
+    hash_reader(File::open(path)?, hash_type, progress)
+}
+
+/// HMAC (RFC 2104) of raw bytes, returning the lowercase hex digest
+pub fn hmac_bytes(key: &[u8], message: &[u8], hash_type: HashType) -> String {
+    let mut hmac = Hmac::new(hash_type, key);
+    hmac.update(message);
+    to_hex_string(hmac.finalize())
+}
+
+/// HMAC of everything read from `reader` in chunks, see [`hash_reader`]
+pub fn hmac_reader<R: Read>(
+    reader: R,
+    key: &[u8],
+    hash_type: HashType,
+    progress: impl FnMut(u64) -> bool,
+) -> Result<String> {
+    let mut hmac = Hmac::new(hash_type, key);
+    read_chunks(reader, progress, |chunk| hmac.update(chunk))?;
+    Ok(to_hex_string(hmac.finalize()))
+}
+
+/// HMAC of a file without loading it into memory, see [`hash_reader`]
+pub fn hmac_file<P: AsRef<Path>>(
+    path: P,
+    key: &[u8],
+    hash_type: HashType,
+    progress: impl FnMut(u64) -> bool,
+) -> Result<String> {
+    hmac_reader(File::open(path)?, key, hash_type, progress)
+}
+
+/// Digests under every [`HashType`], or HMACs when a key is given, in [`HashType::all`] order
+pub fn digest_matrix(message: &[u8], key: Option<&[u8]>) -> Vec<(HashType, String)> {
+    let mut digesters = Digester::all(key);
+    for digester in &mut digesters {
+        digester.update(message);
+    }
+    Digester::finalize_all(digesters)
+}
+
+/// Digest matrix of everything read from `reader`, reading the input only once
+pub fn digest_matrix_reader<R: Read>(
+    reader: R,
+    key: Option<&[u8]>,
+    progress: impl FnMut(u64) -> bool,
+) -> Result<Vec<(HashType, String)>> {
+    let mut digesters = Digester::all(key);
+    read_chunks(reader, progress, |chunk| {
+        for digester in &mut digesters {
+            digester.update(chunk);
         }
-        HashType::Sha3_512 => {
-            let mut hasher = Sha3_512::new();
-            hasher.update(&input_bytes);
-            let result = hasher.finalize();
-            to_hex_string(&result)
+    })?;
+    Ok(Digester::finalize_all(digesters))
+}
+
+/// Digest matrix of a file without loading it into memory, see [`hash_reader`]
+pub fn digest_matrix_file<P: AsRef<Path>>(
+    path: P,
+    key: Option<&[u8]>,
+    progress: impl FnMut(u64) -> bool,
+) -> Result<Vec<(HashType, String)>> {
+    digest_matrix_reader(File::open(path)?, key, progress)
+}
+
+/// Hash or HMAC state of one row of the digest matrix
+struct Digester {
+    hash_type: HashType,
+    inner: Hasher,
+    outer: Option<Hasher>, // Outer HMAC hash, when a key is given
+}
+
+impl Digester {
+    fn all(key: Option<&[u8]>) -> Vec<Digester> {
+        HashType::all()
+            .into_iter()
+            .map(|hash_type| match key {
+                Some(key) => {
+                    let hmac = Hmac::new(hash_type, key);
+                    Digester { hash_type, inner: hmac.inner, outer: Some(hmac.outer) }
+                },
+                None => Digester { hash_type, inner: Hasher::new(hash_type), outer: None },
+            })
+            .collect()
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    fn finalize_all(digesters: Vec<Digester>) -> Vec<(HashType, String)> {
+        digesters
+            .into_iter()
+            .map(|digester| {
+                let digest = match digester.outer {
+                    Some(outer) => Hmac { inner: digester.inner, outer }.finalize(),
+                    None => digester.inner.finalize(),
+                };
+                (digester.hash_type, to_hex_string(digest))
+            })
+            .collect()
+    }
+}
+
+/// Feed `reader` to `consume` in chunks of [`CHUNK_SIZE`], reporting progress after each one
+fn read_chunks<R: Read>(
+    mut reader: R,
+    mut progress: impl FnMut(u64) -> bool,
+    mut consume: impl FnMut(&[u8]),
+) -> Result<()> {
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    let mut processed = 0u64;
+
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(read) => read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(Error::Io(err)),
+        };
+        consume(&buffer[..read]);
+        processed += read as u64;
+        if !progress(processed) {
+            return Err(Error::Cancelled);
         }
-        HashType::Sm3 => {
-            let mut sm3 = Sm3Hash::new(&input_bytes);
-            let result = sm3.get_hash();
-            let mut hex_string = String::new();
-            for byte in result.iter() {
-                write!(hex_string, "{:02x}", byte).unwrap();
-            }
-            hex_string
+    }
+}
+
+/// Incremental HMAC for any [`HashType`]
+#[derive(Clone)]
+pub struct Hmac {
+    inner: Hasher,
+    outer: Hasher,
+}
+
+impl Hmac {
+    pub fn new(hash_type: HashType, key: &[u8]) -> Self {
+        // Keys longer than a block are hashed first, shorter ones are zero padded
+        let mut block_key = if key.len() > hash_type.block_size() {
+            let mut hasher = Hasher::new(hash_type);
+            hasher.update(key);
+            hasher.finalize()
+        } else {
+            key.to_vec()
+        };
+        block_key.resize(hash_type.block_size(), 0);
+
+        let inner_pad: Vec<u8> = block_key.iter().map(|byte| byte ^ 0x36).collect();
+        let outer_pad: Vec<u8> = block_key.iter().map(|byte| byte ^ 0x5c).collect();
+        let mut inner = Hasher::new(hash_type);
+        inner.update(&inner_pad);
+        let mut outer = Hasher::new(hash_type);
+        outer.update(&outer_pad);
+
+        Self { inner, outer }
+    }
+
+    /// Feed more message data
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    /// Finish the HMAC and return the tag
+    pub fn finalize(self) -> Vec<u8> {
+        let mut outer = self.outer;
+        outer.update(&self.inner.finalize());
+        outer.finalize()
+    }
+}
+
+/// Incremental hasher for any [`HashType`]
+#[derive(Clone)]
+pub struct Hasher {
+    state: HasherState,
+}
+
+#[derive(Clone)]
+enum HasherState {
+    Sha1(Sha1),
+    Sha224(Sha224),
+    Sha256(Sha256),
+    Sha384(Sha384),
+    Sha512(Sha512),
+    Sha3_224(Sha3_224),
+    Sha3_256(Sha3_256),
+    Sha3_384(Sha3_384),
+    Sha3_512(Sha3_512),
+    Sm3(Sm3),
+}
+
+impl Hasher {
+    pub fn new(hash_type: HashType) -> Self {
+        let state = match hash_type {
+            HashType::Sha1 => HasherState::Sha1(Sha1::new()),
+            HashType::Sha224 => HasherState::Sha224(Sha224::new()),
+            HashType::Sha256 => HasherState::Sha256(Sha256::new()),
+            HashType::Sha384 => HasherState::Sha384(Sha384::new()),
+            HashType::Sha512 => HasherState::Sha512(Sha512::new()),
+            HashType::Sha3_224 => HasherState::Sha3_224(Sha3_224::new()),
+            HashType::Sha3_256 => HasherState::Sha3_256(Sha3_256::new()),
+            HashType::Sha3_384 => HasherState::Sha3_384(Sha3_384::new()),
+            HashType::Sha3_512 => HasherState::Sha3_512(Sha3_512::new()),
+            HashType::Sm3 => HasherState::Sm3(Sm3::new()),
+        };
+        Self { state }
+    }
+
+    /// Feed more data
+    pub fn update(&mut self, data: &[u8]) {
+        match &mut self.state {
+            HasherState::Sha1(hasher) => hasher.update(data),
+            HasherState::Sha224(hasher) => hasher.update(data),
+            HasherState::Sha256(hasher) => hasher.update(data),
+            HasherState::Sha384(hasher) => hasher.update(data),
+            HasherState::Sha512(hasher) => hasher.update(data),
+            HasherState::Sha3_224(hasher) => hasher.update(data),
+            HasherState::Sha3_256(hasher) => hasher.update(data),
+            HasherState::Sha3_384(hasher) => hasher.update(data),
+            HasherState::Sha3_512(hasher) => hasher.update(data),
+            HasherState::Sm3(hasher) => hasher.update(data),
         }
-    };
+    }
 
-    Ok(result)
+    /// Finish the hash and return the digest
+    pub fn finalize(self) -> Vec<u8> {
+        match self.state {
+            HasherState::Sha1(hasher) => hasher.finalize().to_vec(),
+            HasherState::Sha224(hasher) => hasher.finalize().to_vec(),
+            HasherState::Sha256(hasher) => hasher.finalize().to_vec(),
+            HasherState::Sha384(hasher) => hasher.finalize().to_vec(),
+            HasherState::Sha512(hasher) => hasher.finalize().to_vec(),
+            HasherState::Sha3_224(hasher) => hasher.finalize().to_vec(),
+            HasherState::Sha3_256(hasher) => hasher.finalize().to_vec(),
+            HasherState::Sha3_384(hasher) => hasher.finalize().to_vec(),
+            HasherState::Sha3_512(hasher) => hasher.finalize().to_vec(),
+            HasherState::Sm3(hasher) => hasher.finalize().to_vec(),
+        }
+    }
 }
 
 fn to_hex_string<T: AsRef<[u8]>>(bytes: T) -> String {
@@ -110,4 +408,74 @@ fn to_hex_string<T: AsRef<[u8]>>(bytes: T) -> String {
         write!(hex_string, "{:02x}", byte).unwrap();
     }
     hex_string
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LARGE_KEY_MESSAGE: &[u8] = b"Test Using Larger Than Block-Size Key - Hash Key First";
+
+    #[test]
+    fn rfc_2202_hmac_sha1() {
+        assert_eq!(hmac_bytes(&[0x0b; 20], b"Hi There", HashType::Sha1), "b617318655057264e28bc0b6fb378c8ef146be00");
+        assert_eq!(
+            hmac_bytes(b"Jefe", b"what do ya want for nothing?", HashType::Sha1),
+            "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79"
+        );
+        assert_eq!(hmac_bytes(&[0xaa; 80], LARGE_KEY_MESSAGE, HashType::Sha1), "aa4ae5e15272d00e95705637ce8a3b55ed402112");
+    }
+
+    #[test]
+    fn rfc_4231_hmac_sha2() {
+        // Test case 1
+        let key = [0x0b; 20];
+        assert_eq!(hmac_bytes(&key, b"Hi There", HashType::Sha224), "896fb1128abbdf196832107cd49df33f47b4b1169912ba4f53684b22");
+        assert_eq!(
+            hmac_bytes(&key, b"Hi There", HashType::Sha256),
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"
+        );
+        assert_eq!(
+            hmac_bytes(&key, b"Hi There", HashType::Sha384),
+            "afd03944d84895626b0825f4ab46907f15f9dadbe4101ec682aa034c7cebc59cfaea9ea9076ede7f4af152e8b2fa9cb6"
+        );
+        assert_eq!(
+            hmac_bytes(&key, b"Hi There", HashType::Sha512),
+            "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cdedaa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854"
+        );
+
+        // Test case 2
+        assert_eq!(
+            hmac_bytes(b"Jefe", b"what do ya want for nothing?", HashType::Sha256),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            hmac_bytes(b"Jefe", b"what do ya want for nothing?", HashType::Sha512),
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"
+        );
+    }
+
+    #[test]
+    fn rfc_4231_key_longer_than_block() {
+        // Test case 6: the 131-byte key is hashed first for both 64- and 128-byte blocks
+        let key = [0xaa; 131];
+        assert_eq!(
+            hmac_bytes(&key, LARGE_KEY_MESSAGE, HashType::Sha256),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+        assert_eq!(
+            hmac_bytes(&key, LARGE_KEY_MESSAGE, HashType::Sha512),
+            "80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f3526b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598"
+        );
+    }
+
+    #[test]
+    fn streaming_hmac_matches_one_shot() {
+        let key = [0xaa; 131];
+        let mut hmac = Hmac::new(HashType::Sha512, &key);
+        for chunk in LARGE_KEY_MESSAGE.chunks(5) {
+            hmac.update(chunk);
+        }
+        assert_eq!(to_hex_string(hmac.finalize()), hmac_bytes(&key, LARGE_KEY_MESSAGE, HashType::Sha512));
+    }
+}
\ No newline at end of file
@@ -1,3 +1,7 @@
 mod hash;
+mod sm3;
+mod verify;
  
-pub use hash::{InputType, HashType, calculate_hash}; 
\ No newline at end of file
+pub use hash::{InputType, HashType, Hasher, Hmac, calculate_hash, calculate_hmac, digest_matrix, digest_matrix_file, digest_matrix_reader, hash_bytes, hash_file, hash_reader, hmac_bytes, hmac_file, hmac_reader, CHUNK_SIZE};
+pub use sm3::Sm3;
+pub use verify::{ExpectedDigest, digests_equal};
\ No newline at end of file
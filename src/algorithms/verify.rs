@@ -0,0 +1,168 @@
+use subtle::ConstantTimeEq;
+use super::hash::HashType;
+use crate::error::{Error, Result};
+
+/// Expected digest, e.g. a checksum from a vendor advisory
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpectedDigest {
+    pub digest: Vec<u8>,
+    pub algorithm: Option<HashType>, // Algorithm named next to the digest, if any
+}
+
+impl ExpectedDigest {
+    /// Parse an expected digest
+    ///
+    /// Accepts plain hex (optionally `0x`-prefixed, space or colon separated), `sha256:hex`,
+    /// `sha256sum` output (`hex  file`) and BSD-style `SHA256 (file) = hex` lines.
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        let (algorithm, rest) = Self::split_algorithm(text);
+
+        // Hex split into groups, or a digest followed by a file name
+        let tokens: Vec<&str> = rest.split_whitespace().collect();
+        let hex_text = if tokens.iter().all(|token| Self::is_hex(token)) {
+            tokens.concat()
+        } else {
+            tokens.first().copied().unwrap_or_default().to_string()
+        };
+        let hex_text = hex_text
+            .trim_start_matches("0x")
+            .trim_start_matches("0X")
+            .replace(':', "");
+        if hex_text.is_empty() {
+            return Err(Error::Parse("Expected digest is empty".to_string()));
+        }
+        let digest = hex::decode(&hex_text)?;
+
+        match algorithm {
+            Some(hash_type) if hash_type.output_size() != digest.len() => Err(Error::Parse(format!(
+                "{} digests are {} bytes, got {}",
+                hash_type.name(),
+                hash_type.output_size(),
+                digest.len()
+            ))),
+            None if HashType::for_output_size(digest.len()).is_empty() => Err(Error::Parse(format!(
+                "No supported algorithm produces {}-byte digests",
+                digest.len()
+            ))),
+            _ => Ok(Self { digest, algorithm }),
+        }
+    }
+
+    /// Algorithms that may have produced the digest, most likely first
+    pub fn candidates(&self) -> Vec<HashType> {
+        match self.algorithm {
+            Some(hash_type) => vec![hash_type],
+            None => HashType::for_output_size(self.digest.len()),
+        }
+    }
+
+    /// Compare a computed digest with the expected one in constant time
+    pub fn matches(&self, digest: &[u8]) -> bool {
+        digests_equal(&self.digest, digest)
+    }
+
+    /// Compare a computed hex digest with the expected one in constant time
+    pub fn matches_hex(&self, digest_hex: &str) -> bool {
+        hex::decode(digest_hex).is_ok_and(|digest| self.matches(&digest))
+    }
+
+    /// Split off a leading algorithm name, as in `sha256:hex` or `SHA256 (file) = hex`
+    fn split_algorithm(text: &str) -> (Option<HashType>, &str) {
+        if let Some((head, digest)) = text.rsplit_once(" = ") {
+            if let Some((name, _file)) = head.split_once('(') {
+                if let Some(hash_type) = HashType::from_name(name.trim()) {
+                    return (Some(hash_type), digest);
+                }
+            }
+        }
+        if let Some((name, digest)) = text.split_once([':', '=']) {
+            if let Some(hash_type) = HashType::from_name(name.trim()) {
+                return (Some(hash_type), digest);
+            }
+        }
+        (None, text)
+    }
+
+    fn is_hex(token: &str) -> bool {
+        let token = token.trim_start_matches("0x").trim_start_matches("0X");
+        !token.is_empty() && token.chars().all(|c| c.is_ascii_hexdigit() || c == ':')
+    }
+}
+
+/// Compare two digests in constant time with respect to their contents
+pub fn digests_equal(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // SHA-256 of "abc"
+    const ABC_SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    fn abc_sha256() -> Vec<u8> {
+        hex::decode(ABC_SHA256).unwrap()
+    }
+
+    #[test]
+    fn parses_plain_hex() {
+        let grouped: Vec<String> = ABC_SHA256.as_bytes().chunks(8).map(|group| String::from_utf8_lossy(group).to_string()).collect();
+        let colons: Vec<String> = abc_sha256().iter().map(|byte| format!("{:02X}", byte)).collect();
+        for text in [
+            ABC_SHA256.to_string(),
+            format!("  0x{}\n", ABC_SHA256.to_uppercase()),
+            grouped.join(" "),
+            colons.join(":"),
+        ] {
+            let expected = ExpectedDigest::parse(&text).unwrap();
+            assert_eq!(expected.digest, abc_sha256(), "{}", text);
+            assert_eq!(expected.algorithm, None);
+            assert_eq!(expected.candidates(), [HashType::Sha256, HashType::Sha3_256, HashType::Sm3]);
+        }
+    }
+
+    #[test]
+    fn parses_checksum_lines() {
+        let prefixed = ExpectedDigest::parse(&format!("sha256:{}", ABC_SHA256)).unwrap();
+        assert_eq!(prefixed.algorithm, Some(HashType::Sha256));
+        assert_eq!(prefixed.candidates(), [HashType::Sha256]);
+
+        let bsd = ExpectedDigest::parse(&format!("SHA3-256 (image.iso) = {}", ABC_SHA256)).unwrap();
+        assert_eq!((bsd.algorithm, bsd.digest), (Some(HashType::Sha3_256), abc_sha256()));
+
+        let sha256sum = ExpectedDigest::parse(&format!("{}  image 2.iso", ABC_SHA256)).unwrap();
+        assert_eq!((sha256sum.algorithm, sha256sum.digest), (None, abc_sha256()));
+    }
+
+    #[test]
+    fn rejects_invalid_digests() {
+        assert!(matches!(ExpectedDigest::parse("  "), Err(Error::Parse(_))));
+        assert!(matches!(ExpectedDigest::parse("xyz"), Err(Error::InvalidHex(_))));
+        assert!(matches!(ExpectedDigest::parse("abc"), Err(Error::InvalidHex(_))));
+        match ExpectedDigest::parse("112233") {
+            Err(Error::Parse(message)) => assert_eq!(message, "No supported algorithm produces 3-byte digests"),
+            other => panic!("unexpected {:?}", other),
+        }
+        match ExpectedDigest::parse(&format!("sha1:{}", ABC_SHA256)) {
+            Err(Error::Parse(message)) => assert_eq!(message, "SHA-1 digests are 20 bytes, got 32"),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn matches_computed_digests() {
+        let expected = ExpectedDigest::parse(ABC_SHA256).unwrap();
+        assert!(expected.matches(&abc_sha256()));
+        assert!(expected.matches_hex(&ABC_SHA256.to_uppercase()));
+        assert!(!expected.matches(&abc_sha256()[..31]));
+        assert!(!expected.matches_hex("not hex"));
+
+        let mut other = abc_sha256();
+        other[31] ^= 1;
+        assert!(!expected.matches(&other));
+        assert!(digests_equal(&[], &[]));
+        assert!(!digests_equal(&[1], &[1, 2]));
+    }
+}
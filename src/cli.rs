@@ -0,0 +1,650 @@
+use std::fs;
+use std::io::{self, Read};
+use serde_json::{json, Value};
+use eggs::{
+    HashType, hash_bytes, hash_file, hash_reader, DigestPadding, HashAlgorithm, PcrSimulator, PCR_COUNT,
+    GoldenValues, VerificationReport, MeasurementFileProcessor, LinuxTpm,
+};
+
+/// Exit codes
+pub const EXIT_SUCCESS: i32 = 0;
+pub const EXIT_MISMATCH: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_INPUT_ERROR: i32 = 3;
+
+const USAGE: &str = "\
+Usage:
+  eggs                                   Start the graphical interface
+  eggs hash [OPTIONS] [FILE...]          Hash files, or stdin when no FILE (or '-') is given
+  eggs replay [OPTIONS] FILE             Replay a measurement file or table, event log (binary or
+                                         tpm2_eventlog output) or IMA list
+  eggs machine [OPTIONS]                 Replay the event log of the local TPM and compare the
+                                         replayed PCRs with the live values (Linux, usually root)
+
+Hash options:
+  -a, --algorithm ALG    Hash algorithm (default sha256): sha1, sha224, sha256, sha384, sha512,
+                         sha3-224, sha3-256, sha3-384, sha3-512, sm3
+      --hex              Treat input as hexadecimal text instead of raw bytes
+
+Replay options:
+  -p, --pcr N            PCR to replay into (required for plain measurement files)
+                         or to report (event logs, measurement tables and IMA lists)
+  -b, --bank ALG         PCR bank (default sha256): sha1, sha256, sha384, sha512,
+                         sha3-256, sha3-384, sha3-512, sm3
+  -e, --expected HEX     Expected value of the PCR given with --pcr
+  -g, --golden FILE      Expected PCR values ('[bank:]pcr: value' lines or tpm2_pcrread output);
+                         tpm2_eventlog output is checked against its own pcrs section by default
+      --zero-pad         Left-pad short digests of plain measurement files with zero bytes
+  -l, --locality N       Locality of the extends, 0-4 (default 0); PCR 17-22 cannot be
+                         extended from locality 0
+      --startup-locality N
+                         Locality of TPM2_Startup, 0, 3 or 4 (default 0, or the event log's
+                         StartupLocality event); sets the last byte of PCR 0's initial value
+      --dynamic-launch FILE
+                         Measure FILE (e.g. the SINIT ACM or secure loader) into PCR 17 with a
+                         dynamic launch, then extend the file at locality 3 (or --locality)
+                         without resetting PCR 17-22
+
+Machine options:
+      --event-log FILE   Binary event log
+                         (default /sys/kernel/security/tpm0/binary_bios_measurements)
+      --pcr-dir DIR      TPM directory with pcr-<alg>/<n> files (default /sys/class/tpm/tpm0)
+
+Common options:
+  -f, --format FORMAT    Output format: text (default) or json
+  -h, --help             Show this help
+
+Exit codes:
+  0  success, all expected values match
+  1  an expected value does not match, or an IMA template hash does not match its data
+  2  invalid command line
+  3  input could not be read or parsed
+";
+
+/// Output format
+#[derive(PartialEq, Clone, Copy)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// Command-line failure with its exit code
+struct CliError {
+    code: i32,
+    message: String,
+}
+
+impl CliError {
+    fn usage(message: impl Into<String>) -> Self {
+        Self { code: EXIT_USAGE, message: message.into() }
+    }
+
+    fn input(message: impl Into<String>) -> Self {
+        Self { code: EXIT_INPUT_ERROR, message: message.into() }
+    }
+}
+
+/// Run the command line interface, returning the process exit code
+pub fn run(args: &[String]) -> i32 {
+    attach_console();
+
+    let result = match args.first().map(String::as_str) {
+        Some("hash") => run_hash(&args[1..]),
+        Some("replay") => run_replay(&args[1..]),
+        Some("machine") => run_machine(&args[1..]),
+        Some("-h") | Some("--help") | Some("help") => {
+            print!("{}", USAGE);
+            Ok(EXIT_SUCCESS)
+        },
+        Some(other) => Err(CliError::usage(format!("Unknown command '{}'", other))),
+        None => Err(CliError::usage("Missing command")),
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("eggs: {}", err.message);
+            if err.code == EXIT_USAGE {
+                eprintln!("Run 'eggs --help' for usage.");
+            }
+            err.code
+        }
+    }
+}
+
+/// The GUI subsystem has no console on Windows; reuse the one of the calling shell
+#[cfg(windows)]
+fn attach_console() {
+    use winapi::um::wincon::{AttachConsole, ATTACH_PARENT_PROCESS};
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+/// Get the value following an option
+fn option_value<'a>(args: &'a [String], i: &mut usize) -> Result<&'a str, CliError> {
+    let option = &args[*i];
+    *i += 1;
+    args.get(*i)
+        .map(String::as_str)
+        .ok_or_else(|| CliError::usage(format!("Option '{}' requires a value", option)))
+}
+
+fn parse_format(value: &str) -> Result<OutputFormat, CliError> {
+    match value {
+        "text" => Ok(OutputFormat::Text),
+        "json" => Ok(OutputFormat::Json),
+        other => Err(CliError::usage(format!("Unknown output format '{}'", other))),
+    }
+}
+
+fn parse_locality(value: &str) -> Result<u8, CliError> {
+    value.parse::<u8>().map_err(|_| CliError::usage(format!("Invalid locality '{}'", value)))
+}
+
+/// `eggs hash`
+fn run_hash(args: &[String]) -> Result<i32, CliError> {
+    let mut hash_type = HashType::Sha256;
+    let mut hex_input = false;
+    let mut format = OutputFormat::Text;
+    let mut files = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "-a" | "--algorithm" => {
+                let name = option_value(args, &mut i)?;
+                hash_type = HashType::from_name(name)
+                    .ok_or_else(|| CliError::usage(format!("Unknown hash algorithm '{}'", name)))?;
+            },
+            "--hex" => hex_input = true,
+            "-f" | "--format" => format = parse_format(option_value(args, &mut i)?)?,
+            "-h" | "--help" => {
+                print!("{}", USAGE);
+                return Ok(EXIT_SUCCESS);
+            },
+            other if other.starts_with('-') && other != "-" => {
+                return Err(CliError::usage(format!("Unknown option '{}'", other)));
+            },
+            file => files.push(file.to_string()),
+        }
+        i += 1;
+    }
+    if files.is_empty() {
+        files.push("-".to_string());
+    }
+
+    let mut results = Vec::new();
+    for file in &files {
+        let name = if file == "-" { "stdin" } else { file.as_str() };
+        let digest = if hex_input {
+            let data = if file == "-" {
+                let mut data = Vec::new();
+                io::stdin().read_to_end(&mut data).map(|_| data)
+            } else {
+                fs::read(file)
+            }
+            .map_err(|e| CliError::input(format!("Error reading {}: {}", name, e)))?;
+            let text = String::from_utf8_lossy(&data);
+            let hex_only: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+            let data = hex::decode(hex_only)
+                .map_err(|e| CliError::input(format!("Invalid hex input in {}: {}", name, e)))?;
+            hash_bytes(&data, hash_type)
+        } else if file == "-" {
+            hash_reader(io::stdin().lock(), hash_type, |_| true)
+                .map_err(|e| CliError::input(format!("Error reading {}: {}", name, e)))?
+        } else {
+            // Stream the file so large images are never loaded into memory
+            hash_file(file, hash_type, |_| true)
+                .map_err(|e| CliError::input(format!("Error reading {}: {}", name, e)))?
+        };
+
+        results.push((file.clone(), digest));
+    }
+
+    match format {
+        OutputFormat::Text => {
+            for (file, digest) in &results {
+                println!("{}  {}", digest, file);
+            }
+        },
+        OutputFormat::Json => {
+            let entries: Vec<Value> = results
+                .iter()
+                .map(|(file, digest)| json!({
+                    "file": file,
+                    "algorithm": hash_type.name(),
+                    "digest": digest,
+                }))
+                .collect();
+            println!("{}", Value::Array(entries));
+        },
+    }
+
+    Ok(EXIT_SUCCESS)
+}
+
+/// `eggs replay`
+fn run_replay(args: &[String]) -> Result<i32, CliError> {
+    let mut pcr_index = None;
+    let mut algorithm = HashAlgorithm::SHA256;
+    let mut expected = None;
+    let mut golden_file = None;
+    let mut padding = DigestPadding::Exact;
+    let mut locality = None;
+    let mut startup_locality = None;
+    let mut launch_file = None;
+    let mut format = OutputFormat::Text;
+    let mut file = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "-p" | "--pcr" => {
+                let value = option_value(args, &mut i)?;
+                match value.parse::<usize>() {
+                    Ok(index) if index < PCR_COUNT => pcr_index = Some(index),
+                    _ => return Err(CliError::usage(format!("Invalid PCR index '{}'", value))),
+                }
+            },
+            "-b" | "--bank" => {
+                let name = option_value(args, &mut i)?;
+                algorithm = name.parse::<HashAlgorithm>()
+                    .map_err(|_| CliError::usage(format!("Unknown bank '{}'", name)))?;
+            },
+            "-e" | "--expected" => expected = Some(option_value(args, &mut i)?.to_string()),
+            "-g" | "--golden" => golden_file = Some(option_value(args, &mut i)?.to_string()),
+            "--zero-pad" => padding = DigestPadding::ZeroPad,
+            "-l" | "--locality" => locality = Some(parse_locality(option_value(args, &mut i)?)?),
+            "--startup-locality" => startup_locality = Some(parse_locality(option_value(args, &mut i)?)?),
+            "--dynamic-launch" => launch_file = Some(option_value(args, &mut i)?.to_string()),
+            "-f" | "--format" => format = parse_format(option_value(args, &mut i)?)?,
+            "-h" | "--help" => {
+                print!("{}", USAGE);
+                return Ok(EXIT_SUCCESS);
+            },
+            other if other.starts_with('-') => {
+                return Err(CliError::usage(format!("Unknown option '{}'", other)));
+            },
+            path if file.is_none() => file = Some(path.to_string()),
+            _ => return Err(CliError::usage("Only one file can be replayed")),
+        }
+        i += 1;
+    }
+    let file = file.ok_or_else(|| CliError::usage("Missing measurement file"))?;
+
+    // Collect expected values
+    let mut golden_values = GoldenValues::default();
+    if let Some(ref path) = golden_file {
+        let text = fs::read_to_string(path)
+            .map_err(|e| CliError::input(format!("Error reading {}: {}", path, e)))?;
+        golden_values = GoldenValues::parse(&text, &algorithm)
+            .map_err(|e| CliError::input(format!("Invalid golden values in {}: {}", path, e)))?;
+    }
+    if let Some(ref hex_value) = expected {
+        let pcr_index = pcr_index.ok_or_else(|| CliError::usage("--expected requires --pcr"))?;
+        let text = format!("{}: {}", pcr_index, hex_value);
+        let parsed = GoldenValues::parse(&text, &algorithm)
+            .map_err(|e| CliError::usage(format!("Invalid expected value: {}", e)))?;
+        golden_values.values.extend(parsed.values);
+    }
+
+    // Replay the file into a simulator with the selected bank only
+    let mut processor = MeasurementFileProcessor::new();
+    processor.set_padding(padding);
+    processor.load_file(&file).map_err(|e| CliError::input(e.to_string()))?;
+    let mut simulator = PcrSimulator::new(algorithm.clone());
+    simulator.set_startup_locality(startup_locality.unwrap_or(0)).map_err(|e| CliError::usage(e.to_string()))?;
+    simulator.set_locality(locality.unwrap_or(0)).map_err(|e| CliError::usage(e.to_string()))?;
+    let launch_data = match launch_file {
+        Some(ref path) => Some(fs::read(path).map_err(|e| CliError::input(format!("Error reading {}: {}", path, e)))?),
+        None => None,
+    };
+
+    // Skipping a line would silently give a different PCR value
+    if !processor.get_diagnostics().is_empty() {
+        let lines: Vec<String> = processor
+            .get_diagnostics()
+            .iter()
+            .map(|diagnostic| format!("  line {}: {}", diagnostic.line_number, diagnostic.reason))
+            .collect();
+        return Err(CliError::input(format!("Invalid lines in {}:\n{}", file, lines.join("\n"))));
+    }
+    
+    // tpm2_eventlog output carries the PCR values read with it; tpm2_pcrread output nothing else
+    if let Some(expected_values) = processor.get_expected_values() {
+        if processor.get_event_log().is_none() {
+            return Err(CliError::usage(format!("{} only holds PCR values; pass it with --golden", file)));
+        }
+        if golden_file.is_none() && expected.is_none() {
+            golden_values.values = expected_values.values
+                .iter()
+                .filter(|golden| golden.algorithm == algorithm)
+                .cloned()
+                .collect();
+        }
+    }
+
+    let log_events = if processor.get_event_log().is_some() {
+        Some(processor.event_log_events())
+    } else if processor.get_measurement_table().is_some() {
+        Some(processor.measurement_table_events())
+    } else {
+        None
+    };
+
+    // A dynamic launch follows startup, and the file's events follow the launch at
+    // --locality (a reset returns to locality 0, a launch leaves locality 3)
+    let launched = |simulator: &mut PcrSimulator| -> Result<(), CliError> {
+        if let (Some(path), Some(data)) = (&launch_file, &launch_data) {
+            simulator
+                .dynamic_launch(format!("Dynamic Launch: {}", path), data)
+                .map_err(|e| CliError::input(e.to_string()))?;
+        }
+        if let Some(locality) = locality {
+            simulator.set_locality(locality).map_err(|e| CliError::usage(e.to_string()))?;
+        }
+        Ok(())
+    };
+
+    let mut template_mismatches: Vec<String> = Vec::new();
+    let replayed_pcrs: Vec<usize> = if let Some(events) = log_events {
+        simulator.reset_for_events(&events, startup_locality).map_err(|e| CliError::input(e.to_string()))?;
+        launched(&mut simulator)?;
+        simulator
+            .extend_events(events)
+            .map_err(|e| CliError::input(e.to_string()))?;
+        match pcr_index {
+            Some(index) => vec![index],
+            None => touched_pcrs(&simulator),
+        }
+    } else if let Some(ima_list) = processor.get_ima_list() {
+        // Entries whose template hash does not cover their data have been tampered with
+        template_mismatches = ima_list
+            .mismatched_entries()
+            .iter()
+            .map(|entry| format!(
+                "{} {}: template hash does not match the template data of {}",
+                if ima_list.binary { "record" } else { "line" },
+                entry.position,
+                entry.file_name
+            ))
+            .collect();
+
+        // Every entry goes into its own PCR, like replay_events with the launch after the reset
+        let events = processor.ima_events(&simulator.active_algorithms());
+        simulator.reset_for_events(&events, startup_locality).map_err(|e| CliError::input(e.to_string()))?;
+        launched(&mut simulator)?;
+        simulator.extend_events(events).map_err(|e| CliError::input(e.to_string()))?;
+        match pcr_index {
+            Some(index) => vec![index],
+            None => touched_pcrs(&simulator),
+        }
+    } else {
+        let pcr_index = pcr_index
+            .ok_or_else(|| CliError::usage("--pcr is required for plain measurement files"))?;
+        let events = processor
+            .get_parsed_measurements()
+            .iter()
+            .map(|measurement| processor.measurement_event(measurement, pcr_index, &algorithm))
+            .collect::<eggs::Result<Vec<_>>>()
+            .map_err(|e| CliError::input(e.to_string()))?;
+        launched(&mut simulator)?;
+        simulator.extend_events(events).map_err(|e| CliError::input(e.to_string()))?;
+        vec![pcr_index]
+    };
+
+    let report = simulator.verify(&golden_values);
+    let pcr_values: Vec<(usize, String, usize)> = replayed_pcrs
+        .iter()
+        .map(|&index| {
+            let value = simulator.get_pcr_hex_string(&algorithm, index).unwrap_or_default();
+            let events = simulator.measurement_log().iter().filter(|e| e.pcr_index == index).count();
+            (index, value, events)
+        })
+        .collect();
+
+    match format {
+        OutputFormat::Text => {
+            for (index, value, events) in &pcr_values {
+                println!("PCR{} {}: {} ({} events)", index, algorithm.name(), value, events);
+            }
+            print_verification(&report);
+            for mismatch in &template_mismatches {
+                println!("IMA {}", mismatch);
+            }
+        },
+        OutputFormat::Json => {
+            let pcrs: Vec<Value> = pcr_values
+                .iter()
+                .map(|(index, value, events)| json!({
+                    "pcr": index,
+                    "bank": algorithm.name(),
+                    "value": value,
+                    "events": events,
+                }))
+                .collect();
+            let output = json!({
+                "file": file,
+                "pcrs": pcrs,
+                "verification": verification_json(&report),
+                "errors": report.errors,
+                "template_mismatches": template_mismatches,
+                "match": report.all_match() && template_mismatches.is_empty(),
+            });
+            println!("{}", output);
+        },
+    }
+
+    Ok(if report.all_match() && template_mismatches.is_empty() { EXIT_SUCCESS } else { EXIT_MISMATCH })
+}
+
+/// `eggs machine`
+fn run_machine(args: &[String]) -> Result<i32, CliError> {
+    let mut tpm = LinuxTpm::default();
+    let mut format = OutputFormat::Text;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--event-log" => tpm.event_log_path = option_value(args, &mut i)?.into(),
+            "--pcr-dir" => tpm.pcr_dir = option_value(args, &mut i)?.into(),
+            "-f" | "--format" => format = parse_format(option_value(args, &mut i)?)?,
+            "-h" | "--help" => {
+                print!("{}", USAGE);
+                return Ok(EXIT_SUCCESS);
+            },
+            other => return Err(CliError::usage(format!("Unknown option '{}'", other))),
+        }
+        i += 1;
+    }
+
+    let verification = tpm.verify().map_err(|e| CliError::input(e.to_string()))?;
+    let report = &verification.report;
+    let banks: Vec<&str> = verification.simulator.banks().iter().map(|bank| bank.algorithm.name()).collect();
+
+    match format {
+        OutputFormat::Text => {
+            println!(
+                "Replayed {} events of {} in banks {}",
+                verification.simulator.measurement_log().len(),
+                tpm.event_log_path.display(),
+                banks.join(", ")
+            );
+            print_verification(report);
+        },
+        OutputFormat::Json => {
+            let output = json!({
+                "event_log": tpm.event_log_path.to_string_lossy(),
+                "pcr_dir": tpm.pcr_dir.to_string_lossy(),
+                "banks": banks,
+                "events": verification.simulator.measurement_log().len(),
+                "verification": verification_json(report),
+                "errors": report.errors,
+                "match": report.all_match(),
+            });
+            println!("{}", output);
+        },
+    }
+
+    Ok(if report.all_match() { EXIT_SUCCESS } else { EXIT_MISMATCH })
+}
+
+/// Print one line per verified PCR
+fn print_verification(report: &VerificationReport) {
+    for result in &report.results {
+        let verdict = if result.is_match() { "MATCH" } else { "MISMATCH" };
+        println!("{} PCR{} {}: {}", verdict, result.pcr_index, result.algorithm.name(), result.summary());
+    }
+    for error in &report.errors {
+        println!("ERROR {}", error);
+    }
+}
+
+fn verification_json(report: &VerificationReport) -> Vec<Value> {
+    report.results
+        .iter()
+        .map(|result| json!({
+            "pcr": result.pcr_index,
+            "bank": result.algorithm.name(),
+            "expected": hex::encode(&result.expected),
+            "replayed": hex::encode(&result.replayed),
+            "match": result.is_match(),
+            "first_divergent_event": result.first_divergent_event,
+            "summary": result.summary(),
+        }))
+        .collect()
+}
+
+/// PCRs extended at least once, in index order
+fn touched_pcrs(simulator: &PcrSimulator) -> Vec<usize> {
+    (0..PCR_COUNT)
+        .filter(|index| simulator.measurement_log().iter().any(|event| event.pcr_index == *index))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    /// Measurement table with one EV_POST_CODE event in PCR 0
+    fn table_file(name: &str) -> (String, Vec<u8>) {
+        let digest = PcrSimulator::hash(&HashAlgorithm::SHA256, b"bios");
+        let path = std::env::temp_dir().join(format!("eggs-cli-{}-{}.txt", name, std::process::id()));
+        fs::write(&path, format!("0 sha256 {} EV_POST_CODE BIOS\n", hex::encode(&digest))).unwrap();
+        (path.to_string_lossy().to_string(), digest)
+    }
+
+    fn pcr0_after(startup_locality: u8, digest: &[u8]) -> String {
+        let mut initial = vec![0u8; 32];
+        initial[31] = startup_locality;
+        hex::encode(PcrSimulator::hash(&HashAlgorithm::SHA256, &[initial, digest.to_vec()].concat()))
+    }
+
+    #[test]
+    fn startup_locality_flag_applies_to_tables() {
+        let (path, digest) = table_file("startup-locality");
+        for startup_locality in [0, 3, 4] {
+            let expected = pcr0_after(startup_locality, &digest);
+            let flag = startup_locality.to_string();
+            let code = run_replay(&args(&["--startup-locality", &flag, "-p", "0", "-e", &expected, &path]));
+            assert!(matches!(code, Ok(EXIT_SUCCESS)), "startup locality {}", startup_locality);
+        }
+
+        // Without the flag the table starts up from locality 0
+        let code = run_replay(&args(&["-p", "0", "-e", &pcr0_after(3, &digest), &path]));
+        assert!(matches!(code, Ok(EXIT_MISMATCH)));
+        fs::remove_file(path).unwrap();
+    }
+
+    /// Write a temporary file for a test
+    fn temp_file(name: &str, content: &str) -> String {
+        let path = std::env::temp_dir().join(format!("eggs-cli-{}-{}.txt", name, std::process::id()));
+        fs::write(&path, content).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    fn exit_code(result: Result<i32, CliError>) -> i32 {
+        result.unwrap_or_else(|err| err.code)
+    }
+
+    #[test]
+    fn commands_and_help() {
+        assert_eq!(run(&args(&["--help"])), EXIT_SUCCESS);
+        assert_eq!(run(&args(&["replay", "--help"])), EXIT_SUCCESS);
+        assert_eq!(run(&args(&["frobnicate"])), EXIT_USAGE);
+        assert_eq!(run(&args(&[])), EXIT_USAGE);
+    }
+
+    #[test]
+    fn invalid_options_are_usage_errors() {
+        let (path, _) = table_file("usage");
+        for replay_args in [
+            vec!["--frobnicate", &path],
+            vec![],
+            vec![&path, &path],
+            vec!["-p", "24", &path],
+            vec!["-p", &path],
+            vec!["-b", "md5", &path],
+            vec!["-f", "xml", &path],
+            vec!["-l", "five", &path],
+            vec!["-e", "00", &path],
+        ] {
+            assert_eq!(exit_code(run_replay(&args(&replay_args))), EXIT_USAGE, "{:?}", replay_args);
+        }
+
+        assert_eq!(exit_code(run_hash(&args(&["-a", "md5", &path]))), EXIT_USAGE);
+        assert_eq!(exit_code(run_hash(&args(&["--frobnicate"]))), EXIT_USAGE);
+        assert_eq!(exit_code(run_machine(&args(&["--pcr-dir"]))), EXIT_USAGE);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn unreadable_input_is_an_input_error() {
+        let missing = std::env::temp_dir().join("eggs-cli-missing").to_string_lossy().to_string();
+        assert_eq!(exit_code(run_replay(&args(&[&missing]))), EXIT_INPUT_ERROR);
+        assert_eq!(exit_code(run_hash(&args(&[&missing]))), EXIT_INPUT_ERROR);
+        assert_eq!(exit_code(run_machine(&args(&["--event-log", &missing]))), EXIT_INPUT_ERROR);
+
+        let (path, _) = table_file("missing-golden");
+        assert_eq!(exit_code(run_replay(&args(&["-g", &missing, &path]))), EXIT_INPUT_ERROR);
+        let golden = temp_file("bad-golden", "0: 1122\n");
+        assert_eq!(exit_code(run_replay(&args(&["-g", &golden, &path]))), EXIT_INPUT_ERROR);
+
+        // A table line that cannot be used fails the replay instead of being skipped
+        let table = temp_file("bad-table", &format!("{}\n0 sha256 1122 EV_POST_CODE short\n", fs::read_to_string(&path).unwrap()));
+        assert_eq!(exit_code(run_replay(&args(&[&table]))), EXIT_INPUT_ERROR);
+        for file in [path, golden, table] {
+            fs::remove_file(file).unwrap();
+        }
+    }
+
+    #[test]
+    fn plain_measurements_need_a_pcr() {
+        let digest = PcrSimulator::hash(&HashAlgorithm::SHA256, b"kernel");
+        let path = temp_file("plain", &format!("{}\n", hex::encode(&digest)));
+        let expected = hex::encode(PcrSimulator::hash(&HashAlgorithm::SHA256, &[vec![0; 32], digest.clone()].concat()));
+
+        assert_eq!(exit_code(run_replay(&args(&[&path]))), EXIT_USAGE);
+        assert_eq!(exit_code(run_replay(&args(&["-p", "8", "-e", &expected, &path]))), EXIT_SUCCESS);
+        assert_eq!(exit_code(run_replay(&args(&["-p", "8", "-e", &"00".repeat(32), &path]))), EXIT_MISMATCH);
+        assert_eq!(exit_code(run_replay(&args(&["-p", "8", "-e", &expected, "-f", "json", &path]))), EXIT_SUCCESS);
+        assert_eq!(exit_code(run_hash(&args(&["-f", "json", &path]))), EXIT_SUCCESS);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn invalid_startup_locality_is_a_usage_error() {
+        let (path, _) = table_file("bad-startup-locality");
+        let err = run_replay(&args(&["--startup-locality", "2", &path])).unwrap_err();
+        assert_eq!(err.code, EXIT_USAGE);
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,110 @@
+use std::fmt;
+use std::io;
+use crate::models::locality::{Localities, PcrOperation};
+use crate::models::pcr_simulator::HashAlgorithm;
+
+/// Errors returned by the eggs library
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Input is not valid hexadecimal
+    InvalidHex(String),
+    /// PCR index outside the simulated range
+    InvalidPcrIndex(usize),
+    /// Digest length does not match the output size of its algorithm
+    DigestLength {
+        algorithm: HashAlgorithm,
+        expected: usize,
+        actual: usize,
+    },
+    /// The bank of an algorithm is not active in the simulator
+    InactiveBank(HashAlgorithm),
+    /// An event has no digest for an active bank
+    MissingDigest {
+        event: usize,
+        algorithm: HashAlgorithm,
+    },
+    /// Locality outside 0-4, or not a valid startup locality
+    InvalidLocality(u8),
+    /// The current locality may not reset or extend a PCR
+    LocalityDenied {
+        operation: PcrOperation,
+        pcr_index: usize,
+        locality: u8,
+        allowed: Localities,
+    },
+    /// DRTM hash data or end without a started hash sequence
+    NoHashSequence,
+    /// Policy command that a TPM would reject
+    InvalidPolicy(String),
+    /// Quote signature that does not verify
+    Signature(String),
+    /// Malformed file or data
+    Parse(String),
+    /// Reading a file failed
+    Io(io::Error),
+    /// The operation was cancelled by the caller
+    Cancelled,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidHex(reason) => write!(f, "Invalid hex input: {}", reason),
+            Error::InvalidPcrIndex(index) => write!(f, "Invalid PCR index: {}", index),
+            Error::DigestLength { algorithm, expected, actual } => write!(
+                f,
+                "{} digest must be {} bytes, got {}",
+                algorithm.name(),
+                expected,
+                actual
+            ),
+            Error::InactiveBank(algorithm) => write!(f, "{} bank is not active", algorithm.name()),
+            Error::MissingDigest { event, algorithm } => {
+                write!(f, "Event {} has no {} digest", event, algorithm.name())
+            },
+            Error::InvalidLocality(locality) => write!(f, "Invalid locality: {}", locality),
+            Error::LocalityDenied { operation, pcr_index, locality, allowed } => write!(
+                f,
+                "PCR {} cannot be {} from locality {} (allowed: {})",
+                pcr_index,
+                match operation {
+                    PcrOperation::Reset => "reset",
+                    PcrOperation::Extend => "extended",
+                },
+                locality,
+                allowed
+            ),
+            Error::NoHashSequence => write!(f, "No DRTM hash sequence was started"),
+            Error::InvalidPolicy(reason) => write!(f, "Invalid policy: {}", reason),
+            Error::Signature(reason) => write!(f, "Invalid signature: {}", reason),
+            Error::Parse(reason) => write!(f, "{}", reason),
+            Error::Io(err) => write!(f, "I/O error: {}", err),
+            Error::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<hex::FromHexError> for Error {
+    fn from(err: hex::FromHexError) -> Self {
+        Error::InvalidHex(err.to_string())
+    }
+}
+
+/// Result type of the eggs library
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,45 @@
+//! Cryptographic hashing and TPM measurement simulation.
+//!
+//! The library behind the `eggs` application. Everything is exported from the crate root:
+//!
+//! - hashing - [`calculate_hash`], [`hash_file`] and [`Hasher`] for every supported [`HashType`], HMACs and digest matrices
+//! - PCRs - the multi-bank [`PcrSimulator`], [`MeasurementEvent`]s, the step-through [`ReplayDebugger`], golden-value verification and saved [`Session`]s
+//! - TPM policies and quotes - the [`PolicyCalculator`] and [`Quote`] verification
+//! - files - [`MeasurementFileProcessor`], [`TcgEventLog`], [`UefiEvent`] data, [`ImaMeasurementList`]s and the local [`LinuxTpm`]
+//!
+//! ```
+//! use eggs::{HashAlgorithm, PcrSimulator};
+//!
+//! let mut simulator = PcrSimulator::with_banks(vec![HashAlgorithm::SHA1, HashAlgorithm::SHA256]);
+//! simulator.add_event_data("kernel".to_string(), b"kernel image", 4)?;
+//! let pcr4 = simulator.get_pcr_hex_string(&HashAlgorithm::SHA256, 4)?;
+//! assert_eq!(pcr4.len(), 64);
+//! # Ok::<(), eggs::Error>(())
+//! ```
+
+mod algorithms;
+mod models;
+mod utils;
+mod error;
+
+pub use error::{Error, Result};
+pub use algorithms::{
+    calculate_hash, calculate_hmac, digest_matrix, digest_matrix_file, digest_matrix_reader, digests_equal,
+    hash_bytes, hash_file, hash_reader, hmac_bytes, hmac_file, hmac_reader, ExpectedDigest, HashType, Hasher,
+    Hmac, InputType, Sm3, CHUNK_SIZE,
+};
+pub use models::export::ExportFormat;
+pub use models::locality::{Localities, PcrAttributes, PcrOperation, LOCALITY_COUNT};
+pub use models::measurement_event::{EventSource, MeasurementEvent};
+pub use models::pcr_simulator::{DigestPadding, HashAlgorithm, PcrBank, PcrSimulator, TaggedDigest, DRTM_PCR, PCR_COUNT};
+pub use models::policy::{marshal_pcr_selection, PcrSelection, PolicyCalculator, PolicyCommand, PolicyStep};
+pub use models::quote::{ClockInfo, Quote, QuotePublicKey, QuoteSignature, QuoteStatus, QuoteVerification};
+pub use models::replay_debugger::{BankExtend, Breakpoint, ReplayDebugger, ReplayStep};
+pub use models::session::{Session, SESSION_VERSION};
+pub use models::verification::{GoldenValue, GoldenValues, PcrVerification, VerificationReport};
+pub use utils::file_processor::{LineDiagnostic, MeasurementFileProcessor, ParsedMeasurement};
+pub use utils::ima::{ImaEntry, ImaMeasurementList, IMA_PCR};
+pub use utils::linux_tpm::{LinuxTpm, MachineVerification, DEFAULT_EVENT_LOG_PATH, DEFAULT_PCR_DIR};
+pub use utils::measurement_table::{MeasurementTable, MeasurementTableLine};
+pub use utils::tcg_event_log::{event_type_from_name, event_type_name, EventLogFormat, SpecIdEvent, TcgEvent, TcgEventLog};
+pub use utils::uefi_event::{EfiGuid, UefiEvent, UefiVariable};
@@ -1,12 +1,16 @@
 #![windows_subsystem = "windows"]
-mod algorithms;
+mod cli;
 mod ui;
-mod models;
-mod utils;
 
 use eframe::egui;
 
 fn main() -> Result<(), eframe::Error> {
+    // Any argument selects the headless command line interface
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args));
+    }
+    
     let options = eframe::NativeOptions {
         initial_window_size: Some(egui::vec2(600.0, 600.0)),
         ..Default::default()
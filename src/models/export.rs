@@ -0,0 +1,295 @@
+use serde_json::{json, Value};
+use crate::models::measurement_event::MeasurementEvent;
+use crate::models::pcr_simulator::{PcrSimulator, PCR_COUNT};
+use crate::models::session::event_to_json;
+use crate::utils::tcg_event_log::event_type_name;
+
+/// Export file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    Yaml,
+}
+
+impl ExportFormat {
+    pub fn all() -> [ExportFormat; 3] {
+        [ExportFormat::Json, ExportFormat::Csv, ExportFormat::Yaml]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "JSON",
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Yaml => "YAML",
+        }
+    }
+
+    /// File extension without the dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Yaml => "yaml",
+        }
+    }
+}
+
+impl PcrSimulator {
+    /// Export every PCR of every active bank
+    ///
+    /// - JSON: `{"pcrs": [{"bank", "pcr", "value", "events"}]}` with lowercase hex values
+    /// - CSV: `bank,pcr,value` rows after a header line
+    /// - YAML: `tpm2_pcrread` output (`sha256:` then `  0 : 0x...` lines, uppercase hex)
+    pub fn export_pcr_values(&self, format: ExportFormat) -> String {
+        match format {
+            ExportFormat::Json => {
+                let pcrs: Vec<Value> = self.banks
+                    .iter()
+                    .flat_map(|bank| {
+                        self.get_all_pcr_values(&bank.algorithm)
+                            .into_iter()
+                            .map(move |(index, value)| json!({
+                                "bank": bank.algorithm.name(),
+                                "pcr": index,
+                                "value": value,
+                                "events": self.extend_count(index, &bank.algorithm),
+                            }))
+                    })
+                    .collect();
+                let output = json!({ "pcrs": pcrs });
+                serde_json::to_string_pretty(&output).expect("a JSON value always serializes")
+            },
+            ExportFormat::Csv => {
+                let mut output = String::from("bank,pcr,value\n");
+                for bank in &self.banks {
+                    for (index, value) in self.get_all_pcr_values(&bank.algorithm) {
+                        output.push_str(&format!("{},{},{}\n", bank.algorithm.name(), index, value));
+                    }
+                }
+                output
+            },
+            ExportFormat::Yaml => self.pcrread_yaml(""),
+        }
+    }
+
+    /// Export the measurement log
+    ///
+    /// - JSON: `{"measurement_log": [...]}` with events as in session files
+    /// - CSV: a measurement table (`pcr,algorithm,digest,event_type,description`, banks joined
+    ///   with `+`) that can be loaded again as a measurement file
+    /// - YAML: `tpm2_eventlog` style `events` followed by the resulting `pcrs`
+    pub fn export_measurement_log(&self, format: ExportFormat) -> String {
+        match format {
+            ExportFormat::Json => {
+                let events: Vec<Value> = self.measurement_log.iter().map(event_to_json).collect();
+                let output = json!({ "measurement_log": events });
+                serde_json::to_string_pretty(&output).expect("a JSON value always serializes")
+            },
+            ExportFormat::Csv => {
+                let mut output = String::from("pcr,algorithm,digest,event_type,description\n");
+                for event in &self.measurement_log {
+                    let algorithms: Vec<&str> = event.digests.iter().map(|tagged| tagged.algorithm.name()).collect();
+                    let digests: Vec<String> = event.digests.iter().map(|tagged| hex::encode(&tagged.digest)).collect();
+                    output.push_str(&format!(
+                        "{},{},{},{},{}\n",
+                        event.pcr_index,
+                        algorithms.join("+"),
+                        digests.join("+"),
+                        event_type_label(event),
+                        csv_field(&event.description)
+                    ));
+                }
+                output
+            },
+            ExportFormat::Yaml => {
+                let mut output = String::from("---\nversion: 2\nevents:\n");
+                for (i, event) in self.measurement_log.iter().enumerate() {
+                    output.push_str(&format!("- EventNum: {}\n", i));
+                    output.push_str(&format!("  PCRIndex: {}\n", event.pcr_index));
+                    if event.event_type.is_some() {
+                        output.push_str(&format!("  EventType: {}\n", event_type_label(event)));
+                    }
+                    output.push_str(&format!("  Description: {}\n", yaml_string(&event.description)));
+                    output.push_str(&format!("  DigestCount: {}\n", event.digests.len()));
+                    output.push_str("  Digests:\n");
+                    for tagged in &event.digests {
+                        output.push_str(&format!("  - AlgorithmId: {}\n", tagged.algorithm.tpm2_name()));
+                        output.push_str(&format!("    Digest: \"{}\"\n", hex::encode(&tagged.digest)));
+                    }
+                    output.push_str(&format!("  EventSize: {}\n", event.event_data.len()));
+                    output.push_str(&format!("  Event: \"{}\"\n", hex::encode(&event.event_data)));
+                }
+                output.push_str("pcrs:\n");
+                output.push_str(&self.pcrread_yaml("  "));
+                output
+            },
+        }
+    }
+
+    /// PCR values in `tpm2_pcrread` format, every line prefixed with `indent`
+    fn pcrread_yaml(&self, indent: &str) -> String {
+        let mut output = String::new();
+        for bank in &self.banks {
+            output.push_str(&format!("{}{}:\n", indent, bank.algorithm.tpm2_name()));
+            for index in 0..PCR_COUNT {
+                output.push_str(&format!(
+                    "{}  {:<2}: 0x{}\n",
+                    indent,
+                    index,
+                    hex::encode_upper(&bank.pcr_values[index])
+                ));
+            }
+        }
+        output
+    }
+}
+
+/// Event type name, or hex number for types without a name
+fn event_type_label(event: &MeasurementEvent) -> String {
+    match event.event_type {
+        None => "-".to_string(),
+        Some(event_type) if event_type_name(event_type) == "UNKNOWN" => format!("0x{:08x}", event_type),
+        Some(event_type) => event_type_name(event_type).to_string(),
+    }
+}
+
+/// Double-quoted YAML scalar; JSON string escapes are valid YAML
+fn yaml_string(text: &str) -> String {
+    Value::from(text).to_string()
+}
+
+/// Quote a CSV field if needed; line breaks become spaces since measurement tables are read line by line
+fn csv_field(text: &str) -> String {
+    let text = text.replace("\r\n", " ").replace(['\n', '\r'], " ");
+    if text.contains([',', '"']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::pcr_simulator::{DigestPadding, HashAlgorithm, TaggedDigest};
+    use crate::utils::measurement_table::MeasurementTable;
+    use crate::utils::tpm2_tools::{is_eventlog_output, is_pcrread_output, parse_eventlog_output, parse_pcrread_output};
+
+    fn event(pcr_index: usize, data: &[u8]) -> MeasurementEvent {
+        let digests = [HashAlgorithm::SHA1, HashAlgorithm::SHA256]
+            .into_iter()
+            .map(|algorithm| TaggedDigest::new(algorithm.clone(), PcrSimulator::hash(&algorithm, data)))
+            .collect();
+        MeasurementEvent::new(pcr_index, digests, String::from_utf8_lossy(data).to_string())
+    }
+
+    fn described(mut event: MeasurementEvent, description: &str) -> MeasurementEvent {
+        event.description = description.to_string();
+        event
+    }
+
+    /// Typed events, one with an unnamed type and descriptions that need quoting
+    fn simulator() -> PcrSimulator {
+        let mut simulator = PcrSimulator::with_banks(vec![HashAlgorithm::SHA1, HashAlgorithm::SHA256]);
+        simulator.apply_event(event(0, b"crtm").with_event_type(8).with_event_data(b"1.00".to_vec())).unwrap();
+        simulator.apply_event(described(event(4, b"shim"), "shim, \"signed\"").with_event_type(0x8000_0003)).unwrap();
+        simulator.apply_event(described(event(7, b"db"), "key: value\nnext").with_event_type(0x1234)).unwrap();
+        simulator
+    }
+
+    fn values(simulator: &PcrSimulator) -> Vec<Vec<Vec<u8>>> {
+        simulator.banks().iter().map(|bank| bank.pcr_values.clone()).collect()
+    }
+
+    #[test]
+    fn pcr_values_yaml_reads_back_as_pcrread_output() {
+        let simulator = simulator();
+        let yaml = simulator.export_pcr_values(ExportFormat::Yaml);
+
+        assert!(yaml.starts_with("sha1:\n  0 : 0x"));
+        assert!(is_pcrread_output(&yaml));
+        let golden_values = parse_pcrread_output(&yaml).unwrap();
+        assert_eq!(golden_values.values.len(), 2 * PCR_COUNT);
+        let report = simulator.verify(&golden_values);
+        assert!(report.all_match(), "{:?}", report.errors);
+    }
+
+    #[test]
+    fn pcr_values_json_and_csv_list_every_bank() {
+        let simulator = simulator();
+        let sha256_pcr4 = hex::encode(&simulator.bank(&HashAlgorithm::SHA256).unwrap().pcr_values[4]);
+
+        let json: Value = serde_json::from_str(&simulator.export_pcr_values(ExportFormat::Json)).unwrap();
+        let pcrs = json["pcrs"].as_array().unwrap();
+        assert_eq!(pcrs.len(), 2 * PCR_COUNT);
+        let pcr4 = pcrs.iter().find(|pcr| pcr["bank"] == "SHA256" && pcr["pcr"] == 4).unwrap();
+        assert_eq!(pcr4["value"], sha256_pcr4.as_str());
+        assert_eq!(pcr4["events"], 1);
+        assert!(pcrs.iter().filter(|pcr| pcr["pcr"] == 5).all(|pcr| pcr["events"] == 0));
+
+        let csv = simulator.export_pcr_values(ExportFormat::Csv);
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows[0], "bank,pcr,value");
+        assert_eq!(rows.len(), 1 + 2 * PCR_COUNT);
+        assert!(rows.contains(&format!("SHA256,4,{}", sha256_pcr4).as_str()));
+    }
+
+    #[test]
+    fn measurement_log_csv_loads_as_measurement_table() {
+        let simulator = simulator();
+        let csv = simulator.export_measurement_log(ExportFormat::Csv);
+        assert!(csv.contains(",EV_S_CRTM_VERSION,"));
+        assert!(csv.contains(",0x00001234,"));
+
+        assert!(MeasurementTable::is_table(&csv));
+        let (table, diagnostics) = MeasurementTable::parse(&csv, DigestPadding::Exact);
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        let events = table.to_measurement_events("log.csv");
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[1].description, "shim, \"signed\"");
+        // Measurement tables are line based, a line break in a description becomes a space
+        assert_eq!(events[2].description, "key: value next");
+        assert_eq!(events.iter().map(|event| event.event_type).collect::<Vec<_>>(), vec![Some(8), Some(0x8000_0003), Some(0x1234)]);
+
+        let mut loaded = PcrSimulator::with_banks(table.algorithms());
+        for event in events {
+            loaded.apply_event(event).unwrap();
+        }
+        assert_eq!(values(&loaded), values(&simulator));
+    }
+
+    #[test]
+    fn measurement_log_yaml_reads_back_as_eventlog_output() {
+        let simulator = simulator();
+        let yaml = simulator.export_measurement_log(ExportFormat::Yaml);
+        assert!(yaml.contains("  Description: \"key: value\\nnext\"\n"));
+
+        assert!(is_eventlog_output(&yaml));
+        let (event_log, pcrs, diagnostics) = parse_eventlog_output(&yaml).unwrap();
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        assert_eq!(event_log.events.len(), 3);
+        for (parsed, event) in event_log.events.iter().zip(simulator.measurement_log()) {
+            assert_eq!(parsed.pcr_index as usize, event.pcr_index);
+            assert_eq!(Some(parsed.event_type), event.event_type);
+            assert_eq!(parsed.event_data, event.event_data);
+            for tagged in &event.digests {
+                assert_eq!(parsed.digest_for(&tagged.algorithm), Some(tagged.digest.as_slice()));
+            }
+        }
+        assert!(simulator.verify(&pcrs).all_match());
+    }
+
+    #[test]
+    fn measurement_log_json_matches_session_events() {
+        let simulator = simulator();
+        let json: Value = serde_json::from_str(&simulator.export_measurement_log(ExportFormat::Json)).unwrap();
+        let events = json["measurement_log"].as_array().unwrap();
+
+        assert_eq!(events.len(), 3);
+        for (exported, event) in events.iter().zip(simulator.measurement_log()) {
+            assert_eq!(exported, &event_to_json(event));
+        }
+        assert_eq!(events[2]["description"], "key: value\nnext");
+    }
+}
@@ -0,0 +1,115 @@
+use std::fmt;
+use crate::models::measurement_event::MeasurementEvent;
+use crate::models::pcr_simulator::PCR_COUNT;
+use crate::utils::tcg_event_log::EV_NO_ACTION;
+
+/// Number of TPM localities (0-4)
+pub const LOCALITY_COUNT: u8 = 5;
+
+/// Signature at the start of the StartupLocality event data
+pub const STARTUP_LOCALITY_SIGNATURE: &[u8] = b"StartupLocality\0";
+
+/// Set of localities, bit `n` standing for locality `n`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Localities(u8);
+
+impl Localities {
+    pub const NONE: Localities = Localities(0);
+    pub const ALL: Localities = Localities(0b1_1111);
+
+    /// Set of the given localities
+    pub const fn of(localities: &[u8]) -> Self {
+        let mut mask = 0;
+        let mut i = 0;
+        while i < localities.len() {
+            mask |= 1 << localities[i];
+            i += 1;
+        }
+        Localities(mask)
+    }
+
+    pub fn contains(&self, locality: u8) -> bool {
+        locality < LOCALITY_COUNT && self.0 & (1 << locality) != 0
+    }
+
+    pub fn to_vec(&self) -> Vec<u8> {
+        (0..LOCALITY_COUNT).filter(|&locality| self.contains(locality)).collect()
+    }
+}
+
+impl fmt::Display for Localities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let localities = self.to_vec();
+        match localities.as_slice() {
+            [] => write!(f, "none"),
+            [first, .., last] if localities.len() as u8 == last - first + 1 => write!(f, "{}-{}", first, last),
+            _ => {
+                let names: Vec<String> = localities.iter().map(|locality| locality.to_string()).collect();
+                write!(f, "{}", names.join(", "))
+            },
+        }
+    }
+}
+
+/// PCR operation limited by locality
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PcrOperation {
+    Reset,
+    Extend,
+}
+
+/// Localities from which a PCR may be reset (TPM2_PCR_Reset) and extended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PcrAttributes {
+    pub reset: Localities,
+    pub extend: Localities,
+}
+
+impl PcrAttributes {
+    /// Attributes of a PCR in the TCG PC Client Platform TPM Profile
+    ///
+    /// | PCR   | Reset      | Extend     |
+    /// |-------|------------|------------|
+    /// | 0-15  | none       | 0-4        |
+    /// | 16    | 0-4        | 0-4        |
+    /// | 17-19 | 4          | 2-4        |
+    /// | 20    | 2, 4       | 1-4        |
+    /// | 21-22 | 2, 4       | 2          |
+    /// | 23    | 0-4        | 0-4        |
+    pub fn pc_client(pcr_index: usize) -> Self {
+        let (reset, extend) = match pcr_index {
+            0..=15 => (Localities::NONE, Localities::ALL),
+            17..=19 => (Localities::of(&[4]), Localities::of(&[2, 3, 4])),
+            20 => (Localities::of(&[2, 4]), Localities::of(&[1, 2, 3, 4])),
+            21 | 22 => (Localities::of(&[2, 4]), Localities::of(&[2])),
+            // PCR 16 (debug) and 23 (application)
+            _ => (Localities::ALL, Localities::ALL),
+        };
+        Self { reset, extend }
+    }
+
+    /// Localities allowed to perform an operation
+    pub fn allowed(&self, operation: PcrOperation) -> Localities {
+        match operation {
+            PcrOperation::Reset => self.reset,
+            PcrOperation::Extend => self.extend,
+        }
+    }
+
+    /// The PC Client attributes of every PCR
+    pub fn pc_client_table() -> Vec<PcrAttributes> {
+        (0..PCR_COUNT).map(Self::pc_client).collect()
+    }
+}
+
+/// Locality of a StartupLocality event (EV_NO_ACTION in PCR 0), the locality
+/// TPM2_Startup was issued from
+pub fn startup_locality(event: &MeasurementEvent) -> Option<u8> {
+    if event.pcr_index != 0 || event.event_type != Some(EV_NO_ACTION) {
+        return None;
+    }
+    match event.event_data.strip_prefix(STARTUP_LOCALITY_SIGNATURE) {
+        Some([locality, ..]) => Some(*locality),
+        _ => None,
+    }
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::error::Result;
+use crate::models::pcr_simulator::{HashAlgorithm, PcrSimulator, TaggedDigest};
+use crate::utils::tcg_event_log;
+use crate::utils::uefi_event::{self, UefiEvent};
+
+/// Origin of a measurement event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventSource {
+    /// Entered by hand
+    Manual,
+    /// Line of a text measurement file (1-based line number)
+    File { path: String, line: usize },
+    /// Record of a binary event log (0-based event index)
+    EventLog { path: String, index: usize },
+}
+
+impl fmt::Display for EventSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventSource::Manual => write!(f, "manual"),
+            EventSource::File { path, line } => write!(f, "{}:{}", path, line),
+            EventSource::EventLog { path, index } => write!(f, "{}#{}", path, index),
+        }
+    }
+}
+
+/// Measurement event - one extend of a PCR, with per-bank digests
+#[derive(Debug, Clone)]
+pub struct MeasurementEvent {
+    pub pcr_index: usize,
+    pub event_type: Option<u32>, // TCG event type, if known
+    pub digests: Vec<TaggedDigest>,
+    pub event_data: Vec<u8>,
+    pub uintn_size: usize, // UEFI UINTN width in bytes, for decoding the event data
+    pub description: String,
+    pub source: EventSource,
+    pub timestamp: u64, // Seconds since the Unix epoch
+}
+
+impl MeasurementEvent {
+    /// Create a manual event recorded now
+    pub fn new(pcr_index: usize, digests: Vec<TaggedDigest>, description: String) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        Self {
+            pcr_index,
+            event_type: None,
+            digests,
+            event_data: Vec::new(),
+            uintn_size: 8,
+            description,
+            source: EventSource::Manual,
+            timestamp,
+        }
+    }
+
+    /// Set the TCG event type
+    pub fn with_event_type(mut self, event_type: u32) -> Self {
+        self.event_type = Some(event_type);
+        self
+    }
+
+    /// Set the raw event data
+    pub fn with_event_data(mut self, event_data: Vec<u8>) -> Self {
+        self.event_data = event_data;
+        self
+    }
+
+    /// Set the UEFI UINTN width in bytes of the log the event came from
+    pub fn with_uintn_size(mut self, uintn_size: usize) -> Self {
+        self.uintn_size = uintn_size;
+        self
+    }
+
+    /// Set the event source
+    pub fn with_source(mut self, source: EventSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Get event type name
+    pub fn event_type_name(&self) -> &'static str {
+        match self.event_type {
+            Some(event_type) => tcg_event_log::event_type_name(event_type),
+            None => "-",
+        }
+    }
+
+    /// Get the digest recorded for the given bank
+    pub fn digest_for(&self, algorithm: &HashAlgorithm) -> Option<&[u8]> {
+        self.digests
+            .iter()
+            .find(|tagged| tagged.algorithm == *algorithm)
+            .map(|tagged| tagged.digest.as_slice())
+    }
+
+    /// Decoded UEFI event data; None for event types that are not decoded
+    pub fn decode_event_data(&self) -> Option<Result<UefiEvent>> {
+        UefiEvent::decode(self.event_type?, &self.event_data, self.uintn_size)
+    }
+
+    /// Banks whose digest is not the hash of the event data; None if the event type's
+    /// digest does not cover its data (e.g. Authenticode hashes of EFI applications) or the
+    /// event has no data
+    pub fn data_digest_mismatches(&self) -> Option<Vec<HashAlgorithm>> {
+        if self.event_data.is_empty() {
+            return None;
+        }
+        let candidates = uefi_event::measured_data(self.event_type?, &self.event_data)?;
+        Some(self.digests
+            .iter()
+            .filter(|tagged| !candidates
+                .iter()
+                .any(|data| PcrSimulator::hash(&tagged.algorithm, data) == tagged.digest))
+            .map(|tagged| tagged.algorithm.clone())
+            .collect())
+    }
+
+    /// Whether the event is extended into a PCR (EV_NO_ACTION events are informational only)
+    pub fn is_extended(&self) -> bool {
+        self.event_type != Some(tcg_event_log::EV_NO_ACTION)
+    }
+
+    /// Format the timestamp as UTC time of day (HH:MM:SS)
+    pub fn time_of_day(&self) -> String {
+        let seconds = self.timestamp % 86_400;
+        format!("{:02}:{:02}:{:02}", seconds / 3600, (seconds / 60) % 60, seconds % 60)
+    }
+}
@@ -1 +1,9 @@
-pub mod pcr_simulator; 
\ No newline at end of file
+pub mod pcr_simulator;
+pub mod measurement_event;
+pub mod export;
+pub mod locality;
+pub mod policy;
+pub mod quote;
+pub mod replay_debugger;
+pub mod session;
+pub mod verification;
\ No newline at end of file
@@ -1,10 +1,14 @@
+use std::str::FromStr;
 use sha2::{Sha256, Sha384, Sha512, Digest};
 use sha1::Sha1;
 use sha3::{Sha3_256, Sha3_384, Sha3_512};
 use libsm::sm3::hash::Sm3Hash;
-use hex;
+use crate::error::{Error, Result};
+use crate::models::locality::{self, PcrAttributes, PcrOperation, LOCALITY_COUNT};
+use crate::models::measurement_event::MeasurementEvent;
 
-#[derive(Debug, Clone)]
+/// Hash algorithm of a PCR bank
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum HashAlgorithm {
     SHA1,
     SHA256,
@@ -16,21 +20,27 @@ pub enum HashAlgorithm {
     SM3,
 }
 
-impl HashAlgorithm {
-    pub fn from_str(algorithm: &str) -> Option<Self> {
+impl FromStr for HashAlgorithm {
+    type Err = Error;
+
+    /// Look up an algorithm by name, e.g. "sha256", "sha3-384" or "sm3_256"
+    fn from_str(algorithm: &str) -> Result<Self> {
         match algorithm.to_lowercase().as_str() {
-            "sha1" => Some(HashAlgorithm::SHA1),
-            "sha256" => Some(HashAlgorithm::SHA256),
-            "sha384" => Some(HashAlgorithm::SHA384),
-            "sha512" => Some(HashAlgorithm::SHA512),
-            "sha3-256" | "sha3_256" => Some(HashAlgorithm::SHA3_256),
-            "sha3-384" | "sha3_384" => Some(HashAlgorithm::SHA3_384),
-            "sha3-512" | "sha3_512" => Some(HashAlgorithm::SHA3_512),
-            "sm3" => Some(HashAlgorithm::SM3),
-            _ => None,
+            "sha1" => Ok(HashAlgorithm::SHA1),
+            "sha256" => Ok(HashAlgorithm::SHA256),
+            "sha384" => Ok(HashAlgorithm::SHA384),
+            "sha512" => Ok(HashAlgorithm::SHA512),
+            "sha3-256" | "sha3_256" => Ok(HashAlgorithm::SHA3_256),
+            "sha3-384" | "sha3_384" => Ok(HashAlgorithm::SHA3_384),
+            "sha3-512" | "sha3_512" => Ok(HashAlgorithm::SHA3_512),
+            "sm3" | "sm3_256" | "sm3-256" => Ok(HashAlgorithm::SM3),
+            _ => Err(Error::Parse(format!("Unknown algorithm '{}'", algorithm))),
         }
     }
-    
+}
+
+impl HashAlgorithm {
+    /// Names of all supported algorithms, accepted by [`str::parse`]
     pub fn all_algorithms() -> Vec<&'static str> {
         vec![
             "SHA1", 
@@ -44,6 +54,7 @@ impl HashAlgorithm {
         ]
     }
     
+    /// Digest size in bytes
     pub fn output_size_bytes(&self) -> usize {
         match self {
             HashAlgorithm::SHA1 => 20,
@@ -52,41 +63,204 @@ impl HashAlgorithm {
             HashAlgorithm::SHA512 | HashAlgorithm::SHA3_512 => 64,
         }
     }
+
+    /// Check the length of a digest for this bank, applying the padding policy
+    pub fn check_digest(&self, mut digest: Vec<u8>, padding: DigestPadding) -> Result<Vec<u8>> {
+        let expected = self.output_size_bytes();
+        if digest.len() < expected && padding == DigestPadding::ZeroPad {
+            let mut padded = vec![0u8; expected - digest.len()];
+            padded.append(&mut digest);
+            digest = padded;
+        }
+
+        if digest.len() != expected {
+            return Err(Error::DigestLength {
+                algorithm: self.clone(),
+                expected,
+                actual: digest.len(),
+            });
+        }
+        Ok(digest)
+    }
+
+    /// Get the TCG algorithm identifier (TPM_ALG_ID)
+    pub fn tcg_alg_id(&self) -> u16 {
+        match self {
+            HashAlgorithm::SHA1 => 0x0004,
+            HashAlgorithm::SHA256 => 0x000B,
+            HashAlgorithm::SHA384 => 0x000C,
+            HashAlgorithm::SHA512 => 0x000D,
+            HashAlgorithm::SM3 => 0x0012,
+            HashAlgorithm::SHA3_256 => 0x0027,
+            HashAlgorithm::SHA3_384 => 0x0028,
+            HashAlgorithm::SHA3_512 => 0x0029,
+        }
+    }
+
+    /// Look up an algorithm by its TCG algorithm identifier (TPM_ALG_ID)
+    pub fn from_tcg_alg_id(alg_id: u16) -> Option<Self> {
+        match alg_id {
+            0x0004 => Some(HashAlgorithm::SHA1),
+            0x000B => Some(HashAlgorithm::SHA256),
+            0x000C => Some(HashAlgorithm::SHA384),
+            0x000D => Some(HashAlgorithm::SHA512),
+            0x0012 => Some(HashAlgorithm::SM3),
+            0x0027 => Some(HashAlgorithm::SHA3_256),
+            0x0028 => Some(HashAlgorithm::SHA3_384),
+            0x0029 => Some(HashAlgorithm::SHA3_512),
+            _ => None,
+        }
+    }
+
+    /// Get display name
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashAlgorithm::SHA1 => "SHA1",
+            HashAlgorithm::SHA256 => "SHA256",
+            HashAlgorithm::SHA384 => "SHA384",
+            HashAlgorithm::SHA512 => "SHA512",
+            HashAlgorithm::SHA3_256 => "SHA3-256",
+            HashAlgorithm::SHA3_384 => "SHA3-384",
+            HashAlgorithm::SHA3_512 => "SHA3-512",
+            HashAlgorithm::SM3 => "SM3",
+        }
+    }
+
+    /// Name used by tpm2-tools, e.g. in `tpm2_pcrread` output
+    pub fn tpm2_name(&self) -> &'static str {
+        match self {
+            HashAlgorithm::SHA1 => "sha1",
+            HashAlgorithm::SHA256 => "sha256",
+            HashAlgorithm::SHA384 => "sha384",
+            HashAlgorithm::SHA512 => "sha512",
+            HashAlgorithm::SHA3_256 => "sha3_256",
+            HashAlgorithm::SHA3_384 => "sha3_384",
+            HashAlgorithm::SHA3_512 => "sha3_512",
+            HashAlgorithm::SM3 => "sm3_256",
+        }
+    }
+}
+/// Number of PCRs in each bank
+pub const PCR_COUNT: usize = 24;
+
+/// PCR extended by a dynamic launch
+pub const DRTM_PCR: usize = 17;
+
+/// Handling of digests shorter than the output size of their bank
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DigestPadding {
+    /// Digests must have exactly the output size
+    #[default]
+    Exact,
+    /// Shorter digests are left-padded with zero bytes
+    ZeroPad,
+}
+
+/// Digest for a single bank (TPMT_HA)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaggedDigest {
+    pub algorithm: HashAlgorithm,
+    pub digest: Vec<u8>,
 }
 
+impl TaggedDigest {
+    /// Create a digest for the bank of `algorithm`
+    pub fn new(algorithm: HashAlgorithm, digest: Vec<u8>) -> Self {
+        Self { algorithm, digest }
+    }
+}
+
+/// PCR bank - the PCR values of a single hash algorithm
 #[derive(Debug, Clone)]
-pub struct PcrSimulator {
+pub struct PcrBank {
     pub algorithm: HashAlgorithm,
     pub pcr_values: Vec<Vec<u8>>,
-    pub measurement_log: Vec<(String, Vec<u8>, usize)>, // (description, measurement, PCR index)
 }
 
-impl PcrSimulator {
+impl PcrBank {
+    /// Create a bank with every PCR at its initial value
     pub fn new(algorithm: HashAlgorithm) -> Self {
-        let output_size = algorithm.output_size_bytes();
-        
-        // Initialize PCR values
-        let mut pcr_values = Vec::new();
-        for i in 0..24 {
-            if i >= 17 && i <= 22 {
-                // PCR 17-22 initial value F
-                pcr_values.push(vec![0xFF; output_size]);
-            } else {
-                // PCR 0-16 and 23 initial value 0
-                pcr_values.push(vec![0u8; output_size]);
-            }
-        }
+        let pcr_values = (0..PCR_COUNT)
+            .map(|i| Self::initial_value(&algorithm, i))
+            .collect();
 
         Self {
             algorithm,
             pcr_values,
-            measurement_log: Vec::new(),
         }
     }
+
+    /// Get the initial value of a PCR after TPM2_Startup from locality 0
+    pub fn initial_value(algorithm: &HashAlgorithm, pcr_index: usize) -> Vec<u8> {
+        Self::startup_value(algorithm, pcr_index, 0)
+    }
+
+    /// Get the value of a PCR after TPM2_Startup from `startup_locality`
+    pub fn startup_value(algorithm: &HashAlgorithm, pcr_index: usize, startup_locality: u8) -> Vec<u8> {
+        let output_size = algorithm.output_size_bytes();
+        if (17..=22).contains(&pcr_index) {
+            // PCR 17-22 initial value F
+            vec![0xFF; output_size]
+        } else {
+            // PCR 0-16 and 23 initial value 0, PCR 0 ends in the startup locality
+            let mut value = vec![0u8; output_size];
+            if pcr_index == 0 {
+                value[output_size - 1] = startup_locality;
+            }
+            value
+        }
+    }
+
+    /// Reset a single PCR to its initial value; `pcr_index` must be below [`PCR_COUNT`]
+    pub(crate) fn reset_pcr(&mut self, pcr_index: usize) {
+        self.pcr_values[pcr_index] = Self::initial_value(&self.algorithm, pcr_index);
+    }
+
+    /// Reset all PCRs to their initial values
+    pub fn reset(&mut self) {
+        for i in 0..self.pcr_values.len() {
+            self.reset_pcr(i);
+        }
+    }
+}
+
+/// PCR simulator - replays measurements into one or more PCR banks
+///
+/// Extends and resets are checked against the current locality using the PC Client
+/// PCR attributes ([`PcrAttributes::pc_client`]).
+#[derive(Debug, Clone)]
+pub struct PcrSimulator {
+    pub(crate) banks: Vec<PcrBank>,
+    pub(crate) measurement_log: Vec<MeasurementEvent>,
+    pub(crate) locality: u8,                 // Locality of extends and resets
+    pub(crate) startup_locality: u8,         // Locality of TPM2_Startup (0, 3 or 4)
+    pub(crate) reset_pcrs: [bool; PCR_COUNT], // PCRs reset to zero since startup
+    pub(crate) hash_sequence: Option<Vec<u8>>, // Data of a DRTM hash sequence in progress
+}
+
+impl PcrSimulator {
+    /// Create a simulator with a single active bank
+    pub fn new(algorithm: HashAlgorithm) -> Self {
+        Self::with_banks(vec![algorithm])
+    }
+
+    /// Create a simulator with several active banks
+    pub fn with_banks(algorithms: Vec<HashAlgorithm>) -> Self {
+        let mut simulator = Self {
+            banks: Vec::new(),
+            measurement_log: Vec::new(),
+            locality: 0,
+            startup_locality: 0,
+            reset_pcrs: [false; PCR_COUNT],
+            hash_sequence: None,
+        };
+        simulator.set_active_banks(algorithms);
+        simulator
+    }
     
-    /// Calculate hash value (according to selected algorithm)
-    pub fn hash(&self, data: &[u8]) -> Vec<u8> {
-        match self.algorithm {
+    /// Calculate hash value with the given algorithm
+    pub fn hash(algorithm: &HashAlgorithm, data: &[u8]) -> Vec<u8> {
+        match algorithm {
             HashAlgorithm::SHA1 => {
                 let mut hasher = Sha1::new();
                 hasher.update(data);
@@ -129,104 +303,552 @@ impl PcrSimulator {
         }
     }
 
-    /// Extend PCR
-    pub fn extend_pcr(&mut self, pcr_index: usize, measurement: &[u8]) -> Result<(), String> {
-        if pcr_index >= self.pcr_values.len() {
-            return Err(format!("Invalid PCR index: {}", pcr_index));
+    /// Active banks, in the order they were allocated
+    pub fn banks(&self) -> &[PcrBank] {
+        &self.banks
+    }
+
+    /// Events extended since the last reset, oldest first
+    pub fn measurement_log(&self) -> &[MeasurementEvent] {
+        &self.measurement_log
+    }
+
+    /// Locality of later extends and resets
+    pub fn locality(&self) -> u8 {
+        self.locality
+    }
+
+    /// Locality of TPM2_Startup
+    pub fn startup_locality(&self) -> u8 {
+        self.startup_locality
+    }
+
+    /// PCRs reset to zero since startup
+    pub fn reset_pcrs(&self) -> &[bool; PCR_COUNT] {
+        &self.reset_pcrs
+    }
+
+    /// Data of a DRTM hash sequence in progress
+    pub fn hash_sequence(&self) -> Option<&[u8]> {
+        self.hash_sequence.as_deref()
+    }
+
+    /// Get active bank algorithms
+    pub fn active_algorithms(&self) -> Vec<HashAlgorithm> {
+        self.banks.iter().map(|bank| bank.algorithm.clone()).collect()
+    }
+
+    /// Get the bank of an algorithm
+    pub fn bank(&self, algorithm: &HashAlgorithm) -> Option<&PcrBank> {
+        self.banks.iter().find(|bank| bank.algorithm == *algorithm)
+    }
+
+    /// Change the active banks (like TPM2_PCR_Allocate, resets all values)
+    pub fn set_active_banks(&mut self, algorithms: Vec<HashAlgorithm>) {
+        self.banks.clear();
+        for algorithm in algorithms {
+            if self.bank(&algorithm).is_none() {
+                self.banks.push(PcrBank::new(algorithm));
+            }
         }
+        self.reset();
+    }
 
-        let mut data = Vec::new();
-        data.extend_from_slice(&self.pcr_values[pcr_index]);
-        data.extend_from_slice(measurement);
-        
-        // Calculate new PCR value (hash extension)
-        let result = self.hash(&data);
-        self.pcr_values[pcr_index] = result;
-        
+    /// Change the locality of later extends and resets
+    pub fn set_locality(&mut self, locality: u8) -> Result<()> {
+        if locality >= LOCALITY_COUNT {
+            return Err(Error::InvalidLocality(locality));
+        }
+        self.locality = locality;
         Ok(())
     }
 
-    /// Add new measurement
-    pub fn add_measurement(&mut self, description: String, hex_value: &str, pcr_index: usize) -> Result<(), String> {
-        let value = hex::decode(hex_value)
-            .map_err(|e| format!("Failed to decode measurement: {}", e))?;
-            
-        self.measurement_log.push((description, value.clone(), pcr_index));
-        self.extend_pcr(pcr_index, &value)?;
+    /// Start the TPM from locality 0, 3 or 4 (resets all values)
+    ///
+    /// With locality 3 or 4 the last byte of the initial PCR 0 value is the locality.
+    pub fn set_startup_locality(&mut self, startup_locality: u8) -> Result<()> {
+        if ![0, 3, 4].contains(&startup_locality) {
+            return Err(Error::InvalidLocality(startup_locality));
+        }
+        self.startup_locality = startup_locality;
+        self.reset();
+        Ok(())
+    }
+
+    /// Check that the current locality may perform an operation on a PCR
+    pub fn check_locality(&self, operation: PcrOperation, pcr_index: usize) -> Result<()> {
+        if pcr_index >= PCR_COUNT {
+            return Err(Error::InvalidPcrIndex(pcr_index));
+        }
+
+        let allowed = PcrAttributes::pc_client(pcr_index).allowed(operation);
+        if !allowed.contains(self.locality) {
+            return Err(Error::LocalityDenied {
+                operation,
+                pcr_index,
+                locality: self.locality,
+                allowed,
+            });
+        }
+        Ok(())
+    }
+
+    /// Value a PCR started from: its startup value, or zero if it was reset since
+    ///
+    /// `pcr_index` must be below [`PCR_COUNT`].
+    pub(crate) fn start_value(&self, algorithm: &HashAlgorithm, pcr_index: usize) -> Vec<u8> {
+        if self.reset_pcrs[pcr_index] {
+            vec![0u8; algorithm.output_size_bytes()]
+        } else {
+            PcrBank::startup_value(algorithm, pcr_index, self.startup_locality)
+        }
+    }
+
+    /// Check whether a PCR still holds the value it started from
+    pub fn is_initial(&self, algorithm: &HashAlgorithm, pcr_index: usize) -> Result<bool> {
+        if pcr_index >= PCR_COUNT {
+            return Err(Error::InvalidPcrIndex(pcr_index));
+        }
+        Ok(self.bank(algorithm)
+            .is_some_and(|bank| bank.pcr_values[pcr_index] == self.start_value(algorithm, pcr_index)))
+    }
+
+    /// Extend PCR with per-bank digests (TPML_DIGEST_VALUES)
+    ///
+    /// Only the banks listed in `digests` are extended; digests for inactive banks are ignored.
+    pub fn extend_pcr(&mut self, pcr_index: usize, digests: &[TaggedDigest]) -> Result<()> {
+        self.check_locality(PcrOperation::Extend, pcr_index)?;
+
+        // Validate every digest before modifying any bank; padding is up to the caller
+        for tagged in digests {
+            tagged.algorithm.check_digest(tagged.digest.clone(), DigestPadding::Exact)?;
+        }
+
+        for tagged in digests {
+            if let Some(bank) = self.banks.iter_mut().find(|bank| bank.algorithm == tagged.algorithm) {
+                let mut data = Vec::new();
+                data.extend_from_slice(&bank.pcr_values[pcr_index]);
+                data.extend_from_slice(&tagged.digest);
+                
+                // Calculate new PCR value (hash extension)
+                bank.pcr_values[pcr_index] = Self::hash(&bank.algorithm, &data);
+            }
+        }
         
         Ok(())
     }
 
-    /// Reset PCR values
+    /// Extend the PCR of an event with its digests and record it in the log
+    pub fn apply_event(&mut self, event: MeasurementEvent) -> Result<()> {
+        self.extend_pcr(event.pcr_index, &event.digests)?;
+        self.measurement_log.push(event);
+        Ok(())
+    }
+
+    /// Add new measurement digest to a single bank
+    ///
+    /// The digest must have exactly the output size of the bank; it is never padded.
+    pub fn add_measurement(&mut self, description: String, hex_value: &str, pcr_index: usize, algorithm: &HashAlgorithm) -> Result<()> {
+        if self.bank(algorithm).is_none() {
+            return Err(Error::InactiveBank(algorithm.clone()));
+        }
+
+        let value = algorithm.check_digest(Self::parse_hex(hex_value)?, DigestPadding::Exact)?;
+        let digests = vec![TaggedDigest::new(algorithm.clone(), value)];
+            
+        self.apply_event(MeasurementEvent::new(pcr_index, digests, description))
+    }
+
+    /// Measure event data into every active bank (like TPM2_PCR_Event)
+    pub fn add_event_data(&mut self, description: String, data: &[u8], pcr_index: usize) -> Result<()> {
+        let digests: Vec<TaggedDigest> = self.banks
+            .iter()
+            .map(|bank| TaggedDigest::new(bank.algorithm.clone(), Self::hash(&bank.algorithm, data)))
+            .collect();
+
+        let event = MeasurementEvent::new(pcr_index, digests, description)
+            .with_event_data(data.to_vec());
+        self.apply_event(event)
+    }
+
+    /// Reset PCR values and return to locality 0
     pub fn reset(&mut self) {
-        let output_size = self.algorithm.output_size_bytes();
-        
-        // Reset PCR values to their initial values
-        for i in 0..self.pcr_values.len() {
-            if i >= 17 && i <= 22 {
-                // PCR 17-22 reset F
-                self.pcr_values[i] = vec![0xFF; output_size];
-            } else {
-                // PCR 0-16 and 23 reset 0
-                self.pcr_values[i] = vec![0u8; output_size];
-            }
+        // Reset PCR values to their startup values
+        self.locality = 0;
+        self.reset_pcrs = [false; PCR_COUNT];
+        self.hash_sequence = None;
+        for pcr_index in 0..PCR_COUNT {
+            self.restart_pcr(pcr_index);
         }
         
         self.measurement_log.clear();
     }
 
+    /// Reset a PCR to zero in every bank (like TPM2_PCR_Reset), dropping its log entries
+    pub fn reset_pcr(&mut self, pcr_index: usize) -> Result<()> {
+        self.check_locality(PcrOperation::Reset, pcr_index)?;
+        self.clear_pcr(pcr_index);
+        Ok(())
+    }
+
+    /// Start a DRTM hash sequence (_TPM_Hash_Start): the platform resets PCR 17-22 to zero
+    ///
+    /// The sequence is driven by the platform at locality 4, so it is not limited by the
+    /// current locality.
+    pub fn hash_start(&mut self) {
+        for pcr_index in 17..=22 {
+            self.clear_pcr(pcr_index);
+        }
+        self.hash_sequence = Some(Vec::new());
+    }
+
+    /// Add data to the DRTM hash sequence (_TPM_Hash_Data)
+    pub fn hash_data(&mut self, data: &[u8]) -> Result<()> {
+        self.hash_sequence
+            .as_mut()
+            .ok_or(Error::NoHashSequence)?
+            .extend_from_slice(data);
+        Ok(())
+    }
+
+    /// End the DRTM hash sequence (_TPM_Hash_End), extending the digest of its data into
+    /// PCR 17 in every active bank
+    ///
+    /// The launched environment continues at locality 3, which may extend PCR 17-19.
+    pub fn hash_end(&mut self, description: String) -> Result<()> {
+        let data = self.hash_sequence.take().ok_or(Error::NoHashSequence)?;
+        let digests: Vec<TaggedDigest> = self.banks
+            .iter()
+            .map(|bank| TaggedDigest::new(bank.algorithm.clone(), Self::hash(&bank.algorithm, &data)))
+            .collect();
+
+        let caller_locality = self.locality;
+        self.locality = 4;
+        if let Err(err) = self.apply_event(MeasurementEvent::new(DRTM_PCR, digests, description)) {
+            self.locality = caller_locality;
+            return Err(err);
+        }
+        self.locality = 3;
+        Ok(())
+    }
+
+    /// Dynamic launch (Intel TXT GETSEC[SENTER], AMD SKINIT): reset PCR 17-22 and measure
+    /// `data`, e.g. the SINIT ACM or secure loader image, into PCR 17
+    ///
+    /// Afterwards the current locality is 3.
+    pub fn dynamic_launch(&mut self, description: String, data: &[u8]) -> Result<()> {
+        self.hash_start();
+        self.hash_data(data)?;
+        self.hash_end(description)
+    }
+
+    /// Put a PCR back to its startup value in every bank and drop its log entries
+    ///
+    /// `pcr_index` must be below [`PCR_COUNT`].
+    pub(crate) fn restart_pcr(&mut self, pcr_index: usize) {
+        self.reset_pcrs[pcr_index] = false;
+        for bank in &mut self.banks {
+            bank.pcr_values[pcr_index] = PcrBank::startup_value(&bank.algorithm, pcr_index, self.startup_locality);
+        }
+        self.measurement_log.retain(|event| event.pcr_index != pcr_index);
+    }
+
+    /// Set a PCR to zero in every bank and drop its log entries
+    fn clear_pcr(&mut self, pcr_index: usize) {
+        self.reset_pcrs[pcr_index] = true;
+        for bank in &mut self.banks {
+            bank.pcr_values[pcr_index] = vec![0u8; bank.algorithm.output_size_bytes()];
+        }
+        self.measurement_log.retain(|event| event.pcr_index != pcr_index);
+    }
+
+    /// Reset before replaying an event log, starting up from `startup_locality` if given,
+    /// else from the locality of the log's StartupLocality event, or locality 0 without one
+    pub fn reset_for_events(&mut self, events: &[MeasurementEvent], startup_locality: Option<u8>) -> Result<()> {
+        let startup_locality = startup_locality
+            .or_else(|| events.iter().find_map(locality::startup_locality))
+            .unwrap_or(0);
+        self.set_startup_locality(startup_locality)
+    }
+
     /// Get PCR hex representation
-    pub fn get_pcr_hex_string(&self, index: usize) -> Result<String, String> {
-        if index >= self.pcr_values.len() {
-            return Err("Invalid PCR index".to_string());
+    pub fn get_pcr_hex_string(&self, algorithm: &HashAlgorithm, index: usize) -> Result<String> {
+        let bank = self.bank(algorithm)
+            .ok_or_else(|| Error::InactiveBank(algorithm.clone()))?;
+        if index >= bank.pcr_values.len() {
+            return Err(Error::InvalidPcrIndex(index));
         }
-        Ok(hex::encode(&self.pcr_values[index]))
+        Ok(hex::encode(&bank.pcr_values[index]))
     }
     
     /// Parse hex string
-    pub fn parse_hex(hex_str: &str) -> Result<Vec<u8>, String> {
-        hex::decode(hex_str.trim().replace(" ", ""))
-            .map_err(|e| format!("Invalid hex string: {}", e))
+    pub fn parse_hex(hex_str: &str) -> Result<Vec<u8>> {
+        Ok(hex::decode(hex_str.trim().replace(" ", ""))?)
     }
 
-    /// Replay measurement sequence
-    pub fn replay(&mut self, pcr_index: usize, measurements: &[String]) -> Result<String, String> {
+    /// Replay measurement events into one PCR, starting from its startup value
+    ///
+    /// The PCR is restarted in every active bank and its earlier log entries are dropped.
+    pub fn replay(&mut self, pcr_index: usize, events: Vec<MeasurementEvent>) -> Result<()> {
         // Ensure PCR index is valid
-        if pcr_index >= self.pcr_values.len() {
-            return Err(format!("Invalid PCR index: {}", pcr_index));
+        if pcr_index >= PCR_COUNT {
+            return Err(Error::InvalidPcrIndex(pcr_index));
         }
 
-        // Reset target PCR to its initial value
-        let output_size = self.algorithm.output_size_bytes();
-        if pcr_index >= 17 && pcr_index <= 22 {
-            self.pcr_values[pcr_index] = vec![0xFF; output_size];
-        } else {
-            self.pcr_values[pcr_index] = vec![0u8; output_size];
-        }
+        // Reset target PCR to its startup value
+        self.restart_pcr(pcr_index);
         
-        // Apply all measurements
-        for measurement in measurements {
-            let value = Self::parse_hex(measurement)?;
-            self.extend_pcr(pcr_index, &value)?;
+        // Apply all measurements; EV_NO_ACTION events are not extended
+        for mut event in events.into_iter().filter(MeasurementEvent::is_extended) {
+            event.pcr_index = pcr_index;
+            self.apply_event(event)?;
+        }
+
+        Ok(())
+    }
+
+    /// Replay a whole event log, extending each event into its own PCR in every active bank
+    ///
+    /// The startup locality is `startup_locality`, or that of the log's StartupLocality event
+    /// when not given. Returns the number of extended events.
+    pub fn replay_events(&mut self, events: Vec<MeasurementEvent>, startup_locality: Option<u8>) -> Result<usize> {
+        self.reset_for_events(&events, startup_locality)?;
+        self.extend_events(events)
+    }
+
+    /// Extend each event of a log into its own PCR in every active bank, without a reset first
+    ///
+    /// Returns the number of extended events.
+    pub fn extend_events(&mut self, events: Vec<MeasurementEvent>) -> Result<usize> {
+        let mut extended = 0;
+        for (i, event) in events.into_iter().enumerate() {
+            if !event.is_extended() {
+                continue;
+            }
+
+            for algorithm in self.active_algorithms() {
+                if event.digest_for(&algorithm).is_none() {
+                    return Err(Error::MissingDigest { event: i, algorithm });
+                }
+            }
+
+            self.apply_event(event)?;
+            extended += 1;
         }
 
-        // Return final PCR value
-        self.get_pcr_hex_string(pcr_index)
+        Ok(extended)
     }
     
-    /// Get all PCR values
-    #[allow(dead_code)]
-    pub fn get_all_pcr_values(&self) -> Vec<(usize, String)> {
+    /// Number of logged extends of a PCR in a bank
+    pub fn extend_count(&self, pcr_index: usize, algorithm: &HashAlgorithm) -> usize {
+        self.measurement_log
+            .iter()
+            .filter(|event| event.pcr_index == pcr_index && event.digest_for(algorithm).is_some())
+            .count()
+    }
+
+    /// Get all PCR values of a bank
+    pub fn get_all_pcr_values(&self, algorithm: &HashAlgorithm) -> Vec<(usize, String)> {
         let mut result = Vec::new();
-        for (index, value) in self.pcr_values.iter().enumerate() {
-            result.push((index, hex::encode(value)));
+        if let Some(bank) = self.bank(algorithm) {
+            for (index, value) in bank.pcr_values.iter().enumerate() {
+                result.push((index, hex::encode(value)));
+            }
         }
         result
     }
-    
-    /// Change hash algorithm
-    pub fn change_algorithm(&mut self, algorithm: HashAlgorithm) {
-        self.algorithm = algorithm;
-        self.reset(); // Reset all values when changing algorithm
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::locality::STARTUP_LOCALITY_SIGNATURE;
+    use crate::utils::tcg_event_log::EV_NO_ACTION;
+
+    fn event(pcr_index: usize, data: &[u8]) -> MeasurementEvent {
+        let digests = [HashAlgorithm::SHA1, HashAlgorithm::SHA256]
+            .into_iter()
+            .map(|algorithm| TaggedDigest::new(algorithm.clone(), PcrSimulator::hash(&algorithm, data)))
+            .collect();
+        MeasurementEvent::new(pcr_index, digests, String::from_utf8_lossy(data).to_string())
+    }
+
+    #[test]
+    fn replay_replaces_the_log_of_its_pcr() {
+        let mut simulator = PcrSimulator::with_banks(vec![HashAlgorithm::SHA1, HashAlgorithm::SHA256]);
+        simulator.apply_event(event(5, b"other")).unwrap();
+        let events = vec![event(4, b"shim"), event(4, b"grub")];
+
+        simulator.replay(4, events.clone()).unwrap();
+        let first: Vec<String> = simulator.banks().iter().map(|bank| hex::encode(&bank.pcr_values[4])).collect();
+        simulator.replay(4, events).unwrap();
+        let second: Vec<String> = simulator.banks().iter().map(|bank| hex::encode(&bank.pcr_values[4])).collect();
+
+        assert_eq!(first, second);
+        let descriptions: Vec<&str> = simulator.measurement_log().iter().map(|event| event.description.as_str()).collect();
+        assert_eq!(descriptions, ["other", "shim", "grub"]);
+    }
+
+    #[test]
+    fn replay_skips_no_action_events() {
+        let mut with_no_action = PcrSimulator::with_banks(vec![HashAlgorithm::SHA1, HashAlgorithm::SHA256]);
+        with_no_action
+            .replay(4, vec![event(4, b"spec id").with_event_type(EV_NO_ACTION), event(4, b"shim")])
+            .unwrap();
+        let mut without = PcrSimulator::with_banks(vec![HashAlgorithm::SHA1, HashAlgorithm::SHA256]);
+        without.replay(4, vec![event(4, b"shim")]).unwrap();
+
+        for (bank, expected) in with_no_action.banks().iter().zip(without.banks()) {
+            assert_eq!(bank.pcr_values[4], expected.pcr_values[4]);
+        }
+        assert_eq!(with_no_action.measurement_log().len(), 1);
+    }
+
+    #[test]
+    fn replay_events_starts_a_new_log() {
+        let mut simulator = PcrSimulator::with_banks(vec![HashAlgorithm::SHA1, HashAlgorithm::SHA256]);
+        let events = vec![event(0, b"crtm"), event(4, b"shim")];
+        simulator.replay_events(events.clone(), None).unwrap();
+        simulator.replay_events(events, None).unwrap();
+        assert_eq!(simulator.measurement_log().len(), 2);
+    }
+
+    #[test]
+    fn extend_rejects_wrong_digest_length() {
+        let mut simulator = PcrSimulator::with_banks(vec![HashAlgorithm::SHA1, HashAlgorithm::SHA256]);
+        let digests = [
+            TaggedDigest::new(HashAlgorithm::SHA1, vec![0x11; 20]),
+            TaggedDigest::new(HashAlgorithm::SHA256, vec![0x22; 20]),
+        ];
+        assert!(matches!(
+            simulator.extend_pcr(4, &digests),
+            Err(Error::DigestLength { expected: 32, actual: 20, .. })
+        ));
+        // No bank is extended when one digest is wrong
+        assert!(simulator.is_initial(&HashAlgorithm::SHA1, 4).unwrap());
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn pcr_index_past_the_last_pcr_is_an_error() {
+        let mut simulator = PcrSimulator::with_banks(vec![HashAlgorithm::SHA1, HashAlgorithm::SHA256]);
+        assert!(matches!(simulator.is_initial(&HashAlgorithm::SHA1, PCR_COUNT), Err(Error::InvalidPcrIndex(24))));
+        assert!(matches!(simulator.reset_pcr(PCR_COUNT), Err(Error::InvalidPcrIndex(24))));
+        assert!(matches!(simulator.replay(PCR_COUNT, Vec::new()), Err(Error::InvalidPcrIndex(24))));
+    }
+
+    fn startup_locality_event(locality: u8) -> MeasurementEvent {
+        MeasurementEvent::new(0, Vec::new(), "StartupLocality".to_string())
+            .with_event_type(EV_NO_ACTION)
+            .with_event_data([STARTUP_LOCALITY_SIGNATURE, &[locality]].concat())
+    }
+
+    fn pcr0(simulator: &PcrSimulator) -> String {
+        simulator.get_pcr_hex_string(&HashAlgorithm::SHA256, 0).unwrap()
+    }
+
+    #[test]
+    fn startup_locality_sets_last_byte_of_pcr0() {
+        let mut simulator = PcrSimulator::new(HashAlgorithm::SHA256);
+        for (locality, last_byte) in [(0, "00"), (3, "03"), (4, "04")] {
+            simulator.reset_for_events(&[], Some(locality)).unwrap();
+            assert_eq!(pcr0(&simulator), format!("{}{}", "00".repeat(31), last_byte));
+        }
+        assert!(simulator.set_startup_locality(1).is_err());
+    }
+
+    #[test]
+    fn explicit_startup_locality_overrides_the_log() {
+        let events = vec![startup_locality_event(3)];
+        let mut simulator = PcrSimulator::new(HashAlgorithm::SHA256);
+
+        simulator.replay_events(events.clone(), None).unwrap();
+        assert_eq!(simulator.startup_locality(), 3);
+        simulator.replay_events(events, Some(4)).unwrap();
+        assert_eq!(simulator.startup_locality(), 4);
+        assert!(pcr0(&simulator).ends_with("04"));
+
+        // Without an event or an explicit locality, startup is from locality 0
+        simulator.replay_events(vec![event(4, b"shim")], None).unwrap();
+        assert_eq!(simulator.startup_locality(), 0);
+    }
+
+    #[test]
+    fn dynamic_launch_measures_into_zeroed_pcr17() {
+        let mut simulator = PcrSimulator::with_banks(vec![HashAlgorithm::SHA1, HashAlgorithm::SHA256]);
+        simulator.apply_event(event(4, b"shim")).unwrap();
+        assert!(simulator.bank(&HashAlgorithm::SHA256).unwrap().pcr_values[DRTM_PCR].iter().all(|byte| *byte == 0xFF));
+
+        simulator.dynamic_launch("SINIT".to_string(), b"sinit acm").unwrap();
+
+        for bank in simulator.banks() {
+            let algorithm = &bank.algorithm;
+            let zero = vec![0u8; algorithm.output_size_bytes()];
+            let measurement = PcrSimulator::hash(algorithm, b"sinit acm");
+            assert_eq!(bank.pcr_values[DRTM_PCR], PcrSimulator::hash(algorithm, &[zero.clone(), measurement].concat()));
+            for pcr_index in 18..=22 {
+                assert_eq!(bank.pcr_values[pcr_index], zero);
+            }
+        }
+        assert!(simulator.reset_pcrs()[17..=22].iter().all(|reset| *reset));
+        assert!(!simulator.reset_pcrs()[16] && !simulator.reset_pcrs()[23]);
+        assert_eq!(simulator.locality(), 3);
+        assert!(simulator.hash_sequence().is_none());
+
+        let log: Vec<(usize, &str)> = simulator.measurement_log()
+            .iter()
+            .map(|event| (event.pcr_index, event.description.as_str()))
+            .collect();
+        assert_eq!(log, [(4, "shim"), (DRTM_PCR, "SINIT")]);
+    }
+
+    #[test]
+    fn launched_environment_extends_at_locality_3() {
+        let mut simulator = PcrSimulator::with_banks(vec![HashAlgorithm::SHA1, HashAlgorithm::SHA256]);
+        assert!(matches!(simulator.apply_event(event(18, b"mle")), Err(Error::LocalityDenied { pcr_index: 18, locality: 0, .. })));
+
+        simulator.dynamic_launch("SINIT".to_string(), b"sinit acm").unwrap();
+        for pcr_index in [17, 18, 19, 20] {
+            simulator.apply_event(event(pcr_index, b"mle")).unwrap();
+        }
+        assert!(matches!(simulator.apply_event(event(21, b"mle")), Err(Error::LocalityDenied { pcr_index: 21, locality: 3, .. })));
+
+        // A second launch starts PCR 17-22 over, dropping their log entries
+        simulator.dynamic_launch("SINIT".to_string(), b"sinit acm").unwrap();
+        let pcrs: Vec<usize> = simulator.measurement_log().iter().map(|event| event.pcr_index).collect();
+        assert_eq!(pcrs, [DRTM_PCR]);
+    }
+
+    #[test]
+    fn hash_sequence_collects_data_until_hash_end() {
+        let mut sequence = PcrSimulator::with_banks(vec![HashAlgorithm::SHA1, HashAlgorithm::SHA256]);
+        sequence.hash_start();
+        sequence.hash_data(b"sinit ").unwrap();
+        sequence.hash_data(b"acm").unwrap();
+        assert_eq!(sequence.hash_sequence(), Some(&b"sinit acm"[..]));
+        sequence.hash_end("SINIT".to_string()).unwrap();
+
+        let mut launched = PcrSimulator::with_banks(vec![HashAlgorithm::SHA1, HashAlgorithm::SHA256]);
+        launched.dynamic_launch("SINIT".to_string(), b"sinit acm").unwrap();
+        for (bank, expected) in sequence.banks().iter().zip(launched.banks()) {
+            assert_eq!(bank.pcr_values[DRTM_PCR], expected.pcr_values[DRTM_PCR]);
+        }
+    }
+
+    #[test]
+    fn hash_data_and_hash_end_need_hash_start() {
+        let mut simulator = PcrSimulator::with_banks(vec![HashAlgorithm::SHA1, HashAlgorithm::SHA256]);
+        assert!(matches!(simulator.hash_data(b"data"), Err(Error::NoHashSequence)));
+        assert!(matches!(simulator.hash_end("SINIT".to_string()), Err(Error::NoHashSequence)));
+        assert_eq!(simulator.locality(), 0);
+        assert!(simulator.measurement_log().is_empty());
+
+        simulator.hash_start();
+        simulator.hash_end("SINIT".to_string()).unwrap();
+        assert!(matches!(simulator.hash_end("SINIT".to_string()), Err(Error::NoHashSequence)));
+
+        // reset() abandons a sequence in progress
+        simulator.hash_start();
+        simulator.reset();
+        assert!(simulator.hash_sequence().is_none());
+    }
+}
@@ -0,0 +1,347 @@
+use std::fmt;
+use crate::error::{Error, Result};
+use crate::models::pcr_simulator::{DigestPadding, HashAlgorithm, PcrSimulator, PCR_COUNT};
+
+pub const TPM_CC_POLICY_SECRET: u32 = 0x0000_0151;
+pub const TPM_CC_POLICY_SIGNED: u32 = 0x0000_0160;
+pub const TPM_CC_POLICY_AUTHORIZE: u32 = 0x0000_016A;
+pub const TPM_CC_POLICY_AUTH_VALUE: u32 = 0x0000_016B;
+pub const TPM_CC_POLICY_COMMAND_CODE: u32 = 0x0000_016C;
+pub const TPM_CC_POLICY_OR: u32 = 0x0000_0171;
+pub const TPM_CC_POLICY_PCR: u32 = 0x0000_017F;
+pub const TPM_CC_POLICY_PASSWORD: u32 = 0x0000_018C;
+
+/// Size of the pcrSelect bitmap for 24 PCRs
+const PCR_SELECT_SIZE: usize = PCR_COUNT / 8;
+
+/// PCRs of one bank (TPMS_PCR_SELECTION)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PcrSelection {
+    pub algorithm: HashAlgorithm,
+    pub pcrs: Vec<usize>, // Ascending, without duplicates
+}
+
+impl PcrSelection {
+    pub fn new(algorithm: HashAlgorithm, mut pcrs: Vec<usize>) -> Result<Self> {
+        if let Some(&pcr_index) = pcrs.iter().find(|&&pcr_index| pcr_index >= PCR_COUNT) {
+            return Err(Error::InvalidPcrIndex(pcr_index));
+        }
+        pcrs.sort_unstable();
+        pcrs.dedup();
+        Ok(Self { algorithm, pcrs })
+    }
+
+    /// Parse a PCR list such as "0,2,4-7"
+    pub fn parse(algorithm: HashAlgorithm, text: &str) -> Result<Self> {
+        let mut pcrs = Vec::new();
+        for part in text.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            let number = |text: &str| text.trim().parse::<usize>()
+                .map_err(|_| Error::Parse(format!("Invalid PCR '{}'", text.trim())));
+            match part.split_once('-') {
+                Some((first, last)) => {
+                    let (first, last) = (number(first)?, number(last)?);
+                    if last >= PCR_COUNT {
+                        return Err(Error::Parse(format!("PCR {} is out of range (0-{})", last, PCR_COUNT - 1)));
+                    }
+                    if first > last {
+                        return Err(Error::Parse(format!("Invalid PCR range '{}'", part)));
+                    }
+                    pcrs.extend(first..=last);
+                },
+                None => pcrs.push(number(part)?),
+            }
+        }
+        if pcrs.is_empty() {
+            return Err(Error::Parse("No PCRs selected".to_string()));
+        }
+        Self::new(algorithm, pcrs)
+    }
+
+    /// pcrSelect bitmap: bit `n % 8` of byte `n / 8` selects PCR `n`
+    pub fn bitmap(&self) -> [u8; PCR_SELECT_SIZE] {
+        let mut bitmap = [0u8; PCR_SELECT_SIZE];
+        for &pcr_index in &self.pcrs {
+            bitmap[pcr_index / 8] |= 1 << (pcr_index % 8);
+        }
+        bitmap
+    }
+}
+
+impl fmt::Display for PcrSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pcrs: Vec<String> = self.pcrs.iter().map(|pcr_index| pcr_index.to_string()).collect();
+        write!(f, "{}:{}", self.algorithm.tpm2_name(), pcrs.join(","))
+    }
+}
+
+/// Marshal a TPML_PCR_SELECTION
+pub fn marshal_pcr_selection(selections: &[PcrSelection]) -> Vec<u8> {
+    let mut data = (selections.len() as u32).to_be_bytes().to_vec();
+    for selection in selections {
+        data.extend_from_slice(&selection.algorithm.tcg_alg_id().to_be_bytes());
+        data.push(PCR_SELECT_SIZE as u8);
+        data.extend_from_slice(&selection.bitmap());
+    }
+    data
+}
+
+/// Policy command as it affects the policyDigest of a session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyCommand {
+    /// TPM2_PolicyPCR with the selected PCR values concatenated in selection order
+    Pcr { selections: Vec<PcrSelection>, pcr_values: Vec<u8> },
+    /// TPM2_PolicyOR of 2 to 8 branch policy digests
+    Or(Vec<Vec<u8>>),
+    AuthValue,
+    Password,
+    CommandCode(u32),
+    /// TPM2_PolicySecret with the Name of the authorizing object or hierarchy handle
+    Secret { auth_name: Vec<u8>, policy_ref: Vec<u8> },
+    /// TPM2_PolicySigned with the Name of the signing key
+    Signed { key_name: Vec<u8>, policy_ref: Vec<u8> },
+    /// TPM2_PolicyAuthorize with the Name of the key that signs approved policies
+    Authorize { key_name: Vec<u8>, policy_ref: Vec<u8> },
+}
+
+impl PolicyCommand {
+    /// TPM2_PolicyPCR over the current values of the selected PCRs
+    pub fn pcr(simulator: &PcrSimulator, selections: Vec<PcrSelection>) -> Result<Self> {
+        let mut pcr_values = Vec::new();
+        for selection in &selections {
+            let bank = simulator.bank(&selection.algorithm)
+                .ok_or_else(|| Error::InactiveBank(selection.algorithm.clone()))?;
+            for &pcr_index in &selection.pcrs {
+                pcr_values.extend_from_slice(&bank.pcr_values[pcr_index]);
+            }
+        }
+        Ok(PolicyCommand::Pcr { selections, pcr_values })
+    }
+
+    /// Name of the TPM command
+    pub fn name(&self) -> &'static str {
+        match self {
+            PolicyCommand::Pcr { .. } => "PolicyPCR",
+            PolicyCommand::Or(_) => "PolicyOR",
+            PolicyCommand::AuthValue => "PolicyAuthValue",
+            PolicyCommand::Password => "PolicyPassword",
+            PolicyCommand::CommandCode(_) => "PolicyCommandCode",
+            PolicyCommand::Secret { .. } => "PolicySecret",
+            PolicyCommand::Signed { .. } => "PolicySigned",
+            PolicyCommand::Authorize { .. } => "PolicyAuthorize",
+        }
+    }
+
+    /// Command code (TPM_CC) of the command
+    pub fn command_code(&self) -> u32 {
+        match self {
+            PolicyCommand::Pcr { .. } => TPM_CC_POLICY_PCR,
+            PolicyCommand::Or(_) => TPM_CC_POLICY_OR,
+            PolicyCommand::AuthValue => TPM_CC_POLICY_AUTH_VALUE,
+            PolicyCommand::Password => TPM_CC_POLICY_PASSWORD,
+            PolicyCommand::CommandCode(_) => TPM_CC_POLICY_COMMAND_CODE,
+            PolicyCommand::Secret { .. } => TPM_CC_POLICY_SECRET,
+            PolicyCommand::Signed { .. } => TPM_CC_POLICY_SIGNED,
+            PolicyCommand::Authorize { .. } => TPM_CC_POLICY_AUTHORIZE,
+        }
+    }
+
+    /// Composite digest of the selected PCR values (pcrDigest), for PolicyPCR only
+    pub fn pcr_digest(&self, algorithm: &HashAlgorithm) -> Option<Vec<u8>> {
+        match self {
+            PolicyCommand::Pcr { pcr_values, .. } => Some(PcrSimulator::hash(algorithm, pcr_values)),
+            _ => None,
+        }
+    }
+
+    /// policyDigest after this command, given the policyDigest before it
+    pub fn apply(&self, algorithm: &HashAlgorithm, policy_digest: &[u8]) -> Result<Vec<u8>> {
+        let hash = |parts: &[&[u8]]| PcrSimulator::hash(algorithm, &parts.concat());
+        let zero = vec![0u8; algorithm.output_size_bytes()];
+
+        let digest = match self {
+            PolicyCommand::Pcr { selections, pcr_values } => hash(&[
+                policy_digest,
+                &TPM_CC_POLICY_PCR.to_be_bytes(),
+                &marshal_pcr_selection(selections),
+                &PcrSimulator::hash(algorithm, pcr_values),
+            ]),
+            PolicyCommand::Or(branches) => {
+                if !(2..=8).contains(&branches.len()) {
+                    return Err(Error::InvalidPolicy(format!("PolicyOR needs 2 to 8 branches, got {}", branches.len())));
+                }
+                for branch in branches {
+                    algorithm.check_digest(branch.clone(), DigestPadding::Exact)?;
+                }
+                // The session must be in one of the branches, which then replace its digest
+                hash(&[&zero, &TPM_CC_POLICY_OR.to_be_bytes(), &branches.concat()])
+            },
+            // PolicyPassword is recorded like PolicyAuthValue
+            PolicyCommand::AuthValue | PolicyCommand::Password => {
+                hash(&[policy_digest, &TPM_CC_POLICY_AUTH_VALUE.to_be_bytes()])
+            },
+            PolicyCommand::CommandCode(code) => {
+                hash(&[policy_digest, &TPM_CC_POLICY_COMMAND_CODE.to_be_bytes(), &code.to_be_bytes()])
+            },
+            PolicyCommand::Secret { auth_name: name, policy_ref }
+            | PolicyCommand::Signed { key_name: name, policy_ref } => {
+                policy_update(algorithm, policy_digest, self.command_code(), name, policy_ref)
+            },
+            // The approved policy replaces whatever was asserted before
+            PolicyCommand::Authorize { key_name, policy_ref } => {
+                policy_update(algorithm, &zero, TPM_CC_POLICY_AUTHORIZE, key_name, policy_ref)
+            },
+        };
+        Ok(digest)
+    }
+}
+
+impl fmt::Display for PolicyCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyCommand::Pcr { selections, .. } => {
+                let selections: Vec<String> = selections.iter().map(PcrSelection::to_string).collect();
+                write!(f, "PolicyPCR {}", selections.join(" "))
+            },
+            PolicyCommand::Or(branches) => write!(f, "PolicyOR of {} branches", branches.len()),
+            PolicyCommand::CommandCode(code) => write!(f, "PolicyCommandCode 0x{:08x}", code),
+            PolicyCommand::Secret { auth_name: name, policy_ref }
+            | PolicyCommand::Signed { key_name: name, policy_ref }
+            | PolicyCommand::Authorize { key_name: name, policy_ref } => {
+                write!(f, "{} {}", self.name(), hex::encode(name))?;
+                if !policy_ref.is_empty() {
+                    write!(f, " ref {}", hex::encode(policy_ref))?;
+                }
+                Ok(())
+            },
+            _ => write!(f, "{}", self.name()),
+        }
+    }
+}
+
+/// PolicyUpdate(): `H(H(policyDigest || commandCode || name) || policyRef)`
+fn policy_update(algorithm: &HashAlgorithm, policy_digest: &[u8], command_code: u32, name: &[u8], policy_ref: &[u8]) -> Vec<u8> {
+    let digest = PcrSimulator::hash(algorithm, &[policy_digest, &command_code.to_be_bytes(), name].concat());
+    PcrSimulator::hash(algorithm, &[digest.as_slice(), policy_ref].concat())
+}
+
+/// A policy command with the policyDigest after it
+#[derive(Debug, Clone)]
+pub struct PolicyStep {
+    pub command: PolicyCommand,
+    pub policy_digest: Vec<u8>,
+}
+
+/// Trial policy session: chains policy commands from an all-zero policyDigest
+#[derive(Debug, Clone)]
+pub struct PolicyCalculator {
+    pub algorithm: HashAlgorithm, // Hash algorithm of the policy session
+    pub commands: Vec<PolicyCommand>,
+}
+
+impl PolicyCalculator {
+    pub fn new(algorithm: HashAlgorithm) -> Self {
+        Self { algorithm, commands: Vec::new() }
+    }
+
+    /// Add a command to the end of the policy
+    pub fn with(mut self, command: PolicyCommand) -> Self {
+        self.commands.push(command);
+        self
+    }
+
+    /// Every command with the policyDigest after it
+    pub fn steps(&self) -> Result<Vec<PolicyStep>> {
+        let mut policy_digest = vec![0u8; self.algorithm.output_size_bytes()];
+        let mut steps = Vec::new();
+        for command in &self.commands {
+            policy_digest = command.apply(&self.algorithm, &policy_digest)?;
+            steps.push(PolicyStep {
+                command: command.clone(),
+                policy_digest: policy_digest.clone(),
+            });
+        }
+        Ok(steps)
+    }
+
+    /// Final policyDigest, e.g. the authPolicy of an object sealed to it
+    pub fn policy_digest(&self) -> Result<Vec<u8>> {
+        Ok(self.steps()?
+            .pop()
+            .map(|step| step.policy_digest)
+            .unwrap_or_else(|| vec![0u8; self.algorithm.output_size_bytes()]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TPM_CC_UNSEAL: u32 = 0x0000_015E;
+
+    fn apply_sha256(command: &PolicyCommand) -> String {
+        hex::encode(command.apply(&HashAlgorithm::SHA256, &[0; 32]).unwrap())
+    }
+
+    #[test]
+    fn policy_auth_value() {
+        assert_eq!(
+            apply_sha256(&PolicyCommand::AuthValue),
+            "8fcd2169ab92694e0c633f1ab772842b8241bbc20288981fc7ac1eddc1fddb0e"
+        );
+        assert_eq!(apply_sha256(&PolicyCommand::Password), apply_sha256(&PolicyCommand::AuthValue));
+    }
+
+    #[test]
+    fn policy_pcr() {
+        // sha256:0,7 with PCR 0 all zero and PCR 7 all 0x11
+        let selections = vec![PcrSelection::new(HashAlgorithm::SHA256, vec![7, 0]).unwrap()];
+        assert_eq!(hex::encode(marshal_pcr_selection(&selections)), "00000001000b03810000");
+
+        let mut simulator = PcrSimulator::new(HashAlgorithm::SHA256);
+        simulator.banks[0].pcr_values[7] = vec![0x11; 32];
+        let command = PolicyCommand::pcr(&simulator, selections).unwrap();
+        assert_eq!(
+            apply_sha256(&command),
+            "e543270061aadd84e54f13c220912eefd4ee992a5ec53df25eb22aebca0458ab"
+        );
+    }
+
+    #[test]
+    fn pcr_selection_parses_lists_and_ranges() {
+        let selection = PcrSelection::parse(HashAlgorithm::SHA256, "7, 0,2-4").unwrap();
+        assert_eq!(selection.pcrs, vec![0, 2, 3, 4, 7]);
+        assert_eq!(PcrSelection::parse(HashAlgorithm::SHA256, "20-23").unwrap().pcrs, vec![20, 21, 22, 23]);
+    }
+
+    #[test]
+    fn pcr_selection_rejects_ranges_past_the_last_pcr() {
+        assert!(matches!(PcrSelection::parse(HashAlgorithm::SHA256, "0-24"), Err(Error::Parse(_))));
+        assert!(matches!(
+            PcrSelection::parse(HashAlgorithm::SHA256, "0-18446744073709551615"),
+            Err(Error::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn pcr_selection_rejects_reversed_ranges() {
+        match PcrSelection::parse(HashAlgorithm::SHA256, "5-3") {
+            Err(Error::Parse(message)) => assert_eq!(message, "Invalid PCR range '5-3'"),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn policy_or() {
+        let auth_value = PolicyCommand::AuthValue.apply(&HashAlgorithm::SHA256, &[0; 32]).unwrap();
+        let unseal = PolicyCommand::CommandCode(TPM_CC_UNSEAL).apply(&HashAlgorithm::SHA256, &[0; 32]).unwrap();
+        assert_eq!(hex::encode(&unseal), "e613137076524bde487533865884e9732ebee3aacb095d94a6de492ec06c46fa");
+
+        let or = PolicyCommand::Or(vec![auth_value.clone(), unseal]);
+        let expected = "a0a333af4a6491143962f580ceccd7bb9d0a470874e934180e78a9b1c2d12d61";
+        assert_eq!(apply_sha256(&or), expected);
+        // The branch that was satisfied does not change the result
+        assert_eq!(hex::encode(or.apply(&HashAlgorithm::SHA256, &auth_value).unwrap()), expected);
+
+        assert!(PolicyCommand::Or(vec![auth_value]).apply(&HashAlgorithm::SHA256, &[0; 32]).is_err());
+    }
+}
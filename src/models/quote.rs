@@ -0,0 +1,624 @@
+use std::fmt;
+use p256::pkcs8::DecodePublicKey;
+use rsa::pkcs1::DecodeRsaPublicKey;
+use rsa::traits::PublicKeyParts;
+use rsa::{BigUint, Pkcs1v15Sign, Pss, RsaPublicKey};
+use sha1::Sha1;
+use sha2::{Sha256, Sha384, Sha512};
+use crate::error::{Error, Result};
+use crate::models::pcr_simulator::{HashAlgorithm, PcrSimulator, PCR_COUNT};
+use crate::models::policy::PcrSelection;
+
+/// TPM_GENERATED_VALUE, the magic of every structure signed by a TPM
+pub const TPM_GENERATED_VALUE: u32 = 0xFF54_4347;
+/// TPM_ST_ATTEST_QUOTE
+pub const TPM_ST_ATTEST_QUOTE: u16 = 0x8018;
+
+const TPM_ALG_RSA: u16 = 0x0001;
+const TPM_ALG_NULL: u16 = 0x0010;
+const TPM_ALG_RSASSA: u16 = 0x0014;
+const TPM_ALG_RSAPSS: u16 = 0x0016;
+const TPM_ALG_ECDSA: u16 = 0x0018;
+const TPM_ALG_ECC: u16 = 0x0023;
+const TPM_ECC_NIST_P256: u16 = 0x0003;
+const TPM_ECC_NIST_P384: u16 = 0x0004;
+
+/// Big-endian reader for TPM structures
+struct TpmReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    what: &'static str, // Structure named in errors
+}
+
+impl<'a> TpmReader<'a> {
+    fn new(data: &'a [u8], what: &'static str) -> Self {
+        Self { data, pos: 0, what }
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.data.len() - self.pos < len {
+            return Err(Error::Parse(format!(
+                "{} is truncated at offset {} (needed {} bytes, {} left)",
+                self.what,
+                self.pos,
+                len,
+                self.data.len() - self.pos
+            )));
+        }
+        let bytes = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16> {
+        let bytes = self.read_bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn read_u32(&mut self) -> Result<u32> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn read_u64(&mut self) -> Result<u64> {
+        let high = self.read_u32()? as u64;
+        Ok(high << 32 | self.read_u32()? as u64)
+    }
+
+    /// TPM2B: a 16-bit size followed by that many bytes
+    fn read_tpm2b(&mut self) -> Result<&'a [u8]> {
+        let size = self.read_u16()? as usize;
+        self.read_bytes(size)
+    }
+
+    fn read_algorithm(&mut self) -> Result<HashAlgorithm> {
+        let alg_id = self.read_u16()?;
+        HashAlgorithm::from_tcg_alg_id(alg_id)
+            .ok_or_else(|| Error::Parse(format!("{}: unsupported hash algorithm 0x{:04x}", self.what, alg_id)))
+    }
+}
+
+/// TPMS_CLOCK_INFO
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClockInfo {
+    pub clock: u64, // Milliseconds the TPM has been powered
+    pub reset_count: u32,
+    pub restart_count: u32,
+    pub safe: bool,
+}
+
+/// Quote attestation structure (TPMS_ATTEST with TPMS_QUOTE_INFO), e.g. `tpm2_quote -m`
+#[derive(Debug, Clone)]
+pub struct Quote {
+    pub qualified_signer: Vec<u8>,
+    pub extra_data: Vec<u8>, // Nonce supplied by the verifier
+    pub clock_info: ClockInfo,
+    pub firmware_version: u64,
+    pub pcr_selections: Vec<PcrSelection>,
+    pub pcr_digest: Vec<u8>,
+    pub attest: Vec<u8>, // The signed bytes
+}
+
+impl Quote {
+    /// Parse a marshalled TPMS_ATTEST, with or without its TPM2B size
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        // tpm2-tools writes the bare structure, other stacks a TPM2B_ATTEST
+        let attest = match data {
+            [high, low, 0xFF, 0x54, 0x43, 0x47, ..] if u16::from_be_bytes([*high, *low]) as usize == data.len() - 2 => &data[2..],
+            _ => data,
+        };
+
+        let mut reader = TpmReader::new(attest, "Quote");
+        let magic = reader.read_u32()?;
+        if magic != TPM_GENERATED_VALUE {
+            return Err(Error::Parse(format!("Not a TPM attestation: magic 0x{:08x}", magic)));
+        }
+        let attest_type = reader.read_u16()?;
+        if attest_type != TPM_ST_ATTEST_QUOTE {
+            return Err(Error::Parse(format!("Attestation type 0x{:04x} is not a quote", attest_type)));
+        }
+
+        let qualified_signer = reader.read_tpm2b()?.to_vec();
+        let extra_data = reader.read_tpm2b()?.to_vec();
+        let clock_info = ClockInfo {
+            clock: reader.read_u64()?,
+            reset_count: reader.read_u32()?,
+            restart_count: reader.read_u32()?,
+            safe: reader.read_u8()? != 0,
+        };
+        let firmware_version = reader.read_u64()?;
+
+        let mut pcr_selections = Vec::new();
+        for _ in 0..reader.read_u32()? {
+            let algorithm = reader.read_algorithm()?;
+            let size = reader.read_u8()? as usize;
+            let bitmap = reader.read_bytes(size)?;
+            let pcrs: Vec<usize> = (0..size * 8)
+                .filter(|&pcr_index| bitmap[pcr_index / 8] & (1 << (pcr_index % 8)) != 0)
+                .collect();
+            pcr_selections.push(PcrSelection::new(algorithm, pcrs)?);
+        }
+        let pcr_digest = reader.read_tpm2b()?.to_vec();
+
+        Ok(Self {
+            qualified_signer,
+            extra_data,
+            clock_info,
+            firmware_version,
+            pcr_selections,
+            pcr_digest,
+            attest: attest.to_vec(),
+        })
+    }
+
+    /// Firmware version as the four 16-bit parts of TPM_PT_FIRMWARE_VERSION_1 and _2
+    pub fn firmware_version_string(&self) -> String {
+        let parts: Vec<String> = (0..4)
+            .map(|i| ((self.firmware_version >> (48 - 16 * i)) & 0xFFFF).to_string())
+            .collect();
+        parts.join(".")
+    }
+}
+
+/// Quote signature (TPMT_SIGNATURE), e.g. `tpm2_quote -s`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuoteSignature {
+    RsaSsa { hash: HashAlgorithm, signature: Vec<u8> },
+    RsaPss { hash: HashAlgorithm, signature: Vec<u8> },
+    Ecdsa { hash: HashAlgorithm, r: Vec<u8>, s: Vec<u8> },
+}
+
+impl QuoteSignature {
+    /// Parse a marshalled TPMT_SIGNATURE
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let mut reader = TpmReader::new(data, "Signature");
+        let scheme = reader.read_u16()?;
+        let hash = reader.read_algorithm()?;
+        match scheme {
+            TPM_ALG_RSASSA => Ok(QuoteSignature::RsaSsa { hash, signature: reader.read_tpm2b()?.to_vec() }),
+            TPM_ALG_RSAPSS => Ok(QuoteSignature::RsaPss { hash, signature: reader.read_tpm2b()?.to_vec() }),
+            TPM_ALG_ECDSA => Ok(QuoteSignature::Ecdsa {
+                hash,
+                r: reader.read_tpm2b()?.to_vec(),
+                s: reader.read_tpm2b()?.to_vec(),
+            }),
+            _ => Err(Error::Parse(format!("Unsupported signature scheme 0x{:04x}", scheme))),
+        }
+    }
+
+    /// Hash algorithm of the signature, also used for the quoted PCR digest
+    pub fn hash_algorithm(&self) -> &HashAlgorithm {
+        match self {
+            QuoteSignature::RsaSsa { hash, .. }
+            | QuoteSignature::RsaPss { hash, .. }
+            | QuoteSignature::Ecdsa { hash, .. } => hash,
+        }
+    }
+
+    pub fn scheme_name(&self) -> &'static str {
+        match self {
+            QuoteSignature::RsaSsa { .. } => "RSASSA",
+            QuoteSignature::RsaPss { .. } => "RSAPSS",
+            QuoteSignature::Ecdsa { .. } => "ECDSA",
+        }
+    }
+}
+
+/// Public key that signed a quote
+#[derive(Debug, Clone)]
+pub enum QuotePublicKey {
+    Rsa(RsaPublicKey),
+    P256(p256::ecdsa::VerifyingKey),
+    P384(p384::ecdsa::VerifyingKey),
+}
+
+impl QuotePublicKey {
+    /// Parse a PEM public key (`PUBLIC KEY` or `RSA PUBLIC KEY`) or a marshalled TPM2B_PUBLIC
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        match std::str::from_utf8(data) {
+            Ok(text) if text.trim_start().starts_with("-----BEGIN") => Self::from_pem(text),
+            _ => Self::from_tpm2b_public(data),
+        }
+    }
+
+    pub fn from_pem(text: &str) -> Result<Self> {
+        let text = text.trim();
+        if let Ok(key) = RsaPublicKey::from_public_key_pem(text).or_else(|_| RsaPublicKey::from_pkcs1_pem(text)) {
+            return Ok(QuotePublicKey::Rsa(key));
+        }
+        if let Ok(key) = p256::ecdsa::VerifyingKey::from_public_key_pem(text) {
+            return Ok(QuotePublicKey::P256(key));
+        }
+        if let Ok(key) = p384::ecdsa::VerifyingKey::from_public_key_pem(text) {
+            return Ok(QuotePublicKey::P384(key));
+        }
+        Err(Error::Parse("PEM is not an RSA, P-256 or P-384 public key".to_string()))
+    }
+
+    /// Parse a TPM2B_PUBLIC (or bare TPMT_PUBLIC) of an RSA or NIST P-256/P-384 key
+    pub fn from_tpm2b_public(data: &[u8]) -> Result<Self> {
+        let public = match data {
+            [high, low, ..] if u16::from_be_bytes([*high, *low]) as usize == data.len() - 2 => &data[2..],
+            _ => data,
+        };
+
+        let mut reader = TpmReader::new(public, "Public key");
+        let key_type = reader.read_u16()?;
+        reader.read_u16()?; // nameAlg
+        reader.read_u32()?; // objectAttributes
+        reader.read_tpm2b()?; // authPolicy
+        if reader.read_u16()? != TPM_ALG_NULL {
+            reader.read_u16()?; // symmetric keyBits
+            reader.read_u16()?; // symmetric mode
+        }
+        if reader.read_u16()? != TPM_ALG_NULL {
+            reader.read_u16()?; // scheme hashAlg
+        }
+
+        match key_type {
+            TPM_ALG_RSA => {
+                reader.read_u16()?; // keyBits
+                let exponent = match reader.read_u32()? {
+                    0 => 65537,
+                    exponent => exponent,
+                };
+                let modulus = reader.read_tpm2b()?;
+                RsaPublicKey::new(BigUint::from_bytes_be(modulus), BigUint::from(exponent))
+                    .map(QuotePublicKey::Rsa)
+                    .map_err(|err| Error::Parse(format!("Invalid RSA public key: {}", err)))
+            },
+            TPM_ALG_ECC => {
+                let curve = reader.read_u16()?;
+                if reader.read_u16()? != TPM_ALG_NULL {
+                    reader.read_u16()?; // kdf hashAlg
+                }
+                // Coordinates may omit leading zero bytes; the SEC1 point needs them at field size
+                let field_size = match curve {
+                    TPM_ECC_NIST_P256 => 32,
+                    TPM_ECC_NIST_P384 => 48,
+                    _ => return Err(Error::Parse(format!("Unsupported ECC curve 0x{:04x}", curve))),
+                };
+                let coordinate = |value: &[u8]| {
+                    left_pad(value, field_size).map_err(|_| Error::Parse("Invalid ECC public point".to_string()))
+                };
+                let point = [vec![0x04], coordinate(reader.read_tpm2b()?)?, coordinate(reader.read_tpm2b()?)?].concat();
+                let invalid = |_| Error::Parse("Invalid ECC public point".to_string());
+                if curve == TPM_ECC_NIST_P256 {
+                    p256::ecdsa::VerifyingKey::from_sec1_bytes(&point)
+                        .map(QuotePublicKey::P256)
+                        .map_err(invalid)
+                } else {
+                    p384::ecdsa::VerifyingKey::from_sec1_bytes(&point)
+                        .map(QuotePublicKey::P384)
+                        .map_err(invalid)
+                }
+            },
+            _ => Err(Error::Parse(format!("Unsupported key type 0x{:04x}", key_type))),
+        }
+    }
+
+    /// Verify a quote signature over `message`
+    pub fn verify(&self, message: &[u8], signature: &QuoteSignature) -> Result<()> {
+        let digest = PcrSimulator::hash(signature.hash_algorithm(), message);
+        let invalid = || Error::Signature(format!("{} signature does not match the key", signature.scheme_name()));
+
+        match (self, signature) {
+            (QuotePublicKey::Rsa(key), QuoteSignature::RsaSsa { hash, signature }) => {
+                key.verify(pkcs1v15_scheme(hash)?, &digest, signature).map_err(|_| invalid())
+            },
+            (QuotePublicKey::Rsa(key), QuoteSignature::RsaPss { hash, signature }) => {
+                // TPMs use a salt as long as the digest; some use the longest salt the key allows.
+                // A key too small for the digest leaves no salt length to try.
+                let max_salt = key.n().bits().div_ceil(8).checked_sub(digest.len() + 2);
+                let salt_lens: Vec<usize> = [Some(digest.len()), max_salt]
+                    .into_iter()
+                    .flatten()
+                    .filter(|&salt_len| max_salt.is_some_and(|max_salt| salt_len <= max_salt))
+                    .collect();
+                if salt_lens.is_empty() {
+                    return Err(Error::Signature(format!(
+                        "RSA-{} key is too small for a {} RSAPSS signature",
+                        key.n().bits(),
+                        hash.name()
+                    )));
+                }
+                salt_lens
+                    .iter()
+                    .find_map(|&salt_len| key.verify(pss_scheme(hash, salt_len).ok()?, &digest, signature).ok())
+                    .ok_or_else(invalid)
+            },
+            (QuotePublicKey::P256(key), QuoteSignature::Ecdsa { r, s, .. }) => {
+                use p256::ecdsa::signature::hazmat::PrehashVerifier;
+                let signature = p256::ecdsa::Signature::from_scalars(
+                    p256::FieldBytes::clone_from_slice(&left_pad(r, 32)?),
+                    p256::FieldBytes::clone_from_slice(&left_pad(s, 32)?),
+                ).map_err(|_| invalid())?;
+                key.verify_prehash(&digest, &signature).map_err(|_| invalid())
+            },
+            (QuotePublicKey::P384(key), QuoteSignature::Ecdsa { r, s, .. }) => {
+                use p384::ecdsa::signature::hazmat::PrehashVerifier;
+                let signature = p384::ecdsa::Signature::from_scalars(
+                    p384::FieldBytes::clone_from_slice(&left_pad(r, 48)?),
+                    p384::FieldBytes::clone_from_slice(&left_pad(s, 48)?),
+                ).map_err(|_| invalid())?;
+                key.verify_prehash(&digest, &signature).map_err(|_| invalid())
+            },
+            _ => Err(Error::Signature(format!("{} signature does not fit the {} key", signature.scheme_name(), self))),
+        }
+    }
+}
+
+impl fmt::Display for QuotePublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuotePublicKey::Rsa(key) => write!(f, "RSA-{}", key.n().bits()),
+            QuotePublicKey::P256(_) => write!(f, "ECC NIST P-256"),
+            QuotePublicKey::P384(_) => write!(f, "ECC NIST P-384"),
+        }
+    }
+}
+
+fn pkcs1v15_scheme(hash: &HashAlgorithm) -> Result<Pkcs1v15Sign> {
+    match hash {
+        HashAlgorithm::SHA1 => Ok(Pkcs1v15Sign::new::<Sha1>()),
+        HashAlgorithm::SHA256 => Ok(Pkcs1v15Sign::new::<Sha256>()),
+        HashAlgorithm::SHA384 => Ok(Pkcs1v15Sign::new::<Sha384>()),
+        HashAlgorithm::SHA512 => Ok(Pkcs1v15Sign::new::<Sha512>()),
+        _ => Err(Error::Signature(format!("RSA signatures with {} are not supported", hash.name()))),
+    }
+}
+
+fn pss_scheme(hash: &HashAlgorithm, salt_len: usize) -> Result<Pss> {
+    match hash {
+        HashAlgorithm::SHA1 => Ok(Pss::new_with_salt::<Sha1>(salt_len)),
+        HashAlgorithm::SHA256 => Ok(Pss::new_with_salt::<Sha256>(salt_len)),
+        HashAlgorithm::SHA384 => Ok(Pss::new_with_salt::<Sha384>(salt_len)),
+        HashAlgorithm::SHA512 => Ok(Pss::new_with_salt::<Sha512>(salt_len)),
+        _ => Err(Error::Signature(format!("RSA signatures with {} are not supported", hash.name()))),
+    }
+}
+
+/// Left-pad an ECDSA scalar or ECC coordinate to the field size
+fn left_pad(scalar: &[u8], size: usize) -> Result<Vec<u8>> {
+    let scalar = &scalar[scalar.iter().take_while(|&&byte| byte == 0).count()..];
+    if scalar.len() > size {
+        return Err(Error::Signature(format!("ECDSA scalar longer than {} bytes", size)));
+    }
+    Ok([vec![0u8; size - scalar.len()], scalar.to_vec()].concat())
+}
+
+/// Overall outcome of a quote check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteStatus {
+    /// PCR digest, signature and nonce all check out
+    Valid,
+    /// Nothing failed, but no signature was checked, so the quote may be forged
+    Unverified,
+    /// The PCR digest, signature or nonce does not match
+    Invalid,
+}
+
+/// Result of checking a quote against the replayed PCRs
+#[derive(Debug, Clone)]
+pub struct QuoteVerification {
+    pub digest_algorithm: HashAlgorithm, // Hash of the quoted PCR digest
+    pub replayed_digest: Option<Vec<u8>>, // None if a quoted bank is not active
+    pub signature: Option<std::result::Result<(), String>>, // None without a signature and key
+    pub nonce_matches: Option<bool>, // None without an expected nonce
+    pub errors: Vec<String>,
+}
+
+impl QuoteVerification {
+    pub fn pcr_digest_matches(&self, quote: &Quote) -> bool {
+        self.replayed_digest.as_deref() == Some(quote.pcr_digest.as_slice())
+    }
+
+    /// A signature was given and verified against the key
+    pub fn is_signature_verified(&self) -> bool {
+        matches!(self.signature, Some(Ok(())))
+    }
+
+    pub fn status(&self, quote: &Quote) -> QuoteStatus {
+        if !self.pcr_digest_matches(quote)
+            || !self.errors.is_empty()
+            || matches!(self.signature, Some(Err(_)))
+            || self.nonce_matches == Some(false)
+        {
+            QuoteStatus::Invalid
+        } else if self.is_signature_verified() {
+            QuoteStatus::Valid
+        } else {
+            QuoteStatus::Unverified
+        }
+    }
+}
+
+impl PcrSimulator {
+    /// Digest of the selected PCR values, concatenated in selection order (like a quote's pcrDigest)
+    pub fn pcr_selection_digest(&self, selections: &[PcrSelection], algorithm: &HashAlgorithm) -> Result<Vec<u8>> {
+        let mut values = Vec::new();
+        for selection in selections {
+            let bank = self.bank(&selection.algorithm)
+                .ok_or_else(|| Error::InactiveBank(selection.algorithm.clone()))?;
+            for &pcr_index in &selection.pcrs {
+                if pcr_index >= PCR_COUNT {
+                    return Err(Error::InvalidPcrIndex(pcr_index));
+                }
+                values.extend_from_slice(&bank.pcr_values[pcr_index]);
+            }
+        }
+        Ok(Self::hash(algorithm, &values))
+    }
+
+    /// Check a quote: recompute its PCR digest from the replayed PCRs and optionally verify
+    /// its signature and nonce
+    ///
+    /// Without a signature the digest algorithm is guessed from the quoted digest's length.
+    pub fn verify_quote(
+        &self,
+        quote: &Quote,
+        signature: Option<(&QuoteSignature, &QuotePublicKey)>,
+        nonce: Option<&[u8]>,
+    ) -> QuoteVerification {
+        let mut errors = Vec::new();
+
+        let digest_algorithm = match signature {
+            Some((signature, _)) => signature.hash_algorithm().clone(),
+            None => [HashAlgorithm::SHA256, HashAlgorithm::SHA1, HashAlgorithm::SHA384, HashAlgorithm::SHA512]
+                .into_iter()
+                .find(|algorithm| algorithm.output_size_bytes() == quote.pcr_digest.len())
+                .unwrap_or(HashAlgorithm::SHA256),
+        };
+        let replayed_digest = match self.pcr_selection_digest(&quote.pcr_selections, &digest_algorithm) {
+            Ok(digest) => Some(digest),
+            Err(err) => {
+                errors.push(err.to_string());
+                None
+            }
+        };
+
+        QuoteVerification {
+            digest_algorithm,
+            replayed_digest,
+            signature: signature.map(|(signature, key)| key.verify(&quote.attest, signature).map_err(|err| err.to_string())),
+            nonce_matches: nonce.map(|nonce| nonce == quote.extra_data.as_slice()),
+            errors,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::pcr_simulator::TaggedDigest;
+
+    /// TPMS_ATTEST quoting PCR 0 and 1 of the SHA256 bank, both zero, with nonce deadbeef
+    const ATTEST: &str = "\
+        ff54434780180008000b7369676e65720004deadbeef000000000001e240000000050000000201000100020003000400\
+        000001000b030300000020f5a5fd42d16a20302798ef6ed309979b43003d2320d9f0e8ea9831a92759fb4b";
+    /// TPM2B_PUBLIC of an RSA-1024 key with the default exponent
+    const RSA_PUBLIC: &str = "\
+        00980001000b00040072000000100016000b0400000000000080c4f9ed8f4d8b68ca0c2d68f064e20d9669eadd07f59a\
+        a6db83ef26859642db25fb06533858fac3ff0aa29d62cd51ac5f0c027af796f68b145ca325f9ca768450a183580a8b77\
+        a786d7f45e00896716d703fe67adcd410267c623e36e67356a02e10bd1d19b45a451239240192cd45490df93d5daca11\
+        2e0b53e2c5be6d276ea3";
+    /// RSAPSS signatures of ATTEST with a 32 byte, the longest (94 byte) and a 20 byte salt
+    const PSS_DIGEST_SALT: &str = "\
+        0016000b008064d1eaa8b7b255f96a204ff6ca85fdd65592d85ae4f8a75981e6fe82ea5f60f52b6454c7e3b2a731c3f0\
+        7e794439d9cb2ac120633da6802ffaa853e3912d4312bb54dfb7075c4988403b2529814aa44c48dccc248cf4dd050d55\
+        ce6b6004061a61da6b1eb47a0c9e75c125fd691fa585dd3e952c5f4a03e1cc4dfd8755a0b8c7";
+    const PSS_MAX_SALT: &str = "\
+        0016000b00801c7538f7affecd27ce1c296a5c5306513fc4dcdce204d4619cb0621f6b5833d599fd1466aaed072fce68\
+        37f037516fad89ccca80c92a068c60530f58772635b1b7e57aed58a9ea5a0a71a746937ece03b439e58a9f04af5010ef\
+        94d8fd036883b44b98a73cdd7847ea13c7f6e770abfe96a86c53893fd14264e1c5335fba2482";
+    const PSS_SHORT_SALT: &str = "\
+        0016000b00806539fed781ce2f0bff948ee7922442bfa9f674b1559abcf834c3cb574c2cce1aa5cc4485b68762c6a592\
+        a7d1bb436ea068ff6e135e41292b7146f54429009be82fc9ab6a344dcfe2dbcda1fbe13cb9d35a068da7d875935b5bbf\
+        9e9f10782b39d08ce4baf5a2f11e1fa1e80bb83f03ca9eb10606cce1ac4688b9322b3c76e283";
+    /// RSASSA signature of ATTEST
+    const RSASSA: &str = "\
+        0014000b008089b8f6354af8220c1f0ac341a970d6d16eaeb0eb7bb5deff99768586375e101c05ef2715cac7d8a2dceb\
+        8f9a80c87396adccf554c36ea68c1eb3e5e4c7693e616928a643a66cabdbd17fd5559cc4c09ffef5db0b4074dbdf3ac3\
+        222cd4078ba5698f93c228ff04b6dfe4a09a8b57a59f8b2bd2c647fd390e0192f9e3cd72ef91";
+    /// TPM2B_PUBLIC of a NIST P-256 key and its ECDSA signature of ATTEST
+    const P256_PUBLIC: &str = "\
+        00580023000b00040072000000100018000b000300100020d5c60be1db04e8e9b2782b6de322b7d2385bba26fae2f524\
+        df023363d59e31380020e588ddfb2cd482f6f54bbf3ecba7cc570269d6ec99c3f0221da854a15aeb644e";
+    const P256_SIGNATURE: &str = "\
+        0018000b00205d89a57bba065ad9733464548ecc3a82cc5ed63fc04e1ce3c0e89cb3a7f9de100020024b71c221506d2a\
+        cc2f030173b5dcf02f19a02a980ed4722f8d27888c793646";
+
+    fn bytes(text: &str) -> Vec<u8> {
+        hex::decode(text).unwrap()
+    }
+
+    fn quote() -> Quote {
+        Quote::parse(&bytes(ATTEST)).unwrap()
+    }
+
+    fn rsa_key() -> QuotePublicKey {
+        QuotePublicKey::parse(&bytes(RSA_PUBLIC)).unwrap()
+    }
+
+    #[test]
+    fn parses_tpms_attest() {
+        let quote = quote();
+        assert_eq!(quote.qualified_signer, b"\x00\x0bsigner");
+        assert_eq!(quote.extra_data, [0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(
+            quote.clock_info,
+            ClockInfo { clock: 123_456, reset_count: 5, restart_count: 2, safe: true }
+        );
+        assert_eq!(quote.firmware_version_string(), "1.2.3.4");
+        assert_eq!(quote.pcr_selections, [PcrSelection::new(HashAlgorithm::SHA256, vec![0, 1]).unwrap()]);
+        assert_eq!(quote.pcr_digest, PcrSimulator::hash(&HashAlgorithm::SHA256, &[0; 64]));
+        assert_eq!(quote.attest, bytes(ATTEST));
+
+        // The same structure as a TPM2B_ATTEST
+        let attest = bytes(ATTEST);
+        let tpm2b = [(attest.len() as u16).to_be_bytes().to_vec(), attest.clone()].concat();
+        assert_eq!(Quote::parse(&tpm2b).unwrap().attest, attest);
+
+        assert!(matches!(Quote::parse(&attest[..attest.len() - 1]), Err(Error::Parse(_))));
+    }
+
+    #[test]
+    fn parses_tpm2b_public() {
+        assert_eq!(rsa_key().to_string(), "RSA-1024");
+        assert_eq!(QuotePublicKey::parse(&bytes(P256_PUBLIC)).unwrap().to_string(), "ECC NIST P-256");
+    }
+
+    #[test]
+    fn verifies_signatures() {
+        let quote = quote();
+        let rsassa = QuoteSignature::parse(&bytes(RSASSA)).unwrap();
+        assert_eq!(rsassa.scheme_name(), "RSASSA");
+        assert!(rsa_key().verify(&quote.attest, &rsassa).is_ok());
+
+        let p256 = QuotePublicKey::parse(&bytes(P256_PUBLIC)).unwrap();
+        let ecdsa = QuoteSignature::parse(&bytes(P256_SIGNATURE)).unwrap();
+        assert!(p256.verify(&quote.attest, &ecdsa).is_ok());
+        assert!(matches!(p256.verify(&quote.attest[1..], &ecdsa), Err(Error::Signature(_))));
+        assert!(matches!(rsa_key().verify(&quote.attest, &ecdsa), Err(Error::Signature(_))));
+    }
+
+    #[test]
+    fn rsa_pss_accepts_digest_and_longest_salt() {
+        let quote = quote();
+        for signature in [PSS_DIGEST_SALT, PSS_MAX_SALT] {
+            let signature = QuoteSignature::parse(&bytes(signature)).unwrap();
+            assert!(rsa_key().verify(&quote.attest, &signature).is_ok());
+        }
+
+        let short_salt = QuoteSignature::parse(&bytes(PSS_SHORT_SALT)).unwrap();
+        assert!(matches!(rsa_key().verify(&quote.attest, &short_salt), Err(Error::Signature(_))));
+    }
+
+    #[test]
+    fn quote_without_signature_is_unverified() {
+        let quote = quote();
+        let simulator = PcrSimulator::new(HashAlgorithm::SHA256);
+        let signature = QuoteSignature::parse(&bytes(RSASSA)).unwrap();
+        let nonce = [0xde, 0xad, 0xbe, 0xef];
+
+        let unsigned = simulator.verify_quote(&quote, None, Some(&nonce));
+        assert!(unsigned.pcr_digest_matches(&quote));
+        assert!(!unsigned.is_signature_verified());
+        assert_eq!(unsigned.status(&quote), QuoteStatus::Unverified);
+
+        let signed = simulator.verify_quote(&quote, Some((&signature, &rsa_key())), Some(&nonce));
+        assert!(signed.is_signature_verified());
+        assert_eq!(signed.status(&quote), QuoteStatus::Valid);
+
+        let replayed = simulator.verify_quote(&quote, Some((&signature, &rsa_key())), Some(&[0; 4]));
+        assert_eq!(replayed.status(&quote), QuoteStatus::Invalid);
+
+        let mut extended = simulator.clone();
+        extended.extend_pcr(0, &[TaggedDigest::new(HashAlgorithm::SHA256, vec![0x11; 32])]).unwrap();
+        assert_eq!(extended.verify_quote(&quote, None, None).status(&quote), QuoteStatus::Invalid);
+    }
+}
@@ -0,0 +1,335 @@
+use std::fmt;
+use crate::error::{Error, Result};
+use crate::models::measurement_event::MeasurementEvent;
+use crate::models::pcr_simulator::{HashAlgorithm, PcrSimulator, PCR_COUNT};
+use crate::utils::tcg_event_log::event_type_name;
+
+/// Condition that stops a running replay after the matching event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Breakpoint {
+    EventIndex(usize),
+    Pcr(usize),
+    EventType(u32),
+}
+
+impl Breakpoint {
+    /// Check whether the event at `index` triggers the breakpoint
+    pub fn matches(&self, index: usize, event: &MeasurementEvent) -> bool {
+        match *self {
+            Breakpoint::EventIndex(event_index) => index == event_index,
+            Breakpoint::Pcr(pcr_index) => event.pcr_index == pcr_index,
+            Breakpoint::EventType(event_type) => event.event_type == Some(event_type),
+        }
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Breakpoint::EventIndex(index) => write!(f, "event #{}", index),
+            Breakpoint::Pcr(pcr_index) => write!(f, "PCR {}", pcr_index),
+            Breakpoint::EventType(event_type) => write!(f, "{} (0x{:08x})", event_type_name(event_type), event_type),
+        }
+    }
+}
+
+/// Extend of one bank: `after = H(before || digest)`
+#[derive(Debug, Clone)]
+pub struct BankExtend {
+    pub algorithm: HashAlgorithm,
+    pub before: Vec<u8>,
+    pub digest: Vec<u8>,
+    pub after: Vec<u8>,
+}
+
+impl BankExtend {
+    /// The exact bytes that were hashed, the old PCR value followed by the digest
+    pub fn hashed_bytes(&self) -> Vec<u8> {
+        [self.before.as_slice(), self.digest.as_slice()].concat()
+    }
+}
+
+/// One replayed event with the extend of every bank it touched
+#[derive(Debug, Clone)]
+pub struct ReplayStep {
+    pub event_index: usize,
+    pub pcr_index: usize,
+    pub extends: Vec<BankExtend>,
+}
+
+/// Step-through replay with breakpoints
+///
+/// Replays the same way as [`PcrSimulator::replay`] or [`PcrSimulator::replay_events`],
+/// one event at a time, on a copy of the simulator.
+#[derive(Debug, Clone)]
+pub struct ReplayDebugger {
+    initial: PcrSimulator,
+    simulator: PcrSimulator,
+    events: Vec<(usize, MeasurementEvent)>, // Events to extend with their index in the input
+    steps: Vec<ReplayStep>,
+    pub breakpoints: Vec<Breakpoint>,
+}
+
+impl ReplayDebugger {
+    /// Debug [`PcrSimulator::replay`]: every event is extended into `pcr_index`
+    pub fn for_pcr(simulator: &PcrSimulator, pcr_index: usize, events: Vec<MeasurementEvent>) -> Result<Self> {
+        if pcr_index >= PCR_COUNT {
+            return Err(Error::InvalidPcrIndex(pcr_index));
+        }
+
+        let mut initial = simulator.clone();
+        initial.restart_pcr(pcr_index);
+
+        let events = events
+            .into_iter()
+            .map(|mut event| {
+                event.pcr_index = pcr_index;
+                event
+            })
+            .enumerate()
+            .filter(|(_, event)| event.is_extended())
+            .collect();
+        Ok(Self::new(initial, events))
+    }
+
+    /// Debug [`PcrSimulator::replay_events`]: every event is extended into its own PCR
+    pub fn for_log(simulator: &PcrSimulator, events: Vec<MeasurementEvent>, startup_locality: Option<u8>) -> Result<Self> {
+        let mut initial = simulator.clone();
+        initial.reset_for_events(&events, startup_locality)?;
+
+        let events: Vec<(usize, MeasurementEvent)> = events
+            .into_iter()
+            .enumerate()
+            .filter(|(_, event)| event.is_extended())
+            .collect();
+        for (i, event) in &events {
+            for algorithm in initial.active_algorithms() {
+                if event.digest_for(&algorithm).is_none() {
+                    return Err(Error::MissingDigest { event: *i, algorithm });
+                }
+            }
+        }
+        Ok(Self::new(initial, events))
+    }
+
+    fn new(initial: PcrSimulator, events: Vec<(usize, MeasurementEvent)>) -> Self {
+        Self {
+            simulator: initial.clone(),
+            initial,
+            events,
+            steps: Vec::new(),
+            breakpoints: Vec::new(),
+        }
+    }
+
+    /// Simulator state after the replayed steps
+    pub fn simulator(&self) -> &PcrSimulator {
+        &self.simulator
+    }
+
+    /// Events to replay, with their index in the input
+    pub fn events(&self) -> &[(usize, MeasurementEvent)] {
+        &self.events
+    }
+
+    /// Replayed steps, oldest first
+    pub fn steps(&self) -> &[ReplayStep] {
+        &self.steps
+    }
+
+    /// Number of replayed events
+    pub fn position(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.steps.len() == self.events.len()
+    }
+
+    /// Next event to replay, with its index in the input
+    pub fn next_event(&self) -> Option<&(usize, MeasurementEvent)> {
+        self.events.get(self.steps.len())
+    }
+
+    /// Replay the next event; returns `false` at the end
+    pub fn step_forward(&mut self) -> Result<bool> {
+        let Some((event_index, event)) = self.events.get(self.steps.len()).cloned() else {
+            return Ok(false);
+        };
+
+        let before: Vec<(HashAlgorithm, Vec<u8>)> = self.simulator.banks()
+            .iter()
+            .map(|bank| (bank.algorithm.clone(), bank.pcr_values[event.pcr_index].clone()))
+            .collect();
+        let pcr_index = event.pcr_index;
+        let digests = event.digests.clone();
+        self.simulator.apply_event(event)?;
+
+        // Banks without a digest in the event are not extended
+        let extends = before
+            .into_iter()
+            .filter_map(|(algorithm, before)| {
+                let digest = digests.iter().find(|tagged| tagged.algorithm == algorithm)?.digest.clone();
+                let after = self.simulator.bank(&algorithm)?.pcr_values[pcr_index].clone();
+                Some(BankExtend { algorithm, before, digest, after })
+            })
+            .collect();
+        self.steps.push(ReplayStep { event_index, pcr_index, extends });
+        Ok(true)
+    }
+
+    /// Undo the last replayed event; returns `false` at the start
+    pub fn step_back(&mut self) -> bool {
+        let Some(step) = self.steps.pop() else {
+            return false;
+        };
+
+        for extend in step.extends {
+            if let Some(bank) = self.simulator.banks.iter_mut().find(|bank| bank.algorithm == extend.algorithm) {
+                bank.pcr_values[step.pcr_index] = extend.before;
+            }
+        }
+        self.simulator.measurement_log.pop();
+        true
+    }
+
+    /// Replay until the end or until an event matching a breakpoint has been replayed
+    ///
+    /// Returns the breakpoint that stopped the replay, if any.
+    pub fn run(&mut self) -> Result<Option<Breakpoint>> {
+        while self.step_forward()? {
+            let (event_index, event) = &self.events[self.steps.len() - 1];
+            if let Some(breakpoint) = self.breakpoints.iter().find(|breakpoint| breakpoint.matches(*event_index, event)) {
+                return Ok(Some(breakpoint.clone()));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Go back to the state before the first event
+    pub fn reset(&mut self) {
+        self.simulator = self.initial.clone();
+        self.steps.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::pcr_simulator::TaggedDigest;
+    use crate::utils::tcg_event_log::{EV_NO_ACTION, EV_SEPARATOR};
+
+    fn event(pcr_index: usize, data: &[u8]) -> MeasurementEvent {
+        let digests = [HashAlgorithm::SHA1, HashAlgorithm::SHA256]
+            .into_iter()
+            .map(|algorithm| TaggedDigest::new(algorithm.clone(), PcrSimulator::hash(&algorithm, data)))
+            .collect();
+        MeasurementEvent::new(pcr_index, digests, String::from_utf8_lossy(data).to_string())
+    }
+
+    fn simulator() -> PcrSimulator {
+        PcrSimulator::with_banks(vec![HashAlgorithm::SHA1, HashAlgorithm::SHA256])
+    }
+
+    fn events() -> Vec<MeasurementEvent> {
+        vec![
+            event(0, b"crtm"),
+            event(0, b"spec id").with_event_type(EV_NO_ACTION),
+            event(4, b"shim"),
+            event(7, b"separator").with_event_type(EV_SEPARATOR),
+            event(4, b"grub"),
+        ]
+    }
+
+    fn pcr_values(simulator: &PcrSimulator) -> Vec<Vec<Vec<u8>>> {
+        simulator.banks().iter().map(|bank| bank.pcr_values.clone()).collect()
+    }
+
+    #[test]
+    fn stepping_through_matches_replay_events() {
+        let mut debugger = ReplayDebugger::for_log(&simulator(), events(), None).unwrap();
+        // EV_NO_ACTION is not extended
+        let indices: Vec<usize> = debugger.events().iter().map(|(index, _)| *index).collect();
+        assert_eq!(indices, [0, 2, 3, 4]);
+        assert_eq!(debugger.next_event().unwrap().0, 0);
+
+        while debugger.step_forward().unwrap() {}
+        assert!(debugger.is_finished());
+        assert_eq!(debugger.position(), 4);
+
+        let mut replayed = simulator();
+        replayed.replay_events(events(), None).unwrap();
+        assert_eq!(pcr_values(debugger.simulator()), pcr_values(&replayed));
+        assert_eq!(debugger.simulator().measurement_log().len(), 4);
+
+        let step = &debugger.steps()[1];
+        assert_eq!((step.event_index, step.pcr_index, step.extends.len()), (2, 4, 2));
+        for extend in &step.extends {
+            assert_eq!(extend.after, PcrSimulator::hash(&extend.algorithm, &extend.hashed_bytes()));
+        }
+    }
+
+    #[test]
+    fn step_back_restores_the_previous_state() {
+        let mut debugger = ReplayDebugger::for_log(&simulator(), events(), None).unwrap();
+        assert!(!debugger.step_back());
+
+        debugger.step_forward().unwrap();
+        let after_first = pcr_values(debugger.simulator());
+        debugger.step_forward().unwrap();
+        assert_ne!(pcr_values(debugger.simulator()), after_first);
+
+        assert!(debugger.step_back());
+        assert_eq!(pcr_values(debugger.simulator()), after_first);
+        assert_eq!(debugger.simulator().measurement_log().len(), 1);
+        assert_eq!(debugger.next_event().unwrap().0, 2);
+
+        debugger.reset();
+        assert_eq!(debugger.position(), 0);
+        assert_eq!(pcr_values(debugger.simulator()), pcr_values(&simulator()));
+    }
+
+    #[test]
+    fn run_stops_after_breakpoints() {
+        let mut debugger = ReplayDebugger::for_log(&simulator(), events(), None).unwrap();
+        debugger.breakpoints = vec![Breakpoint::Pcr(4), Breakpoint::EventType(EV_SEPARATOR)];
+
+        assert_eq!(debugger.run().unwrap(), Some(Breakpoint::Pcr(4)));
+        assert_eq!(debugger.steps().last().unwrap().event_index, 2);
+        assert_eq!(debugger.run().unwrap(), Some(Breakpoint::EventType(EV_SEPARATOR)));
+        assert_eq!(debugger.steps().last().unwrap().event_index, 3);
+        assert_eq!(debugger.run().unwrap(), Some(Breakpoint::Pcr(4)));
+        assert_eq!(debugger.run().unwrap(), None);
+        assert!(debugger.is_finished());
+        assert!(!debugger.step_forward().unwrap());
+
+        debugger.reset();
+        debugger.breakpoints = vec![Breakpoint::EventIndex(3)];
+        assert_eq!(debugger.run().unwrap(), Some(Breakpoint::EventIndex(3)));
+        assert_eq!(debugger.position(), 3);
+
+        assert_eq!(Breakpoint::EventIndex(3).to_string(), "event #3");
+        assert_eq!(Breakpoint::EventType(EV_SEPARATOR).to_string(), "EV_SEPARATOR (0x00000004)");
+    }
+
+    #[test]
+    fn for_pcr_extends_every_event_into_one_pcr() {
+        let mut debugger = ReplayDebugger::for_pcr(&simulator(), 8, events()).unwrap();
+        assert!(debugger.events().iter().all(|(_, event)| event.pcr_index == 8));
+        assert_eq!(debugger.events().len(), 4);
+        debugger.run().unwrap();
+
+        let mut replayed = simulator();
+        replayed.replay(8, events()).unwrap();
+        assert_eq!(pcr_values(debugger.simulator()), pcr_values(&replayed));
+
+        assert!(matches!(ReplayDebugger::for_pcr(&simulator(), PCR_COUNT, events()), Err(Error::InvalidPcrIndex(24))));
+    }
+
+    #[test]
+    fn for_log_needs_a_digest_for_every_bank() {
+        let sha1_only = MeasurementEvent::new(4, vec![TaggedDigest::new(HashAlgorithm::SHA1, vec![0; 20])], String::new());
+        let result = ReplayDebugger::for_log(&simulator(), vec![event(0, b"crtm"), sha1_only], None);
+        assert!(matches!(result, Err(Error::MissingDigest { event: 1, algorithm: HashAlgorithm::SHA256 })));
+    }
+}
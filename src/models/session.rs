@@ -0,0 +1,434 @@
+use std::fs;
+use serde_json::{json, Map, Value};
+use crate::error::{Error, Result};
+use crate::models::locality::LOCALITY_COUNT;
+use crate::models::measurement_event::{EventSource, MeasurementEvent};
+use crate::models::pcr_simulator::{DigestPadding, HashAlgorithm, PcrBank, PcrSimulator, TaggedDigest, PCR_COUNT};
+use crate::models::verification::{GoldenValue, GoldenValues};
+
+/// Identifies session files
+const SESSION_FORMAT: &str = "eggs-session";
+/// Current version of the session file format
+pub const SESSION_VERSION: u64 = 1;
+
+/// Saved Boot Replay session: banks, PCR values, measurement log, loaded file and golden values
+///
+/// Sessions are stored as JSON. Digests and event data are lowercase hex, algorithms use
+/// [`HashAlgorithm::name`] and `event_type`/`file_path` may be `null`:
+///
+/// ```text
+/// {
+///   "format": "eggs-session",
+///   "version": 1,
+///   "banks": [{ "algorithm": "SHA256", "pcrs": ["00..00", ... 24 values] }],
+///   "locality": 0, "startup_locality": 3, "reset_pcrs": [16],
+///   "measurement_log": [{
+///     "pcr": 4, "event_type": 13, "description": "shim",
+///     "digests": [{ "algorithm": "SHA256", "digest": "3d45..." }],
+///     "event_data": "", "uintn_size": 8, "timestamp": 1700000000,
+///     "source": { "kind": "file", "path": "boot.txt", "line": 3 }
+///   }],
+///   "file_path": "boot.txt",
+///   "golden_values": [{ "algorithm": "SHA256", "pcr": 4, "value": "9f86..." }]
+/// }
+/// ```
+///
+/// Event sources are `{"kind": "manual"}`, `{"kind": "file", "path", "line"}` or
+/// `{"kind": "event_log", "path", "index"}`.
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub simulator: PcrSimulator,
+    pub file_path: Option<String>, // Measurement file loaded when the session was saved
+    pub golden_values: GoldenValues,
+}
+
+impl Session {
+    pub fn new(simulator: PcrSimulator, file_path: Option<String>, golden_values: GoldenValues) -> Self {
+        Self { simulator, file_path, golden_values }
+    }
+
+    /// Serialize to pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        let banks: Vec<Value> = self.simulator.banks()
+            .iter()
+            .map(|bank| json!({
+                "algorithm": bank.algorithm.name(),
+                "pcrs": bank.pcr_values.iter().map(hex::encode).collect::<Vec<_>>(),
+            }))
+            .collect();
+        let measurement_log: Vec<Value> = self.simulator.measurement_log().iter().map(event_to_json).collect();
+        let golden_values: Vec<Value> = self.golden_values.values
+            .iter()
+            .map(|golden| json!({
+                "algorithm": golden.algorithm.name(),
+                "pcr": golden.pcr_index,
+                "value": hex::encode(&golden.value),
+            }))
+            .collect();
+
+        let session = json!({
+            "format": SESSION_FORMAT,
+            "version": SESSION_VERSION,
+            "banks": banks,
+            "locality": self.simulator.locality(),
+            "startup_locality": self.simulator.startup_locality(),
+            "reset_pcrs": (0..PCR_COUNT).filter(|&i| self.simulator.reset_pcrs()[i]).collect::<Vec<_>>(),
+            "measurement_log": measurement_log,
+            "file_path": self.file_path,
+            "golden_values": golden_values,
+        });
+        serde_json::to_string_pretty(&session).unwrap_or_default()
+    }
+
+    /// Parse a session from JSON, checking every PCR value and digest against its bank
+    pub fn from_json(text: &str) -> Result<Self> {
+        let session: Value = serde_json::from_str(text)
+            .map_err(|err| Error::Parse(format!("Invalid session file: {}", err)))?;
+        let session = as_object(&session, "session")?;
+
+        if session.get("format").and_then(Value::as_str) != Some(SESSION_FORMAT) {
+            return Err(Error::Parse("Not an eggs session file".to_string()));
+        }
+        let version = field(session, "version")?.as_u64().unwrap_or(0);
+        if version == 0 || version > SESSION_VERSION {
+            return Err(Error::Parse(format!("Unsupported session version {}", version)));
+        }
+
+        let mut banks = Vec::new();
+        for bank in as_array(field(session, "banks")?, "banks")? {
+            let bank = as_object(bank, "bank")?;
+            let algorithm = algorithm_field(bank)?;
+            if banks.iter().any(|bank: &PcrBank| bank.algorithm == algorithm) {
+                return Err(Error::Parse(format!("Duplicate {} bank", algorithm.name())));
+            }
+            let pcr_values = as_array(field(bank, "pcrs")?, "pcrs")?
+                .iter()
+                .map(|value| algorithm.check_digest(hex_value(value, "pcrs")?, DigestPadding::Exact))
+                .collect::<Result<Vec<_>>>()?;
+            if pcr_values.len() != PCR_COUNT {
+                return Err(Error::Parse(format!(
+                    "{} bank has {} PCR values, expected {}",
+                    algorithm.name(),
+                    pcr_values.len(),
+                    PCR_COUNT
+                )));
+            }
+            banks.push(PcrBank { algorithm, pcr_values });
+        }
+        if banks.is_empty() {
+            return Err(Error::Parse("Session has no active banks".to_string()));
+        }
+
+        // Sessions saved before localities were modelled start at locality 0
+        let locality = optional_locality(session, "locality")?;
+        let startup_locality = optional_locality(session, "startup_locality")?;
+        if ![0, 3, 4].contains(&startup_locality) {
+            return Err(Error::InvalidLocality(startup_locality));
+        }
+        let mut reset_pcrs = [false; PCR_COUNT];
+        if let Some(pcrs) = session.get("reset_pcrs") {
+            for pcr in as_array(pcrs, "reset_pcrs")? {
+                match pcr.as_u64() {
+                    Some(pcr_index) if (pcr_index as usize) < PCR_COUNT => reset_pcrs[pcr_index as usize] = true,
+                    _ => return Err(Error::Parse(format!("Invalid PCR index {} in 'reset_pcrs'", pcr))),
+                }
+            }
+        }
+
+        let measurement_log = as_array(field(session, "measurement_log")?, "measurement_log")?
+            .iter()
+            .enumerate()
+            .map(|(i, event)| event_from_json(event).map_err(|err| Error::Parse(format!("Log event {}: {}", i + 1, err))))
+            .collect::<Result<Vec<_>>>()?;
+
+        let file_path = session.get("file_path").and_then(Value::as_str).map(String::from);
+
+        let mut golden_values = Vec::new();
+        if let Some(values) = session.get("golden_values") {
+            for golden in as_array(values, "golden_values")? {
+                let golden = as_object(golden, "golden value")?;
+                let algorithm = algorithm_field(golden)?;
+                let value = algorithm.check_digest(hex_value(field(golden, "value")?, "value")?, DigestPadding::Exact)?;
+                golden_values.push(GoldenValue {
+                    algorithm,
+                    pcr_index: pcr_field(golden)?,
+                    value,
+                });
+            }
+        }
+
+        Ok(Self {
+            simulator: PcrSimulator {
+                banks,
+                measurement_log,
+                locality,
+                startup_locality,
+                reset_pcrs,
+                hash_sequence: None,
+            },
+            file_path,
+            golden_values: GoldenValues::new(golden_values),
+        })
+    }
+
+    /// Write the session to a JSON file
+    pub fn save(&self, path: &str) -> Result<()> {
+        fs::write(path, self.to_json())?;
+        Ok(())
+    }
+
+    /// Read a session from a JSON file
+    pub fn load(path: &str) -> Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+/// JSON object of a measurement event, as stored in session files
+pub(crate) fn event_to_json(event: &MeasurementEvent) -> Value {
+    let source = match event.source {
+        EventSource::Manual => json!({ "kind": "manual" }),
+        EventSource::File { ref path, line } => json!({ "kind": "file", "path": path, "line": line }),
+        EventSource::EventLog { ref path, index } => json!({ "kind": "event_log", "path": path, "index": index }),
+    };
+    let digests: Vec<Value> = event.digests
+        .iter()
+        .map(|tagged| json!({
+            "algorithm": tagged.algorithm.name(),
+            "digest": hex::encode(&tagged.digest),
+        }))
+        .collect();
+
+    json!({
+        "pcr": event.pcr_index,
+        "event_type": event.event_type,
+        "description": event.description,
+        "digests": digests,
+        "event_data": hex::encode(&event.event_data),
+        "uintn_size": event.uintn_size,
+        "timestamp": event.timestamp,
+        "source": source,
+    })
+}
+
+fn event_from_json(event: &Value) -> Result<MeasurementEvent> {
+    let event = as_object(event, "event")?;
+
+    let mut digests = Vec::new();
+    for tagged in as_array(field(event, "digests")?, "digests")? {
+        let tagged = as_object(tagged, "digest")?;
+        let algorithm = algorithm_field(tagged)?;
+        let digest = algorithm.check_digest(hex_value(field(tagged, "digest")?, "digest")?, DigestPadding::Exact)?;
+        digests.push(TaggedDigest::new(algorithm, digest));
+    }
+
+    let source = match event.get("source") {
+        None | Some(Value::Null) => EventSource::Manual,
+        Some(source) => {
+            let source = as_object(source, "source")?;
+            let path = || source.get("path").and_then(Value::as_str).unwrap_or_default().to_string();
+            match source.get("kind").and_then(Value::as_str) {
+                Some("manual") => EventSource::Manual,
+                Some("file") => EventSource::File { path: path(), line: usize_field(source, "line")? },
+                Some("event_log") => EventSource::EventLog { path: path(), index: usize_field(source, "index")? },
+                _ => return Err(Error::Parse("Unknown event source".to_string())),
+            }
+        },
+    };
+
+    let event_type = match event.get("event_type") {
+        None | Some(Value::Null) => None,
+        Some(value) => Some(value
+            .as_u64()
+            .and_then(|event_type| u32::try_from(event_type).ok())
+            .ok_or_else(|| Error::Parse("Invalid event_type".to_string()))?),
+    };
+
+    Ok(MeasurementEvent {
+        pcr_index: pcr_field(event)?,
+        event_type,
+        digests,
+        event_data: match event.get("event_data") {
+            Some(data) => hex_value(data, "event_data")?,
+            None => Vec::new(),
+        },
+        uintn_size: match event.get("uintn_size") {
+            None | Some(Value::Null) => 8,
+            Some(value) => match value.as_u64() {
+                Some(4) => 4,
+                Some(8) => 8,
+                _ => return Err(Error::Parse("uintn_size must be 4 or 8".to_string())),
+            },
+        },
+        description: event.get("description").and_then(Value::as_str).unwrap_or_default().to_string(),
+        source,
+        timestamp: event.get("timestamp").and_then(Value::as_u64).unwrap_or(0),
+    })
+}
+
+fn field<'a>(object: &'a Map<String, Value>, name: &str) -> Result<&'a Value> {
+    object.get(name).ok_or_else(|| Error::Parse(format!("Missing '{}'", name)))
+}
+
+fn as_object<'a>(value: &'a Value, name: &str) -> Result<&'a Map<String, Value>> {
+    value.as_object().ok_or_else(|| Error::Parse(format!("'{}' must be an object", name)))
+}
+
+fn as_array<'a>(value: &'a Value, name: &str) -> Result<&'a Vec<Value>> {
+    value.as_array().ok_or_else(|| Error::Parse(format!("'{}' must be an array", name)))
+}
+
+fn hex_value(value: &Value, name: &str) -> Result<Vec<u8>> {
+    let text = value.as_str().ok_or_else(|| Error::Parse(format!("'{}' must be a hex string", name)))?;
+    Ok(hex::decode(text)?)
+}
+
+fn usize_field(object: &Map<String, Value>, name: &str) -> Result<usize> {
+    field(object, name)?
+        .as_u64()
+        .map(|value| value as usize)
+        .ok_or_else(|| Error::Parse(format!("'{}' must be a non-negative integer", name)))
+}
+
+fn optional_locality(object: &Map<String, Value>, name: &str) -> Result<u8> {
+    match object.get(name) {
+        None => Ok(0),
+        Some(value) => value
+            .as_u64()
+            .filter(|&locality| locality < u64::from(LOCALITY_COUNT))
+            .map(|locality| locality as u8)
+            .ok_or_else(|| Error::Parse(format!("'{}' must be a locality (0-{})", name, LOCALITY_COUNT - 1))),
+    }
+}
+
+fn pcr_field(object: &Map<String, Value>) -> Result<usize> {
+    match usize_field(object, "pcr")? {
+        pcr_index if pcr_index < PCR_COUNT => Ok(pcr_index),
+        pcr_index => Err(Error::InvalidPcrIndex(pcr_index)),
+    }
+}
+
+fn algorithm_field(object: &Map<String, Value>) -> Result<HashAlgorithm> {
+    let name = field(object, "algorithm")?.as_str().unwrap_or_default();
+    name.parse()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(pcr_index: usize, data: &[u8]) -> MeasurementEvent {
+        let digests = [HashAlgorithm::SHA1, HashAlgorithm::SHA256]
+            .into_iter()
+            .map(|algorithm| TaggedDigest::new(algorithm.clone(), PcrSimulator::hash(&algorithm, data)))
+            .collect();
+        MeasurementEvent::new(pcr_index, digests, String::from_utf8_lossy(data).to_string())
+    }
+
+    fn session() -> Session {
+        let mut simulator = PcrSimulator::with_banks(vec![HashAlgorithm::SHA1, HashAlgorithm::SHA256]);
+        simulator.set_startup_locality(3).unwrap();
+        simulator.apply_event(event(0, b"crtm").with_event_type(8).with_event_data(b"1.00".to_vec())).unwrap();
+        simulator
+            .apply_event(event(4, b"shim").with_uintn_size(4).with_source(EventSource::EventLog { path: "log.bin".to_string(), index: 7 }))
+            .unwrap();
+        simulator.apply_event(event(8, b"grub").with_source(EventSource::File { path: "boot.txt".to_string(), line: 3 })).unwrap();
+        simulator.reset_pcr(16).unwrap();
+        simulator.set_locality(2).unwrap();
+
+        let golden_values = GoldenValues::new(vec![GoldenValue {
+            algorithm: HashAlgorithm::SHA256,
+            pcr_index: 4,
+            value: simulator.bank(&HashAlgorithm::SHA256).unwrap().pcr_values[4].clone(),
+        }]);
+        Session::new(simulator, Some("boot.txt".to_string()), golden_values)
+    }
+
+    /// Session JSON changed by `edit`
+    fn edited(edit: impl FnOnce(&mut Map<String, Value>)) -> String {
+        let mut value: Value = serde_json::from_str(&session().to_json()).unwrap();
+        edit(value.as_object_mut().unwrap());
+        value.to_string()
+    }
+
+    fn parse_error(text: &str) -> String {
+        Session::from_json(text).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let saved = session();
+        let loaded = Session::from_json(&saved.to_json()).unwrap();
+
+        let values = |session: &Session| -> Vec<Vec<Vec<u8>>> {
+            session.simulator.banks().iter().map(|bank| bank.pcr_values.clone()).collect()
+        };
+        assert_eq!(values(&loaded), values(&saved));
+        assert_eq!((loaded.simulator.locality(), loaded.simulator.startup_locality()), (2, 3));
+        assert_eq!(loaded.simulator.reset_pcrs(), saved.simulator.reset_pcrs());
+        assert_eq!(loaded.file_path.as_deref(), Some("boot.txt"));
+        assert_eq!(loaded.golden_values.values, saved.golden_values.values);
+
+        assert_eq!(loaded.simulator.measurement_log().len(), 3);
+        for (loaded, saved) in loaded.simulator.measurement_log().iter().zip(saved.simulator.measurement_log()) {
+            assert_eq!(loaded.pcr_index, saved.pcr_index);
+            assert_eq!(loaded.event_type, saved.event_type);
+            assert_eq!(loaded.digests, saved.digests);
+            assert_eq!(loaded.event_data, saved.event_data);
+            assert_eq!(loaded.uintn_size, saved.uintn_size);
+            assert_eq!(loaded.description, saved.description);
+            assert_eq!(loaded.source, saved.source);
+            assert_eq!(loaded.timestamp, saved.timestamp);
+        }
+    }
+
+    #[test]
+    fn older_sessions_default_the_optional_fields() {
+        let text = edited(|session| {
+            for name in ["locality", "startup_locality", "reset_pcrs", "golden_values", "file_path"] {
+                session.remove(name);
+            }
+            for event in session["measurement_log"].as_array_mut().unwrap() {
+                let event = event.as_object_mut().unwrap();
+                event.remove("uintn_size");
+                event.remove("source");
+            }
+        });
+        let loaded = Session::from_json(&text).unwrap();
+        assert_eq!((loaded.simulator.locality(), loaded.simulator.startup_locality()), (0, 0));
+        assert!(loaded.simulator.reset_pcrs().iter().all(|&reset| !reset));
+        assert!(loaded.golden_values.is_empty() && loaded.file_path.is_none());
+        assert!(loaded.simulator.measurement_log().iter().all(|event| event.uintn_size == 8 && event.source == EventSource::Manual));
+    }
+
+    #[test]
+    fn rejects_invalid_sessions() {
+        assert!(parse_error("{").starts_with("Invalid session file"));
+        for (edit, message) in [
+            ((|session: &mut Map<String, Value>| { session["format"] = json!("other"); }) as fn(&mut Map<String, Value>),
+                "Not an eggs session file"),
+            (|session| { session["version"] = json!(2); }, "Unsupported session version 2"),
+            (|session| { session["banks"] = json!([]); }, "Session has no active banks"),
+            (|session| {
+                let bank = session["banks"][0].clone();
+                session["banks"].as_array_mut().unwrap().push(bank);
+            }, "Duplicate SHA1 bank"),
+            (|session| { session["banks"][1]["pcrs"].as_array_mut().unwrap().pop(); }, "SHA256 bank has 23 PCR values, expected 24"),
+            (|session| { session["banks"][1]["algorithm"] = json!("MD5"); }, "Unknown algorithm 'MD5'"),
+            (|session| { session["locality"] = json!(5); }, "'locality' must be a locality (0-4)"),
+            (|session| { session["reset_pcrs"] = json!([24]); }, "Invalid PCR index 24 in 'reset_pcrs'"),
+            (|session| { session["measurement_log"][1]["pcr"] = json!(24); }, "Log event 2: Invalid PCR index: 24"),
+            (|session| { session["measurement_log"][0]["uintn_size"] = json!(6); }, "Log event 1: uintn_size must be 4 or 8"),
+            (|session| { session["measurement_log"][0]["source"] = json!({ "kind": "network" }); }, "Unknown event source"),
+            (|session| { session["measurement_log"][0]["event_type"] = json!(-1); }, "Invalid event_type"),
+            (|session| { session["golden_values"][0]["value"] = json!("1122"); }, "SHA256 digest must be 32 bytes"),
+        ] {
+            let error = parse_error(&edited(edit));
+            assert!(error.contains(message), "{:?} does not contain {:?}", error, message);
+        }
+
+        let startup = Session::from_json(&edited(|session| { session["startup_locality"] = json!(2); }));
+        assert!(matches!(startup, Err(Error::InvalidLocality(2))));
+        let digest = Session::from_json(&edited(|session| { session["banks"][0]["pcrs"][0] = json!("00"); }));
+        assert!(matches!(digest, Err(Error::DigestLength { expected: 20, actual: 1, .. })));
+    }
+}
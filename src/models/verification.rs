@@ -0,0 +1,356 @@
+use crate::error::{Error, Result};
+use crate::models::pcr_simulator::{HashAlgorithm, PcrSimulator, PCR_COUNT};
+
+/// Expected value of one PCR in one bank
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoldenValue {
+    pub algorithm: HashAlgorithm,
+    pub pcr_index: usize,
+    pub value: Vec<u8>,
+}
+
+/// Set of expected PCR values
+#[derive(Debug, Clone, Default)]
+pub struct GoldenValues {
+    pub values: Vec<GoldenValue>,
+}
+
+impl GoldenValues {
+    /// Create a set from individual values
+    pub fn new(values: Vec<GoldenValue>) -> Self {
+        Self { values }
+    }
+
+    /// Parse expected PCR values from text
+    ///
+    /// Accepts `tpm2_pcrread` output (a `sha256:` bank header followed by `0 : 0x...` lines)
+    /// as well as one value per line in the form `[bank:]pcr: hex`, `[bank:]pcr=hex` or
+    /// `pcr hex`. Lines without a bank use the most recent bank header or `default_algorithm`.
+    /// Empty lines and `#` comments are skipped.
+    pub fn parse(text: &str, default_algorithm: &HashAlgorithm) -> Result<Self> {
+        let mut values = Vec::new();
+        let mut current_algorithm = default_algorithm.clone();
+
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            let line = match line.find('#') {
+                Some(pos) => &line[..pos],
+                None => line,
+            };
+            let normalized = line.replace([':', '='], " ");
+            let tokens: Vec<&str> = normalized.split_whitespace().collect();
+
+            let (algorithm, pcr, hex_value) = match tokens.as_slice() {
+                [] => continue,
+                [name] => {
+                    // Bank header such as "sha256:"
+                    current_algorithm = name.parse::<HashAlgorithm>().map_err(|_| Error::Parse(
+                        format!("Line {}: unknown bank '{}'", line_number, name)
+                    ))?;
+                    continue;
+                },
+                [pcr, hex_value] => (current_algorithm.clone(), *pcr, *hex_value),
+                [name, pcr, hex_value] => {
+                    let algorithm = name.parse::<HashAlgorithm>().map_err(|_| Error::Parse(
+                        format!("Line {}: unknown bank '{}'", line_number, name)
+                    ))?;
+                    (algorithm, *pcr, *hex_value)
+                },
+                _ => return Err(Error::Parse(
+                    format!("Line {}: expected '[bank:]pcr: value'", line_number)
+                )),
+            };
+
+            let pcr_index = Self::parse_pcr_index(pcr).ok_or_else(|| Error::Parse(
+                format!("Line {}: invalid PCR index '{}'", line_number, pcr)
+            ))?;
+            let hex_value = hex_value.trim_start_matches("0x").trim_start_matches("0X");
+            let value = hex::decode(hex_value)
+                .map_err(|e| Error::Parse(format!("Line {}: invalid hex value: {}", line_number, e)))?;
+            if value.len() != algorithm.output_size_bytes() {
+                return Err(Error::Parse(format!(
+                    "Line {}: {} value must be {} bytes, got {}",
+                    line_number,
+                    algorithm.name(),
+                    algorithm.output_size_bytes(),
+                    value.len()
+                )));
+            }
+
+            values.push(GoldenValue {
+                algorithm,
+                pcr_index,
+                value,
+            });
+        }
+
+        Ok(Self { values })
+    }
+
+    /// Parse a PCR index such as `7`, `PCR7` or `pcr7`
+    fn parse_pcr_index(text: &str) -> Option<usize> {
+        let digits = text.trim_start_matches("PCR").trim_start_matches("pcr");
+        match digits.parse::<usize>() {
+            Ok(index) if index < PCR_COUNT => Some(index),
+            _ => None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Format as `bank:pcr: hex` lines, as accepted by [`GoldenValues::parse`]
+    pub fn to_text(&self) -> String {
+        self.values
+            .iter()
+            .map(|golden| format!("{}:{}: {}\n", golden.algorithm.name().to_lowercase(), golden.pcr_index, hex::encode(&golden.value)))
+            .collect()
+    }
+}
+
+/// Verification result of one PCR in one bank
+#[derive(Debug, Clone)]
+pub struct PcrVerification {
+    pub algorithm: HashAlgorithm,
+    pub pcr_index: usize,
+    pub expected: Vec<u8>,
+    pub replayed: Vec<u8>,
+    pub event_count: usize, // Number of log events extended into this PCR and bank
+    /// Log index of the first event after which the expected value can no longer be reached
+    pub first_divergent_event: Option<usize>,
+    /// Number of events after which the replayed value equalled the expected one, if any
+    pub matched_after: Option<usize>,
+}
+
+impl PcrVerification {
+    pub fn is_match(&self) -> bool {
+        self.expected == self.replayed
+    }
+
+    /// Describe the mismatch for display
+    pub fn summary(&self) -> String {
+        if self.is_match() {
+            return format!("match after {} events", self.event_count);
+        }
+
+        match (self.matched_after, self.first_divergent_event) {
+            (Some(0), Some(event)) => format!(
+                "expected value is the initial value; diverges at log event #{}",
+                event + 1
+            ),
+            (Some(count), Some(event)) => format!(
+                "expected value reached after {} of {} events; diverges at log event #{}",
+                count, self.event_count, event + 1
+            ),
+            (None, Some(event)) => format!(
+                "no prefix of the log reproduces the expected value; diverges at or before log event #{}",
+                event + 1
+            ),
+            _ => "no events extended into this PCR".to_string(),
+        }
+    }
+}
+
+/// Verification report for a set of golden values
+#[derive(Debug, Clone, Default)]
+pub struct VerificationReport {
+    pub results: Vec<PcrVerification>,
+    pub errors: Vec<String>, // Golden values that could not be checked
+}
+
+impl VerificationReport {
+    pub fn all_match(&self) -> bool {
+        self.errors.is_empty() && self.results.iter().all(|result| result.is_match())
+    }
+
+    pub fn mismatch_count(&self) -> usize {
+        self.results.iter().filter(|result| !result.is_match()).count()
+    }
+}
+
+impl PcrSimulator {
+    /// Verify the measurement log against expected PCR values
+    ///
+    /// Each PCR is replayed from its start value through the measurement log, and every
+    /// intermediate value is compared with the expected one to locate the divergent event.
+    pub fn verify(&self, golden_values: &GoldenValues) -> VerificationReport {
+        let mut report = VerificationReport::default();
+
+        for golden in &golden_values.values {
+            if golden.pcr_index >= PCR_COUNT {
+                report.errors.push(Error::InvalidPcrIndex(golden.pcr_index).to_string());
+                continue;
+            }
+            if self.bank(&golden.algorithm).is_none() {
+                report.errors.push(format!(
+                    "PCR{} {}: bank is not active",
+                    golden.pcr_index,
+                    golden.algorithm.name()
+                ));
+                continue;
+            }
+
+            let mut value = self.start_value(&golden.algorithm, golden.pcr_index);
+            let mut matched_after = if value == golden.value { Some(0) } else { None };
+            let mut event_indices = Vec::new();
+
+            for (log_index, event) in self.measurement_log.iter().enumerate() {
+                if event.pcr_index != golden.pcr_index {
+                    continue;
+                }
+                let Some(digest) = event.digest_for(&golden.algorithm) else {
+                    continue;
+                };
+
+                let mut data = value.clone();
+                data.extend_from_slice(digest);
+                value = Self::hash(&golden.algorithm, &data);
+                event_indices.push(log_index);
+
+                if value == golden.value {
+                    matched_after = Some(event_indices.len());
+                }
+            }
+
+            let first_divergent_event = if value == golden.value {
+                None
+            } else {
+                // The event right after the last state that still equalled the expected value
+                event_indices.get(matched_after.unwrap_or(0)).copied()
+            };
+
+            report.results.push(PcrVerification {
+                algorithm: golden.algorithm.clone(),
+                pcr_index: golden.pcr_index,
+                expected: golden.value.clone(),
+                replayed: value,
+                event_count: event_indices.len(),
+                first_divergent_event,
+                matched_after,
+            });
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::measurement_event::MeasurementEvent;
+    use crate::models::pcr_simulator::TaggedDigest;
+
+    fn extend(simulator: &mut PcrSimulator, pcr_index: usize, data: &[u8]) {
+        let digest = PcrSimulator::hash(&HashAlgorithm::SHA256, data);
+        let event = MeasurementEvent::new(pcr_index, vec![TaggedDigest::new(HashAlgorithm::SHA256, digest)], String::new());
+        simulator.apply_event(event).unwrap();
+    }
+
+    fn pcr_value(simulator: &PcrSimulator, pcr_index: usize) -> Vec<u8> {
+        simulator.bank(&HashAlgorithm::SHA256).unwrap().pcr_values[pcr_index].clone()
+    }
+
+    fn golden(pcr_index: usize, value: Vec<u8>) -> GoldenValues {
+        GoldenValues::new(vec![GoldenValue { algorithm: HashAlgorithm::SHA256, pcr_index, value }])
+    }
+
+    #[test]
+    fn parses_golden_value_formats() {
+        let sha1 = "11".repeat(20);
+        let sha256 = "22".repeat(32);
+        let text = format!(
+            "# expected values\nsha1:\n  0 : 0x{sha1}\nsha256:\n  7 : 0x{sha256}\nsha1:PCR4: {sha1}\npcr5={sha256} # default bank\n",
+            sha1 = sha1,
+            sha256 = sha256
+        );
+        let values = GoldenValues::parse(&text, &HashAlgorithm::SHA256).unwrap();
+        let parsed: Vec<(HashAlgorithm, usize)> = values.values.iter().map(|golden| (golden.algorithm.clone(), golden.pcr_index)).collect();
+        assert_eq!(parsed, [
+            (HashAlgorithm::SHA1, 0),
+            (HashAlgorithm::SHA256, 7),
+            (HashAlgorithm::SHA1, 4),
+            (HashAlgorithm::SHA256, 5),
+        ]);
+
+        let round_trip = GoldenValues::parse(&values.to_text(), &HashAlgorithm::SHA1).unwrap();
+        assert_eq!(round_trip.values, values.values);
+    }
+
+    #[test]
+    fn rejects_invalid_golden_values() {
+        for (text, message) in [
+            ("sha256:0: 1122", "Line 1: SHA256 value must be 32 bytes, got 2"),
+            (&format!("24: {}", "00".repeat(32)) as &str, "Line 1: invalid PCR index '24'"),
+            ("\nmd5:", "Line 2: unknown bank 'md5'"),
+            ("0: zz", "Line 1: invalid hex value: Invalid character 'z' at position 0"),
+        ] {
+            match GoldenValues::parse(text, &HashAlgorithm::SHA256) {
+                Err(Error::Parse(error)) => assert_eq!(error, message),
+                other => panic!("{:?} for {:?}", other, text),
+            }
+        }
+    }
+
+    #[test]
+    fn reports_matching_pcrs() {
+        let mut simulator = PcrSimulator::new(HashAlgorithm::SHA256);
+        extend(&mut simulator, 4, b"shim");
+        extend(&mut simulator, 7, b"db");
+        extend(&mut simulator, 4, b"grub");
+
+        let report = simulator.verify(&golden(4, pcr_value(&simulator, 4)));
+        assert!(report.all_match());
+        assert_eq!(report.mismatch_count(), 0);
+        assert_eq!(report.results[0].event_count, 2);
+        assert_eq!(report.results[0].first_divergent_event, None);
+        assert_eq!(report.results[0].summary(), "match after 2 events");
+    }
+
+    #[test]
+    fn locates_the_divergent_event() {
+        let mut expected = PcrSimulator::new(HashAlgorithm::SHA256);
+        extend(&mut expected, 4, b"shim");
+
+        let mut simulator = PcrSimulator::new(HashAlgorithm::SHA256);
+        extend(&mut simulator, 4, b"shim");
+        extend(&mut simulator, 7, b"db");
+        extend(&mut simulator, 4, b"grub");
+        extend(&mut simulator, 4, b"kernel");
+
+        // The expected value was reached after shim; grub (log event #3) diverges
+        let report = simulator.verify(&golden(4, pcr_value(&expected, 4)));
+        assert!(!report.all_match());
+        assert_eq!(report.mismatch_count(), 1);
+        let result = &report.results[0];
+        assert_eq!((result.matched_after, result.first_divergent_event), (Some(1), Some(2)));
+        assert_eq!(result.summary(), "expected value reached after 1 of 3 events; diverges at log event #3");
+
+        // The initial value diverges at the first event
+        let result = &simulator.verify(&golden(4, vec![0; 32])).results[0];
+        assert_eq!((result.matched_after, result.first_divergent_event), (Some(0), Some(0)));
+        assert_eq!(result.summary(), "expected value is the initial value; diverges at log event #1");
+
+        // A value never reached
+        let result = &simulator.verify(&golden(4, vec![0x55; 32])).results[0];
+        assert_eq!((result.matched_after, result.first_divergent_event), (None, Some(0)));
+
+        // Nothing was extended into the PCR
+        let result = &simulator.verify(&golden(9, vec![0x55; 32])).results[0];
+        assert_eq!(result.event_count, 0);
+        assert_eq!(result.summary(), "no events extended into this PCR");
+    }
+
+    #[test]
+    fn reports_golden_values_that_cannot_be_checked() {
+        let simulator = PcrSimulator::new(HashAlgorithm::SHA256);
+        let golden_values = GoldenValues::new(vec![
+            GoldenValue { algorithm: HashAlgorithm::SHA1, pcr_index: 0, value: vec![0; 20] },
+            GoldenValue { algorithm: HashAlgorithm::SHA256, pcr_index: PCR_COUNT, value: vec![0; 32] },
+        ]);
+        let report = simulator.verify(&golden_values);
+        assert!(report.results.is_empty());
+        assert_eq!(report.errors, ["PCR0 SHA1: bank is not active", "Invalid PCR index: 24"]);
+        assert!(!report.all_match());
+    }
+}
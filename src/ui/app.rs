@@ -1,12 +1,14 @@
 use eframe::egui;
 use crate::ui::hash_calculator::HashCalculatorApp;
 use crate::ui::boot_replay::BootReplayApp;
+use crate::ui::policy_calculator::PolicyCalculatorApp;
 
 /// Application tabs
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Tab {
     HashCalculator,
     BootReplay,
+    PolicyCalculator,
 }
 
 /// Main application
@@ -14,6 +16,7 @@ pub struct MainApp {
     current_tab: Tab,
     hash_calculator: HashCalculatorApp,
     boot_replay: BootReplayApp,
+    policy_calculator: PolicyCalculatorApp,
 }
 
 impl Default for MainApp {
@@ -22,6 +25,7 @@ impl Default for MainApp {
             current_tab: Tab::HashCalculator,
             hash_calculator: HashCalculatorApp::default(),
             boot_replay: BootReplayApp::default(),
+            policy_calculator: PolicyCalculatorApp::default(),
         }
     }
 }
@@ -33,6 +37,7 @@ impl eframe::App for MainApp {
             ui.horizontal(|ui| {
                 ui.selectable_value(&mut self.current_tab, Tab::HashCalculator, "Hash Calculator");
                 ui.selectable_value(&mut self.current_tab, Tab::BootReplay, "Boot Measurement Replay");
+                ui.selectable_value(&mut self.current_tab, Tab::PolicyCalculator, "Policy Calculator");
             });
         });
 
@@ -40,6 +45,7 @@ impl eframe::App for MainApp {
         match self.current_tab {
             Tab::HashCalculator => self.hash_calculator.update(ctx, frame),
             Tab::BootReplay => self.boot_replay.update(ctx, frame),
+            Tab::PolicyCalculator => self.policy_calculator.update(ctx, frame, self.boot_replay.simulator()),
         }
     }
 } 
\ No newline at end of file
@@ -1,6 +1,11 @@
 use eframe::egui;
-use crate::models::pcr_simulator::{PcrSimulator, HashAlgorithm};
-use crate::utils::file_processor::MeasurementFileProcessor;
+use eggs::{
+    ExportFormat, PcrAttributes, LOCALITY_COUNT, MeasurementEvent, DigestPadding, PcrBank, PcrSimulator,
+    HashAlgorithm, PCR_COUNT, Quote, QuotePublicKey, QuoteSignature, QuoteStatus, QuoteVerification, Breakpoint,
+    ReplayDebugger, Session, GoldenValue, GoldenValues, VerificationReport, LineDiagnostic,
+    MeasurementFileProcessor, IMA_PCR, LinuxTpm, DEFAULT_EVENT_LOG_PATH, DEFAULT_PCR_DIR,
+    event_type_from_name, event_type_name, EventLogFormat,
+};
 use rfd::FileDialog;
 
 /// Boot Replay Simulator - UI Part
@@ -11,19 +16,68 @@ pub struct BootReplayApp {
     
     // PCR options
     selected_pcr: usize,
+    pcr_view: PcrView,
+    startup_locality: Option<u8>, // Chosen startup locality; None uses the log's StartupLocality event
+    locality_error: Option<String>,
+    
+    // PCRs changed by the last operation, compared with the bank values before it
+    last_banks: Vec<PcrBank>,
+    changed_pcrs: Vec<(HashAlgorithm, usize)>,
     
     // UI state control
     show_details: bool,
+    log_filter: Option<usize>,
     input_mode: InputMode,
     
     // Manual input related
     manual_new_measurement: String,
+    manual_as_event_data: bool,
     manual_error: Option<String>,
     
     // File input related
     file_path: Option<String>,
     show_file_dialog: bool,
     selected_measurements: Vec<bool>,
+    replay_error: Option<String>,
+    
+    // Replay debugger related
+    debugger: Option<ReplayDebugger>,
+    debugger_status: Option<String>,
+    breakpoint_kind: BreakpointKind,
+    breakpoint_input: String,
+    
+    // Dynamic launch related
+    drtm_hex: String,
+    drtm_file: Option<String>,
+    show_drtm_dialog: bool,
+    drtm_status: Option<Result<String, String>>,
+    
+    // Quote verification related
+    quote_files: [Option<String>; 3], // Attestation, signature and public key
+    quote_dialog: Option<QuoteFile>,
+    quote_nonce: String,
+    quote_result: Option<Result<(Quote, QuoteVerification), String>>,
+    
+    // Local TPM related
+    machine_event_log: String,
+    machine_pcr_dir: String,
+    machine_error: Option<String>,
+    
+    // Golden value verification related
+    golden_text: String,
+    golden_error: Option<String>,
+    show_golden_dialog: bool,
+    verification: Option<VerificationReport>,
+    
+    // Session file related
+    show_session_save_dialog: bool,
+    show_session_load_dialog: bool,
+    session_status: Option<Result<String, String>>,
+    
+    // Export related
+    export_format: ExportFormat,
+    export_dialog: Option<ExportContent>,
+    export_status: Option<Result<String, String>>,
     
     // Simulator instance
     simulator: PcrSimulator,
@@ -36,160 +90,758 @@ pub enum InputMode {
     File,
 }
 
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum PcrView {
+    Single,
+    All,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ExportContent {
+    PcrValues,
+    MeasurementLog,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum QuoteFile {
+    Attestation,
+    Signature,
+    PublicKey,
+}
+
+impl QuoteFile {
+    fn all() -> [QuoteFile; 3] {
+        [QuoteFile::Attestation, QuoteFile::Signature, QuoteFile::PublicKey]
+    }
+    
+    fn name(&self) -> &'static str {
+        match self {
+            QuoteFile::Attestation => "Quote (TPMS_ATTEST)",
+            QuoteFile::Signature => "Signature (TPMT_SIGNATURE)",
+            QuoteFile::PublicKey => "Public Key (PEM or TPM2B_PUBLIC)",
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum BreakpointKind {
+    EventIndex,
+    Pcr,
+    EventType,
+}
+
+impl BreakpointKind {
+    fn name(&self) -> &'static str {
+        match self {
+            BreakpointKind::EventIndex => "Event Index",
+            BreakpointKind::Pcr => "PCR",
+            BreakpointKind::EventType => "Event Type",
+        }
+    }
+    
+    /// Parse a breakpoint of this kind from user input
+    fn parse(&self, text: &str) -> Result<Breakpoint, String> {
+        let text = text.trim();
+        match self {
+            BreakpointKind::EventIndex => text.parse::<usize>()
+                .map(Breakpoint::EventIndex)
+                .map_err(|_| format!("Invalid event index '{}'", text)),
+            BreakpointKind::Pcr => match text.parse::<usize>() {
+                Ok(pcr_index) if pcr_index < PCR_COUNT => Ok(Breakpoint::Pcr(pcr_index)),
+                _ => Err(format!("Invalid PCR index '{}' (expected 0-{})", text, PCR_COUNT - 1)),
+            },
+            BreakpointKind::EventType => event_type_from_name(text)
+                .map(Breakpoint::EventType)
+                .ok_or_else(|| format!("Unknown event type '{}'", text)),
+        }
+    }
+}
+
 impl Default for BootReplayApp {
     fn default() -> Self {
         let default_algorithm = HashAlgorithm::SHA256;
         
+        let simulator = PcrSimulator::with_banks(vec![HashAlgorithm::SHA1, default_algorithm.clone()]);
+        
         Self {
-            selected_algorithm: default_algorithm.clone(),
+            selected_algorithm: default_algorithm,
             algorithm_options: HashAlgorithm::all_algorithms(),
             selected_pcr: 0,
+            pcr_view: PcrView::Single,
+            startup_locality: None,
+            locality_error: None,
+            last_banks: simulator.banks().to_vec(),
+            changed_pcrs: Vec::new(),
             show_details: false,
+            log_filter: None,
             input_mode: InputMode::Manual,
             manual_new_measurement: String::new(),
+            manual_as_event_data: false,
             manual_error: None,
             file_path: None,
             show_file_dialog: false,
             selected_measurements: Vec::new(),
-            simulator: PcrSimulator::new(default_algorithm),
+            replay_error: None,
+            debugger: None,
+            debugger_status: None,
+            breakpoint_kind: BreakpointKind::EventIndex,
+            breakpoint_input: String::new(),
+            drtm_hex: String::new(),
+            drtm_file: None,
+            show_drtm_dialog: false,
+            drtm_status: None,
+            quote_files: [None, None, None],
+            quote_dialog: None,
+            quote_nonce: String::new(),
+            quote_result: None,
+            machine_event_log: DEFAULT_EVENT_LOG_PATH.to_string(),
+            machine_pcr_dir: DEFAULT_PCR_DIR.to_string(),
+            machine_error: None,
+            golden_text: String::new(),
+            golden_error: None,
+            show_golden_dialog: false,
+            verification: None,
+            show_session_save_dialog: false,
+            show_session_load_dialog: false,
+            session_status: None,
+            export_format: ExportFormat::Json,
+            export_dialog: None,
+            export_status: None,
+            simulator,
             file_processor: MeasurementFileProcessor::new(),
         }
     }
 }
 
 impl BootReplayApp {
+    /// Simulator with the replayed PCR values
+    pub fn simulator(&self) -> &PcrSimulator {
+        &self.simulator
+    }
+    
     pub fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.track_changes();
+        
         egui::CentralPanel::default().show(ctx, |ui| {
-            // Top title and description
-            ui.vertical_centered(|ui| {
-                ui.heading("Boot Measurement Replay Simulator");
-                ui.label("Simulate TPM PCR extend operations and verify measurements");
-                ui.add_space(10.0);
-            });
-            
-            // Main control area
-            ui.group(|ui| {
-                // Algorithm selection
-                ui.horizontal(|ui| {
-                    ui.label("Hash Algorithm:");
+            egui::ScrollArea::vertical().id_source("boot_replay_panel").show(ui, |ui| {
+                // Top title and description
+                ui.vertical_centered(|ui| {
+                    ui.heading("Boot Measurement Replay Simulator");
+                    ui.label("Simulate TPM PCR extend operations and verify measurements");
+                    ui.add_space(10.0);
+                });
+                
+                // Main control area
+                ui.group(|ui| {
+                    // Active banks
+                    ui.horizontal(|ui| {
+                        ui.label("Active Banks:");
+                        
+                        for alg_name in self.algorithm_options.clone() {
+                            if let Ok(algorithm) = alg_name.parse::<HashAlgorithm>() {
+                                let mut active = self.simulator.bank(&algorithm).is_some();
+                                if ui.checkbox(&mut active, alg_name).changed() {
+                                    self.toggle_bank(algorithm, active);
+                                }
+                            }
+                        }
+                    });
+                    ui.weak("Changing the active banks resets all PCRs.");
                     
-                    let current_algorithm = match self.selected_algorithm {
-                        HashAlgorithm::SHA1 => "SHA1",
-                        HashAlgorithm::SHA256 => "SHA256",
-                        HashAlgorithm::SHA384 => "SHA384",
-                        HashAlgorithm::SHA512 => "SHA512",
-                        HashAlgorithm::SHA3_256 => "SHA3-256",
-                        HashAlgorithm::SHA3_384 => "SHA3-384",
-                        HashAlgorithm::SHA3_512 => "SHA3-512",
-                        HashAlgorithm::SM3 => "SM3",
-                    };
+                    ui.add_space(5.0);
+                    
+                    // Bank selection for manual input and file replay
+                    ui.horizontal(|ui| {
+                        ui.label("Input Bank:");
+                        
+                        // Create bank dropdown menu
+                        egui::ComboBox::from_id_source("algorithm_select")
+                            .selected_text(self.selected_algorithm.name())
+                            .show_ui(ui, |ui| {
+                                for algorithm in self.simulator.active_algorithms() {
+                                    let name = algorithm.name();
+                                    ui.selectable_value(&mut self.selected_algorithm, algorithm, name);
+                                }
+                            });
+                    });
                     
-                    // Create algorithm dropdown menu
-                    egui::ComboBox::from_id_source("algorithm_select")
-                        .selected_text(current_algorithm)
-                        .show_ui(ui, |ui| {
-                            for &alg_name in &self.algorithm_options {
-                                let selected = ui.selectable_label(
-                                    current_algorithm == alg_name,
-                                    alg_name
-                                ).clicked();
-                                
-                                if selected {
-                                    if let Some(algorithm) = HashAlgorithm::from_str(alg_name) {
-                                        self.selected_algorithm = algorithm.clone();
-                                        self.simulator.change_algorithm(algorithm);
+                    ui.add_space(5.0);
+                    
+                    // PCR view selection
+                    ui.horizontal(|ui| {
+                        ui.label("PCR View:");
+                        ui.selectable_value(&mut self.pcr_view, PcrView::Single, "Single PCR");
+                        ui.selectable_value(&mut self.pcr_view, PcrView::All, "All PCRs");
+                    });
+                    
+                    ui.add_space(5.0);
+                    
+                    // PCR selection
+                    ui.horizontal(|ui| {
+                        ui.label("PCR Index:");
+                        let pcr_range = 0..PCR_COUNT;
+                        
+                        egui::ComboBox::from_id_source("pcr_select")
+                            .selected_text(format!("PCR {}", self.selected_pcr))
+                            .show_ui(ui, |ui| {
+                                for pcr in pcr_range {
+                                    if ui.selectable_label(self.selected_pcr == pcr, format!("PCR {}", pcr)).clicked() {
+                                        self.selected_pcr = pcr;
                                     }
                                 }
+                            });
+                    });
+                    
+                    ui.add_space(5.0);
+                    
+                    self.show_locality(ui);
+                    
+                    ui.add_space(5.0);
+                    
+                    match self.pcr_view {
+                        PcrView::Single => {
+                            // Display current PCR value in every active bank
+                            ui.label("Current PCR Value:");
+                            for bank in self.simulator.banks() {
+                                ui.horizontal(|ui| {
+                                    ui.label(format!("{}:", bank.algorithm.name()));
+                                    ui.monospace(hex::encode(&bank.pcr_values[self.selected_pcr]));
+                                });
                             }
-                        });
+                        },
+                        PcrView::All => self.show_pcr_overview(ui),
+                    }
+                    
+                    ui.add_space(10.0);
+                    
+                    // Input mode selection
+                    ui.horizontal(|ui| {
+                        ui.label("Input Mode:");
+                        
+                        if ui.selectable_label(self.input_mode == InputMode::Manual, "Manual Input").clicked() {
+                            self.input_mode = InputMode::Manual;
+                        }
+                        
+                        if ui.selectable_label(self.input_mode == InputMode::File, "File Input").clicked() {
+                            self.input_mode = InputMode::File;
+                        }
+                    });
+                    
+                    ui.add_space(5.0);
+                    
+                    // Display different UI based on selected input mode
+                    match self.input_mode {
+                        InputMode::Manual => self.show_manual_mode(ui),
+                        InputMode::File => self.show_file_mode(ui, ctx),
+                    }
                 });
                 
-                ui.add_space(5.0);
+                // PC Client PCR attribute table
+                egui::CollapsingHeader::new("PCR Attributes")
+                    .default_open(false)
+                    .show(ui, |ui| self.show_pcr_attributes(ui));
                 
-                // PCR selection
-                ui.horizontal(|ui| {
-                    ui.label("PCR Index:");
-                    let pcr_range = 0..24;
-                    
-                    egui::ComboBox::from_id_source("pcr_select")
-                        .selected_text(format!("PCR {}", self.selected_pcr))
-                        .show_ui(ui, |ui| {
-                            for pcr in pcr_range {
-                                if ui.selectable_label(self.selected_pcr == pcr, format!("PCR {}", pcr)).clicked() {
-                                    self.selected_pcr = pcr;
-                                }
-                            }
-                        });
-                });
+                // DRTM hash sequence into PCR 17
+                egui::CollapsingHeader::new("Dynamic Launch (DRTM)")
+                    .default_open(false)
+                    .show(ui, |ui| self.show_dynamic_launch(ui));
                 
-                ui.add_space(5.0);
+                // TPM2_Quote checked against the replayed PCRs
+                egui::CollapsingHeader::new("Quote Verification")
+                    .default_open(false)
+                    .show(ui, |ui| self.show_quote_verification(ui));
                 
-                // Display current PCR value
-                if let Ok(pcr_value) = self.simulator.get_pcr_hex_string(self.selected_pcr) {
-                    ui.horizontal(|ui| {
-                        ui.label("Current PCR Value:");
-                        ui.monospace(pcr_value);
-                    });
-                }
+                // Replay of the local TPM's event log against its live PCRs
+                egui::CollapsingHeader::new("Verify This Machine")
+                    .default_open(false)
+                    .show(ui, |ui| self.show_machine_verification(ui));
+                
+                // Golden value verification
+                egui::CollapsingHeader::new("Golden Value Verification")
+                    .default_open(false)
+                    .show(ui, |ui| self.show_verification(ui));
                 
-                ui.add_space(10.0);
+                // Export of PCR values and the measurement log
+                egui::CollapsingHeader::new("Export")
+                    .default_open(false)
+                    .show(ui, |ui| self.show_export(ui));
                 
-                // Input mode selection
+                // Bottom display measurement log
+                if self.show_details {
+                    self.show_measurement_log(ui);
+                }
+                
+                // Bottom control buttons
                 ui.horizontal(|ui| {
-                    ui.label("Input Mode:");
+                    if ui.button("Reset PCR").clicked() {
+                        self.simulator.reset();
+                    }
                     
-                    if ui.selectable_label(self.input_mode == InputMode::Manual, "Manual Input").clicked() {
-                        self.input_mode = InputMode::Manual;
+                    if ui.button(if self.show_details { "Hide Log" } else { "Show Log" }).clicked() {
+                        self.show_details = !self.show_details;
                     }
                     
-                    if ui.selectable_label(self.input_mode == InputMode::File, "File Input").clicked() {
-                        self.input_mode = InputMode::File;
+                    if ui.button("Save Session").clicked() {
+                        self.show_session_save_dialog = true;
+                    }
+                    
+                    if ui.button("Load Session").clicked() {
+                        self.show_session_load_dialog = true;
                     }
                 });
                 
-                ui.add_space(5.0);
-                
-                // Display different UI based on selected input mode
-                match self.input_mode {
-                    InputMode::Manual => self.show_manual_mode(ui),
-                    InputMode::File => self.show_file_mode(ui, ctx),
-                }
-            });
-            
-            // Bottom display measurement log
-            if self.show_details {
-                self.show_measurement_log(ui);
-            }
-            
-            // Bottom control buttons
-            ui.horizontal(|ui| {
-                if ui.button("Reset PCR").clicked() {
-                    self.simulator.reset();
-                }
-                
-                if ui.button(if self.show_details { "Hide Log" } else { "Show Log" }).clicked() {
-                    self.show_details = !self.show_details;
+                match self.session_status {
+                    Some(Ok(ref message)) => {
+                        ui.label(message);
+                    },
+                    Some(Err(ref error)) => {
+                        ui.colored_label(egui::Color32::RED, error);
+                    },
+                    None => {},
                 }
             });
         });
         
+        self.show_debugger_window(ctx);
+        
         // Handle file dialog
         if self.show_file_dialog {
             self.show_file_dialog = false;
             if let Some(path) = FileDialog::new()
-                .add_filter("Measurement Files", &["txt", "log", "bin"])
+                .add_filter("Measurement Files", &["txt", "log", "bin", "csv", "yaml", "yml"])
+                .add_filter("All Files", &["*"])
                 .set_title("Select Measurement File")
                 .pick_file()
             {
                 let path_str = path.to_string_lossy().to_string();
-                if let Err(_err) = self.file_processor.load_file(&path_str) {
-                    // Report error
+                self.replay_error = None;
+                if self.file_processor.load_file(&path_str).is_err() {
+                    // The processor keeps the error for the file panel
+                    self.file_path = None;
+                    self.selected_measurements.clear();
                 } else {
                     self.file_path = Some(path_str);
                     self.selected_measurements = vec![false; self.file_processor.get_parsed_measurements().len()];
                 }
             }
         }
+        
+        // Handle dynamic launch file dialog
+        if self.show_drtm_dialog {
+            self.show_drtm_dialog = false;
+            if let Some(path) = FileDialog::new()
+                .add_filter("All Files", &["*"])
+                .set_title("Select Launch Image")
+                .pick_file()
+            {
+                self.drtm_file = Some(path.to_string_lossy().to_string());
+                self.drtm_status = None;
+            }
+        }
+        
+        // Handle quote file dialogs
+        if let Some(file) = self.quote_dialog.take() {
+            if let Some(path) = FileDialog::new()
+                .add_filter("All Files", &["*"])
+                .set_title(format!("Select {}", file.name()))
+                .pick_file()
+            {
+                self.quote_files[file as usize] = Some(path.to_string_lossy().to_string());
+                self.quote_result = None;
+            }
+        }
+        
+        // Handle session file dialogs
+        if self.show_session_save_dialog {
+            self.show_session_save_dialog = false;
+            if let Some(path) = FileDialog::new()
+                .add_filter("Session Files", &["json"])
+                .set_title("Save Session")
+                .set_file_name("session.json")
+                .save_file()
+            {
+                self.session_status = Some(self.save_session(&path.to_string_lossy()));
+            }
+        }
+        if self.show_session_load_dialog {
+            self.show_session_load_dialog = false;
+            if let Some(path) = FileDialog::new()
+                .add_filter("Session Files", &["json"])
+                .add_filter("All Files", &["*"])
+                .set_title("Load Session")
+                .pick_file()
+            {
+                self.session_status = Some(self.load_session(&path.to_string_lossy()));
+            }
+        }
+        
+        // Handle export file dialog
+        if let Some(content) = self.export_dialog.take() {
+            let (title, file_name) = match content {
+                ExportContent::PcrValues => ("Export PCR Values", "pcrs"),
+                ExportContent::MeasurementLog => ("Export Measurement Log", "measurement_log"),
+            };
+            let extension = self.export_format.extension();
+            if let Some(path) = FileDialog::new()
+                .add_filter(self.export_format.name(), &[extension])
+                .set_title(title)
+                .set_file_name(format!("{}.{}", file_name, extension))
+                .save_file()
+            {
+                let output = match content {
+                    ExportContent::PcrValues => self.simulator.export_pcr_values(self.export_format),
+                    ExportContent::MeasurementLog => self.simulator.export_measurement_log(self.export_format),
+                };
+                self.export_status = Some(match std::fs::write(&path, output) {
+                    Ok(()) => Ok(format!("Exported to {}", path.display())),
+                    Err(err) => Err(format!("Error writing file: {}", err)),
+                });
+            }
+        }
+        
+        // Handle golden value file dialog
+        if self.show_golden_dialog {
+            self.show_golden_dialog = false;
+            if let Some(path) = FileDialog::new()
+                .add_filter("Golden Value Files", &["txt", "yaml", "yml"])
+                .set_title("Select Golden Value File")
+                .pick_file()
+            {
+                match std::fs::read_to_string(&path) {
+                    Ok(content) => {
+                        self.golden_text = content;
+                        self.golden_error = None;
+                    }
+                    Err(err) => {
+                        self.golden_error = Some(format!("Error reading file: {}", err));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Save banks, measurement log, loaded file and golden values to a session file
+    fn save_session(&self, path: &str) -> Result<String, String> {
+        let golden_values = GoldenValues::parse(&self.golden_text, &self.selected_algorithm)
+            .map_err(|e| format!("Cannot save session, golden values are invalid: {}", e))?;
+        Session::new(self.simulator.clone(), self.file_path.clone(), golden_values)
+            .save(path)
+            .map_err(|e| format!("Error saving session: {}", e))?;
+        Ok(format!("Session saved to {}", path))
+    }
+    
+    /// Restore a saved session, reopening its measurement file if it still exists
+    fn load_session(&mut self, path: &str) -> Result<String, String> {
+        let session = Session::load(path).map_err(|e| format!("Error loading session: {}", e))?;
+        
+        self.simulator = session.simulator;
+        let algorithms = self.simulator.active_algorithms();
+        if !algorithms.contains(&self.selected_algorithm) {
+            self.selected_algorithm = algorithms[0].clone();
+        }
+        self.last_banks = self.simulator.banks().to_vec();
+        self.changed_pcrs.clear();
+        self.log_filter = None;
+        self.debugger = None;
+        self.golden_text = session.golden_values.to_text();
+        self.golden_error = None;
+        self.verification = None;
+        self.replay_error = None;
+        
+        self.file_path = None;
+        self.file_processor = MeasurementFileProcessor::new();
+        self.selected_measurements.clear();
+        if let Some(file_path) = session.file_path {
+            if let Err(err) = self.file_processor.load_file(&file_path) {
+                return Err(format!("Session loaded, but {} could not be reopened: {}", file_path, err));
+            }
+            self.selected_measurements = vec![false; self.file_processor.get_parsed_measurements().len()];
+            self.file_path = Some(file_path);
+        }
+        Ok(format!("Session loaded from {}", path))
+    }
+    
+    /// Current and startup locality, PCR reset and dynamic launch
+    fn show_locality(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Locality:");
+            egui::ComboBox::from_id_source("locality_select")
+                .selected_text(self.simulator.locality().to_string())
+                .show_ui(ui, |ui| {
+                    for locality in 0..LOCALITY_COUNT {
+                        if ui.selectable_label(self.simulator.locality() == locality, locality.to_string()).clicked() {
+                            self.locality_error = self.simulator.set_locality(locality).err().map(|e| e.to_string());
+                        }
+                    }
+                });
+            
+            ui.label("Startup Locality:");
+            let mut startup_locality = self.startup_locality;
+            let selected_text = match startup_locality {
+                Some(locality) => locality.to_string(),
+                None => format!("From log ({})", self.simulator.startup_locality()),
+            };
+            egui::ComboBox::from_id_source("startup_locality_select")
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut startup_locality, None, "From log");
+                    for locality in [0, 3, 4] {
+                        ui.selectable_value(&mut startup_locality, Some(locality), locality.to_string());
+                    }
+                });
+            if startup_locality != self.startup_locality {
+                self.startup_locality = startup_locality;
+                if let Some(locality) = startup_locality {
+                    self.locality_error = self.simulator.set_startup_locality(locality).err().map(|e| e.to_string());
+                }
+            }
+            
+            if ui.button(format!("Reset PCR {}", self.selected_pcr))
+                .on_hover_text("TPM2_PCR_Reset from the current locality")
+                .clicked()
+            {
+                self.locality_error = self.simulator.reset_pcr(self.selected_pcr).err().map(|e| e.to_string());
+            }
+        });
+        ui.weak("Changing the startup locality resets all PCRs. Log replays use it instead of the log's StartupLocality event.");
+        
+        if let Some(ref error) = self.locality_error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+    }
+    
+    /// Dynamic launch of hex data or a file, e.g. the SINIT ACM or secure loader image
+    fn show_dynamic_launch(&mut self, ui: &mut egui::Ui) {
+        ui.label("Resets PCR 17-22 to zero and hashes the launch data into PCR 17 of every active bank at locality 4, like TXT or SKINIT.");
+        
+        match self.drtm_file.clone() {
+            Some(path) => {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Launch image: {}", path));
+                    if ui.button("Clear").clicked() {
+                        self.drtm_file = None;
+                    }
+                });
+            },
+            None => {
+                ui.horizontal(|ui| {
+                    ui.label("Launch data (hex):");
+                    ui.text_edit_singleline(&mut self.drtm_hex);
+                    if ui.button("Select File").clicked() {
+                        self.show_drtm_dialog = true;
+                    }
+                });
+            },
+        }
+        
+        if ui.button("Dynamic Launch").clicked() {
+            self.drtm_status = Some(self.dynamic_launch());
+        }
+        
+        match self.drtm_status {
+            Some(Ok(ref message)) => {
+                ui.label(message);
+            },
+            Some(Err(ref error)) => {
+                ui.colored_label(egui::Color32::RED, error);
+            },
+            None => {},
+        }
+    }
+    
+    fn dynamic_launch(&mut self) -> Result<String, String> {
+        let (description, data) = match self.drtm_file {
+            Some(ref path) => {
+                let data = std::fs::read(path).map_err(|e| format!("Error reading {}: {}", path, e))?;
+                (format!("Dynamic Launch: {}", path), data)
+            },
+            None => {
+                let data = PcrSimulator::parse_hex(&self.drtm_hex).map_err(|e| e.to_string())?;
+                (format!("Dynamic Launch: {}", self.drtm_hex.trim()), data)
+            },
+        };
+        
+        self.simulator.dynamic_launch(description, &data).map_err(|e| e.to_string())?;
+        self.locality_error = None;
+        Ok(format!(
+            "Measured {} bytes into PCR 17; the locality is now {} for the launched environment's extends to PCR 17-19.",
+            data.len(),
+            self.simulator.locality()
+        ))
+    }
+    
+    /// Quote files, nonce and the result of checking the quote against the simulator
+    fn show_quote_verification(&mut self, ui: &mut egui::Ui) {
+        ui.label("Checks a TPM2_Quote (e.g. from tpm2_quote -m/-s) against the replayed PCR values.");
+        
+        egui::Grid::new("quote_files").num_columns(3).show(ui, |ui| {
+            for file in QuoteFile::all() {
+                ui.label(format!("{}:", file.name()));
+                ui.label(self.quote_files[file as usize].as_deref().unwrap_or("(none)"));
+                ui.horizontal(|ui| {
+                    if ui.button("Select").clicked() {
+                        self.quote_dialog = Some(file);
+                    }
+                    if ui.button("Clear").clicked() {
+                        self.quote_files[file as usize] = None;
+                        self.quote_result = None;
+                    }
+                });
+                ui.end_row();
+            }
+            ui.label("Expected nonce (hex):");
+            ui.text_edit_singleline(&mut self.quote_nonce);
+            ui.end_row();
+        });
+        ui.weak("The signature is checked when both a signature and a public key are selected.");
+        
+        if ui.button("Verify Quote").clicked() {
+            self.quote_result = Some(self.verify_quote());
+        }
+        
+        match self.quote_result {
+            Some(Ok((ref quote, ref verification))) => Self::show_quote(ui, quote, verification),
+            Some(Err(ref error)) => {
+                ui.colored_label(egui::Color32::RED, error);
+            },
+            None => {},
+        }
+    }
+    
+    fn verify_quote(&self) -> Result<(Quote, QuoteVerification), String> {
+        let read = |file: QuoteFile| -> Result<Option<Vec<u8>>, String> {
+            match self.quote_files[file as usize] {
+                Some(ref path) => std::fs::read(path).map(Some).map_err(|e| format!("Error reading {}: {}", path, e)),
+                None => Ok(None),
+            }
+        };
+        
+        let quote = match read(QuoteFile::Attestation)? {
+            Some(data) => Quote::parse(&data).map_err(|e| e.to_string())?,
+            None => return Err("Please select a quote".to_string()),
+        };
+        let signature = match (read(QuoteFile::Signature)?, read(QuoteFile::PublicKey)?) {
+            (Some(signature), Some(key)) => Some((
+                QuoteSignature::parse(&signature).map_err(|e| e.to_string())?,
+                QuotePublicKey::parse(&key).map_err(|e| e.to_string())?,
+            )),
+            _ => None,
+        };
+        let nonce = match self.quote_nonce.trim() {
+            "" => None,
+            text => Some(PcrSimulator::parse_hex(text).map_err(|e| e.to_string())?),
+        };
+        
+        let verification = self.simulator.verify_quote(
+            &quote,
+            signature.as_ref().map(|(signature, key)| (signature, key)),
+            nonce.as_deref(),
+        );
+        Ok((quote, verification))
+    }
+    
+    /// Quote fields with the replayed digest, signature and nonce results
+    fn show_quote(ui: &mut egui::Ui, quote: &Quote, verification: &QuoteVerification) {
+        let ok = |ui: &mut egui::Ui, text: &str| { ui.colored_label(egui::Color32::GREEN, text); };
+        let failed = |ui: &mut egui::Ui, text: &str| { ui.colored_label(egui::Color32::RED, text); };
+        
+        egui::Grid::new("quote_fields").num_columns(2).striped(true).show(ui, |ui| {
+            let selections: Vec<String> = quote.pcr_selections.iter().map(|selection| selection.to_string()).collect();
+            let fields = [
+                ("Clock", format!("{} ms", quote.clock_info.clock)),
+                ("Reset Count", quote.clock_info.reset_count.to_string()),
+                ("Restart Count", quote.clock_info.restart_count.to_string()),
+                ("Safe", quote.clock_info.safe.to_string()),
+                ("Firmware Version", format!("{} (0x{:016x})", quote.firmware_version_string(), quote.firmware_version)),
+                ("Nonce", hex::encode(&quote.extra_data)),
+                ("Qualified Signer", hex::encode(&quote.qualified_signer)),
+                ("PCR Selection", selections.join("; ")),
+                ("Quoted PCR Digest", hex::encode(&quote.pcr_digest)),
+                (
+                    "Replayed PCR Digest",
+                    verification.replayed_digest.as_ref().map_or_else(|| "-".to_string(), hex::encode),
+                ),
+            ];
+            for (name, value) in fields {
+                ui.label(format!("{}:", name));
+                ui.monospace(value);
+                ui.end_row();
+            }
+        });
+        
+        for error in &verification.errors {
+            failed(ui, error);
+        }
+        if verification.pcr_digest_matches(quote) {
+            ok(ui, &format!("✓ The quoted PCR digest ({}) matches the replayed PCRs", verification.digest_algorithm.name()));
+        } else {
+            failed(ui, &format!("✗ The quoted PCR digest ({}) does not match the replayed PCRs", verification.digest_algorithm.name()));
+        }
+        match verification.signature {
+            Some(Ok(())) => ok(ui, "✓ The signature is valid"),
+            Some(Err(ref error)) => failed(ui, &format!("✗ {}", error)),
+            None => {
+                ui.weak("Signature unverified: give the signature and the signing key to check it");
+            },
+        }
+        match verification.nonce_matches {
+            Some(true) => ok(ui, "✓ The nonce matches"),
+            Some(false) => failed(ui, "✗ The nonce does not match; the quote may be replayed"),
+            None => {},
+        }
+        
+        ui.separator();
+        match verification.status(quote) {
+            QuoteStatus::Valid => ok(ui, "✓ Quote verified"),
+            QuoteStatus::Unverified => {
+                ui.colored_label(egui::Color32::YELLOW, "Quote unverified: the signature was not checked");
+            },
+            QuoteStatus::Invalid => failed(ui, "✗ Quote invalid"),
+        }
+    }
+    
+    /// PC Client PCR attribute table, marking what the current locality may do
+    fn show_pcr_attributes(&self, ui: &mut egui::Ui) {
+        let locality = self.simulator.locality();
+        ui.label(format!("Localities allowed to reset and extend each PCR (current locality {})", locality));
+        
+        egui::Grid::new("pcr_attributes_grid").striped(true).show(ui, |ui| {
+            ui.strong("PCR");
+            ui.strong("Reset");
+            ui.strong("Extend");
+            ui.end_row();
+            
+            for (pcr, attributes) in PcrAttributes::pc_client_table().iter().enumerate() {
+                ui.label(format!("PCR {}", pcr));
+                for allowed in [attributes.reset, attributes.extend] {
+                    let text = egui::RichText::new(allowed.to_string()).monospace();
+                    if allowed.contains(locality) {
+                        ui.label(text);
+                    } else {
+                        ui.label(text.weak());
+                    }
+                }
+                ui.end_row();
+            }
+        });
+    }
+    
+    /// Activate or deactivate a bank, keeping at least one bank active
+    fn toggle_bank(&mut self, algorithm: HashAlgorithm, active: bool) {
+        let mut algorithms = self.simulator.active_algorithms();
+        if active {
+            algorithms.push(algorithm);
+        } else {
+            algorithms.retain(|alg| *alg != algorithm);
+        }
+        if algorithms.is_empty() {
+            return;
+        }
+        
+        if !algorithms.contains(&self.selected_algorithm) {
+            self.selected_algorithm = algorithms[0].clone();
+        }
+        self.simulator.set_active_banks(algorithms);
     }
 
     /// Display manual input mode UI
@@ -210,16 +862,26 @@ impl BootReplayApp {
                     self.manual_error = Some("Please enter a valid measurement".to_string());
                 } else {
                     // Try to add measurement
-                    match self.simulator.add_measurement(
-                        format!("Manual Input: {}", input),
-                        input,
-                        self.selected_pcr
-                    ) {
+                    let result = if self.manual_as_event_data {
+                        PcrSimulator::parse_hex(input).and_then(|data| self.simulator.add_event_data(
+                            format!("Manual Event Data: {}", input),
+                            &data,
+                            self.selected_pcr
+                        ))
+                    } else {
+                        self.simulator.add_measurement(
+                            format!("Manual Input: {}", input),
+                            input,
+                            self.selected_pcr,
+                            &self.selected_algorithm
+                        )
+                    };
+                    match result {
                         Ok(_) => {
                             self.manual_new_measurement.clear();
                         }
                         Err(err) => {
-                            self.manual_error = Some(err);
+                            self.manual_error = Some(err.to_string());
                         }
                     }
                 }
@@ -231,10 +893,16 @@ impl BootReplayApp {
             ui.colored_label(egui::Color32::RED, error);
         }
         
+        ui.checkbox(&mut self.manual_as_event_data, "Hash input as event data into all active banks");
+        
         ui.add_space(5.0);
         
         // Brief description
-        ui.label("Enter a measurement in hexadecimal format, then click Apply to extend the PCR.");
+        if self.manual_as_event_data {
+            ui.label("Enter event data in hexadecimal format, then click Apply to measure it into every active bank.");
+        } else {
+            ui.label("Enter a measurement in hexadecimal format, then click Apply to extend the PCR in the input bank.");
+        }
     }
 
     /// Display file input mode UI
@@ -257,18 +925,54 @@ impl BootReplayApp {
             ui.colored_label(egui::Color32::RED, error);
         }
         
+        // Display replay error
+        if let Some(ref error) = self.replay_error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+        
         ui.add_space(5.0);
         
+        // Binary event logs carry their own PCR indices
+        if self.file_path.is_some() && self.file_processor.get_event_log().is_some() {
+            self.show_event_log(ui, ctx);
+            return;
+        }
+        
+        // tpm2_pcrread output only holds expected values
+        if self.file_path.is_some() && self.file_processor.get_expected_values().is_some() {
+            self.show_pcrread_values(ui);
+            return;
+        }
+        
+        // IMA measurement lists always replay into PCR 10
+        if self.file_path.is_some() && self.file_processor.get_ima_list().is_some() {
+            self.show_ima_list(ui);
+            return;
+        }
+        
+        // Measurement tables carry their own PCR indices
+        if self.file_path.is_some() && self.file_processor.get_measurement_table().is_some() {
+            self.show_measurement_table(ui, ctx);
+            return;
+        }
+        
         // Display file content and parsed measurements
-        if let Some(_) = self.file_path {
+        if self.file_path.is_some() {
+            self.show_padding_option(ui);
+            
             let file_content = self.file_processor.get_file_content();
             let parsed_measurements = self.file_processor.get_parsed_measurements();
             
+            // Unparsable lines, and digests that do not fit the input bank
+            let mut diagnostics = self.file_processor.get_diagnostics().to_vec();
+            diagnostics.extend(self.file_processor.size_diagnostics(&self.selected_algorithm));
+            diagnostics.sort_by_key(|diagnostic| diagnostic.line_number);
+            
             // If there are parsed measurements
             if !parsed_measurements.is_empty() {
-                ui.label(format!("Parsed {} valid measurements from file", parsed_measurements.len()));
+                ui.label(format!("Parsed {} measurements from file", parsed_measurements.len()));
                 
-                egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                egui::ScrollArea::vertical().id_source("parsed_measurements").max_height(200.0).show(ui, |ui| {
                     // Update selection status vector size
                     if self.selected_measurements.len() != parsed_measurements.len() {
                         self.selected_measurements = vec![false; parsed_measurements.len()];
@@ -293,62 +997,865 @@ impl BootReplayApp {
                     for (i, measurement) in parsed_measurements.iter().enumerate() {
                         ui.horizontal(|ui| {
                             ui.checkbox(&mut self.selected_measurements[i], "");
-                            ui.colored_label(ctx.style().visuals.text_color(), format!("{}: {}", i+1, measurement));
+                            let label = format!("{}: {}", measurement.line_number, measurement.value);
+                            // Flag digests that do not fit the input bank
+                            match self.file_processor.validate_hex(&measurement.value, &self.selected_algorithm) {
+                                Ok(_) => {
+                                    ui.colored_label(ctx.style().visuals.text_color(), label);
+                                },
+                                Err(err) => {
+                                    ui.colored_label(egui::Color32::RED, label).on_hover_text(err.to_string());
+                                },
+                            }
                         });
                     }
                 });
                 
                 // Apply selected measurements button
                 let any_selected = self.selected_measurements.iter().any(|&selected| selected);
-                if ui.add_enabled(any_selected, egui::Button::new("Apply Selected Measurements")).clicked() {
-                    // Collect selected values
-                    let mut selected_values = Vec::new();
-                    for (i, &selected) in self.selected_measurements.iter().enumerate() {
-                        if selected {
-                            selected_values.push(parsed_measurements[i].clone());
-                        }
-                    }
-                    
+                let (apply, debug) = ui.horizontal(|ui| (
+                    ui.add_enabled(any_selected, egui::Button::new("Apply Selected Measurements")).clicked(),
+                    ui.add_enabled(any_selected, egui::Button::new("Debug Replay")).clicked(),
+                )).inner;
+                if apply {
                     // Replay these measurements
-                    if let Err(err) = self.simulator.replay(self.selected_pcr, &selected_values) {
-                        // Handle error
-                        println!("Replay error: {}", err);
-                    }
+                    self.replay_error = self.selected_file_events()
+                        .and_then(|events| self.simulator.replay(self.selected_pcr, events))
+                        .err()
+                        .map(|e| e.to_string());
+                }
+                if debug {
+                    let debugger = self.selected_file_events()
+                        .and_then(|events| ReplayDebugger::for_pcr(&self.simulator, self.selected_pcr, events));
+                    self.open_debugger(debugger);
                 }
             } else if !file_content.is_empty() {
                 ui.label("File does not contain valid measurements");
-                // Display file content
-                ui.group(|ui| {
-                    ui.label("File Content:");
-                    egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
-                        for line in file_content {
-                            ui.colored_label(ctx.style().visuals.text_color(), line);
+            }
+            
+            let open = self.file_processor.get_parsed_measurements().is_empty();
+            self.show_line_diagnostics(ui, ctx, &diagnostics, open);
+        } else {
+            ui.label("Please select a file containing measurements to simulate PCR extension");
+        }
+    }
+
+    /// Events for the selected measurements of a plain measurement file
+    fn selected_file_events(&self) -> eggs::Result<Vec<MeasurementEvent>> {
+        self.file_processor.get_parsed_measurements()
+            .iter()
+            .zip(&self.selected_measurements)
+            .filter(|(_, &selected)| selected)
+            .map(|(measurement, _)| self.file_processor.measurement_event(
+                measurement,
+                self.selected_pcr,
+                &self.selected_algorithm
+            ))
+            .collect()
+    }
+    
+    /// Start debugging a replay, keeping the breakpoints of the previous session
+    fn open_debugger(&mut self, debugger: eggs::Result<ReplayDebugger>) {
+        match debugger {
+            Ok(mut debugger) => {
+                if let Some(ref previous) = self.debugger {
+                    debugger.breakpoints = previous.breakpoints.clone();
+                }
+                self.debugger = Some(debugger);
+                self.debugger_status = None;
+                self.replay_error = None;
+            },
+            Err(err) => self.replay_error = Some(err.to_string()),
+        }
+    }
+    
+    /// Display the replay debugger window while a replay is being debugged
+    fn show_debugger_window(&mut self, ctx: &egui::Context) {
+        if self.debugger.is_none() {
+            return;
+        }
+        
+        let mut open = true;
+        egui::Window::new("Replay Debugger")
+            .open(&mut open)
+            .default_width(640.0)
+            .vscroll(true)
+            .show(ctx, |ui| self.show_debugger(ui));
+        if !open {
+            self.debugger = None;
+        }
+    }
+    
+    fn show_debugger(&mut self, ui: &mut egui::Ui) {
+        let Some(debugger) = self.debugger.as_mut() else {
+            return;
+        };
+        
+        // Position and next event
+        ui.label(format!("Replayed {} of {} events", debugger.position(), debugger.events().len()));
+        match debugger.next_event() {
+            Some((index, event)) => ui.label(format!(
+                "Next: #{} PCR{} {} {}",
+                index,
+                event.pcr_index,
+                event.event_type_name(),
+                event.description
+            )),
+            None => ui.label("End of replay"),
+        };
+        
+        // Controls
+        let mut result = Ok(());
+        ui.horizontal(|ui| {
+            if ui.button("Reset").clicked() {
+                debugger.reset();
+                self.debugger_status = None;
+            }
+            if ui.add_enabled(debugger.position() > 0, egui::Button::new("Step Back")).clicked() {
+                debugger.step_back();
+                self.debugger_status = None;
+            }
+            if ui.add_enabled(!debugger.is_finished(), egui::Button::new("Step Forward")).clicked() {
+                result = debugger.step_forward().map(|_| ());
+                self.debugger_status = None;
+            }
+            if ui.add_enabled(!debugger.is_finished(), egui::Button::new("Run to End")).clicked() {
+                result = debugger.run().map(|breakpoint| {
+                    self.debugger_status = breakpoint.map(|breakpoint| format!("Stopped at breakpoint on {}", breakpoint));
+                });
+            }
+            if ui.button("Apply to Simulator").clicked() {
+                self.simulator = debugger.simulator().clone();
+            }
+        });
+        if let Err(err) = result {
+            self.debugger_status = Some(err.to_string());
+        }
+        if let Some(ref status) = self.debugger_status {
+            ui.colored_label(egui::Color32::YELLOW, status);
+        }
+        
+        ui.separator();
+        
+        // Breakpoints
+        ui.horizontal(|ui| {
+            ui.label("Breakpoint on:");
+            egui::ComboBox::from_id_source("breakpoint_kind")
+                .selected_text(self.breakpoint_kind.name())
+                .show_ui(ui, |ui| {
+                    for kind in [BreakpointKind::EventIndex, BreakpointKind::Pcr, BreakpointKind::EventType] {
+                        ui.selectable_value(&mut self.breakpoint_kind, kind, kind.name());
+                    }
+                });
+            let hint = match self.breakpoint_kind {
+                BreakpointKind::EventIndex => "e.g. 12",
+                BreakpointKind::Pcr => "e.g. 7",
+                BreakpointKind::EventType => "e.g. EV_SEPARATOR",
+            };
+            ui.add(egui::TextEdit::singleline(&mut self.breakpoint_input).hint_text(hint).desired_width(160.0));
+            if ui.button("Add").clicked() {
+                match self.breakpoint_kind.parse(&self.breakpoint_input) {
+                    Ok(breakpoint) => {
+                        if !debugger.breakpoints.contains(&breakpoint) {
+                            debugger.breakpoints.push(breakpoint);
+                        }
+                        self.breakpoint_input.clear();
+                    },
+                    Err(err) => self.debugger_status = Some(err),
+                }
+            }
+        });
+        let mut removed = None;
+        for (i, breakpoint) in debugger.breakpoints.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(format!("    {}", breakpoint));
+                if ui.small_button("Remove").clicked() {
+                    removed = Some(i);
+                }
+            });
+        }
+        if let Some(i) = removed {
+            debugger.breakpoints.remove(i);
+        }
+        
+        ui.separator();
+        
+        // Last replayed step: value before, hashed bytes and value after in every bank
+        let Some(step) = debugger.steps().last() else {
+            ui.label("No events replayed yet");
+            return;
+        };
+        if let Some((_, event)) = debugger.events().get(debugger.position() - 1) {
+            ui.strong(format!(
+                "Step {}: #{} PCR{} {} {}",
+                debugger.position(),
+                step.event_index,
+                step.pcr_index,
+                event.event_type_name(),
+                event.description
+            ));
+        }
+        egui::Grid::new("debugger_step_grid").num_columns(2).show(ui, |ui| {
+            for extend in &step.extends {
+                ui.strong(extend.algorithm.name());
+                ui.end_row();
+                ui.label("Before:");
+                ui.monospace(hex::encode(&extend.before));
+                ui.end_row();
+                ui.label("Digest:");
+                ui.monospace(hex::encode(&extend.digest));
+                ui.end_row();
+                ui.label("Hashed:");
+                ui.add(egui::Label::new(egui::RichText::new(hex::encode(extend.hashed_bytes())).monospace()).wrap(true));
+                ui.end_row();
+                ui.label("After:");
+                ui.monospace(hex::encode(&extend.after));
+                ui.end_row();
+            }
+        });
+    }
+    
+    /// Padding policy for digests shorter than their bank
+    fn show_padding_option(&mut self, ui: &mut egui::Ui) {
+        let mut zero_pad = self.file_processor.get_padding() == DigestPadding::ZeroPad;
+        if ui.checkbox(&mut zero_pad, "Zero-pad short digests to the bank size").changed() {
+            self.file_processor.set_padding(if zero_pad { DigestPadding::ZeroPad } else { DigestPadding::Exact });
+        }
+    }
+    
+    /// List line-level problems and show the file content with the bad lines highlighted
+    fn show_line_diagnostics(&self, ui: &mut egui::Ui, ctx: &egui::Context, diagnostics: &[LineDiagnostic], open: bool) {
+        let file_content = self.file_processor.get_file_content();
+        
+        // Line-level problems
+        if !diagnostics.is_empty() {
+            ui.add_space(5.0);
+            ui.colored_label(egui::Color32::RED, format!("{} problem(s) found:", diagnostics.len()));
+            egui::ScrollArea::vertical().id_source("diagnostics").max_height(120.0).show(ui, |ui| {
+                for diagnostic in diagnostics {
+                    ui.horizontal(|ui| {
+                        ui.colored_label(egui::Color32::RED, format!("Line {}:", diagnostic.line_number));
+                        ui.monospace(diagnostic.text.trim());
+                        ui.label(format!("- {}", diagnostic.reason));
+                    });
+                }
+            });
+        }
+        
+        // Display file content with the bad lines highlighted
+        if !file_content.is_empty() {
+            ui.add_space(5.0);
+            egui::CollapsingHeader::new("File Content")
+                .default_open(open || !diagnostics.is_empty())
+                .show(ui, |ui| {
+                    egui::ScrollArea::vertical().id_source("file_content").max_height(200.0).show(ui, |ui| {
+                        for (i, line) in file_content.iter().enumerate() {
+                            let text = format!("{:>4}  {}", i + 1, line);
+                            match diagnostics.iter().find(|diagnostic| diagnostic.line_number == i + 1) {
+                                Some(diagnostic) => {
+                                    ui.colored_label(egui::Color32::RED, egui::RichText::new(text).monospace())
+                                        .on_hover_text(&diagnostic.reason);
+                                },
+                                None => {
+                                    ui.colored_label(
+                                        ctx.style().visuals.text_color(),
+                                        egui::RichText::new(text).monospace()
+                                    );
+                                },
+                            }
                         }
                     });
                 });
+        }
+    }
+    
+    /// Display a measurement table with its own PCR index per line
+    fn show_measurement_table(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        self.show_padding_option(ui);
+        
+        let Some(table) = self.file_processor.get_measurement_table() else {
+            return;
+        };
+        
+        let banks: Vec<&str> = table.algorithms().iter().map(|alg| alg.name()).collect();
+        ui.label(format!(
+            "Parsed {} events from measurement table (banks: {})",
+            table.lines.len(),
+            if banks.is_empty() { "none on every line".to_string() } else { banks.join(", ") }
+        ));
+        
+        egui::ScrollArea::vertical().id_source("measurement_table").max_height(200.0).show(ui, |ui| {
+            egui::Grid::new("measurement_table_grid")
+                .num_columns(5)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("Line");
+                    ui.strong("PCR");
+                    ui.strong("Event Type");
+                    ui.strong("Description");
+                    ui.strong("Digests");
+                    ui.end_row();
+                    
+                    for line in &table.lines {
+                        ui.label(line.line_number.to_string());
+                        ui.label(line.pcr_index.to_string());
+                        ui.label(line.event_type.map_or("-", event_type_name));
+                        ui.label(&line.description);
+                        ui.vertical(|ui| {
+                            for tagged in &line.digests {
+                                ui.monospace(format!("{}: {}", tagged.algorithm.name(), hex::encode(&tagged.digest)));
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+        });
+        
+        let diagnostics = self.file_processor.get_diagnostics().to_vec();
+        self.show_line_diagnostics(ui, ctx, &diagnostics, table.lines.is_empty());
+        
+        ui.label("Replaying resets all PCRs and extends each line into its own PCR in every active bank.");
+        ui.horizontal(|ui| {
+            let file_banks = self.file_processor
+                .get_measurement_table()
+                .map(|table| table.algorithms())
+                .unwrap_or_default();
+            if ui.add_enabled(!file_banks.is_empty(), egui::Button::new("Use File Banks")).clicked() {
+                if !file_banks.contains(&self.selected_algorithm) {
+                    self.selected_algorithm = file_banks[0].clone();
+                }
+                self.simulator.set_active_banks(file_banks);
+            }
+            if ui.button("Replay Measurement Table").clicked() {
+                let events = self.file_processor.measurement_table_events();
+                self.replay_error = self.simulator.replay_events(events, self.startup_locality).err().map(|e| e.to_string());
+            }
+            if ui.button("Debug Replay").clicked() {
+                let events = self.file_processor.measurement_table_events();
+                self.open_debugger(ReplayDebugger::for_log(&self.simulator, events, self.startup_locality));
+            }
+        });
+    }
+    
+    /// Display parsed binary event log
+    fn show_event_log(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        let Some(event_log) = self.file_processor.get_event_log() else {
+            return;
+        };
+        
+        let format = match event_log.format {
+            EventLogFormat::CryptoAgile => "crypto-agile",
+            EventLogFormat::Sha1 => "SHA1",
+        };
+        let banks: Vec<&str> = event_log.algorithms().iter().map(|alg| alg.name()).collect();
+        ui.label(format!(
+            "Parsed {} events from TCG event log ({} format, banks: {})",
+            event_log.events.len(),
+            format,
+            banks.join(", ")
+        ));
+        if let Some(ref spec_id) = event_log.spec_id {
+            ui.label(format!(
+                "Spec ID: version {}.{} errata {}, platform class {}, UINTN size {}, {} bytes vendor info",
+                spec_id.spec_version_major,
+                spec_id.spec_version_minor,
+                spec_id.spec_errata,
+                spec_id.platform_class,
+                spec_id.uintn_size,
+                spec_id.vendor_info.len()
+            ));
+        }
+        
+        // tpm2_eventlog events whose data could not be read
+        let diagnostics = self.file_processor.get_diagnostics().to_vec();
+        if !diagnostics.is_empty() {
+            self.show_line_diagnostics(ui, ctx, &diagnostics, false);
+        }
+        
+        egui::ScrollArea::vertical().id_source("event_log").max_height(200.0).show(ui, |ui| {
+            for (i, event) in event_log.events.iter().enumerate() {
+                ui.label(format!("#{}: PCR{} {}", i, event.pcr_index, event.event_type_name()));
+                for algorithm in self.simulator.active_algorithms() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("    {}:", algorithm.name()));
+                        match event.digest_for(&algorithm) {
+                            Some(digest) => ui.monospace(hex::encode(digest)),
+                            None => ui.weak("no digest"),
+                        };
+                    });
+                }
+            }
+        });
+        
+        ui.label("Replaying resets all PCRs and extends each event into its own PCR in every active bank.");
+        ui.horizontal(|ui| {
+            if ui.button("Replay Event Log").clicked() {
+                let events = self.file_processor.event_log_events();
+                self.replay_error = self.simulator.replay_events(events, self.startup_locality).err().map(|e| e.to_string());
+            }
+            if ui.button("Debug Replay").clicked() {
+                let events = self.file_processor.event_log_events();
+                self.open_debugger(ReplayDebugger::for_log(&self.simulator, events, self.startup_locality));
+            }
+        });
+        
+        // tpm2_eventlog output ends with the PCR values read from the TPM
+        let Some(expected_values) = self.file_processor.get_expected_values().filter(|values| !values.is_empty()).cloned() else {
+            return;
+        };
+        ui.add_space(5.0);
+        ui.label(format!("The log includes {} PCR values read from the TPM.", expected_values.values.len()));
+        if ui.button("Replay and Verify").clicked() {
+            let events = self.file_processor.event_log_events();
+            match self.simulator.replay_events(events, self.startup_locality) {
+                Ok(_) => {
+                    self.replay_error = None;
+                    self.verify_against(&expected_values);
+                },
+                Err(err) => self.replay_error = Some(err.to_string()),
+            }
+        }
+        self.show_verification_summary(ui);
+    }
+    
+    /// Display expected PCR values from tpm2_pcrread output
+    fn show_pcrread_values(&mut self, ui: &mut egui::Ui) {
+        let Some(expected_values) = self.file_processor.get_expected_values().cloned() else {
+            return;
+        };
+        
+        let mut banks: Vec<&str> = expected_values.values.iter().map(|golden| golden.algorithm.name()).collect();
+        banks.dedup();
+        ui.label(format!(
+            "Parsed {} PCR values from tpm2_pcrread output (banks: {})",
+            expected_values.values.len(),
+            banks.join(", ")
+        ));
+        
+        egui::ScrollArea::vertical().id_source("pcrread_values").max_height(200.0).show(ui, |ui| {
+            for golden in &expected_values.values {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} PCR{}:", golden.algorithm.name(), golden.pcr_index));
+                    ui.monospace(hex::encode(&golden.value));
+                });
+            }
+        });
+        
+        ui.label("Verifying compares these values with the current PCRs and measurement log.");
+        if ui.button("Verify Against These Values").clicked() {
+            self.verify_against(&expected_values);
+        }
+        self.show_verification_summary(ui);
+    }
+    
+    /// Display the local TPM paths and the "Verify This Machine" button
+    fn show_machine_verification(&mut self, ui: &mut egui::Ui) {
+        ui.label("Replays the firmware event log of the local TPM and compares the PCRs it extends with their live values.");
+        egui::Grid::new("machine_paths").num_columns(2).show(ui, |ui| {
+            ui.label("Event log:");
+            ui.add(egui::TextEdit::singleline(&mut self.machine_event_log).desired_width(420.0));
+            ui.end_row();
+            ui.label("PCR directory:");
+            ui.add(egui::TextEdit::singleline(&mut self.machine_pcr_dir).desired_width(420.0));
+            ui.end_row();
+        });
+        
+        ui.horizontal(|ui| {
+            if ui.button("Verify This Machine").clicked() {
+                self.verify_machine();
+            }
+            if ui.button("Default Paths").clicked() {
+                self.machine_event_log = DEFAULT_EVENT_LOG_PATH.to_string();
+                self.machine_pcr_dir = DEFAULT_PCR_DIR.to_string();
+            }
+        });
+        ui.weak("Reading the event log usually requires root.");
+        
+        if let Some(ref error) = self.machine_error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+        self.show_verification_summary(ui);
+    }
+    
+    /// Load the local TPM's event log into the simulator and verify it against the live PCRs
+    fn verify_machine(&mut self) {
+        let tpm = LinuxTpm::new(self.machine_event_log.trim(), self.machine_pcr_dir.trim());
+        match tpm.verify() {
+            Ok(verification) => {
+                self.simulator = verification.simulator;
+                let algorithms = self.simulator.active_algorithms();
+                if !algorithms.contains(&self.selected_algorithm) {
+                    self.selected_algorithm = algorithms[0].clone();
+                }
+                self.golden_text = GoldenValues::new(verification.report.results
+                    .iter()
+                    .map(|result| GoldenValue {
+                        algorithm: result.algorithm.clone(),
+                        pcr_index: result.pcr_index,
+                        value: result.expected.clone(),
+                    })
+                    .collect()
+                ).to_text();
+                self.golden_error = None;
+                self.verification = Some(verification.report);
+                self.machine_error = None;
+            },
+            Err(err) => self.machine_error = Some(err.to_string()),
+        }
+    }
+    
+    /// Use values as the golden values and verify the simulator against them
+    fn verify_against(&mut self, golden_values: &GoldenValues) {
+        self.golden_text = golden_values.to_text();
+        self.golden_error = None;
+        self.verification = Some(self.simulator.verify(golden_values));
+    }
+    
+    /// One-line result of the last verification
+    fn show_verification_summary(&self, ui: &mut egui::Ui) {
+        let Some(ref report) = self.verification else {
+            return;
+        };
+        if report.all_match() {
+            ui.colored_label(egui::Color32::GREEN, format!("All {} PCR values match", report.results.len()));
+        } else {
+            ui.colored_label(egui::Color32::RED, format!(
+                "{} of {} PCR values do not match, {} could not be checked; see Golden Value Verification",
+                report.mismatch_count(),
+                report.results.len(),
+                report.errors.len()
+            ));
+        }
+    }
+
+    /// Display parsed IMA measurement list
+    fn show_ima_list(&mut self, ui: &mut egui::Ui) {
+        let Some(ima_list) = self.file_processor.get_ima_list() else {
+            return;
+        };
+        
+        let mismatched = ima_list.mismatched_entries().len();
+        ui.label(format!(
+            "Parsed {} entries from IMA {} measurement list",
+            ima_list.entries.len(),
+            if ima_list.binary { "binary" } else { "ascii" }
+        ));
+        if mismatched > 0 {
+            ui.colored_label(
+                egui::Color32::RED,
+                format!("{} entries have a template hash that does not match their template data", mismatched)
+            );
+        }
+        
+        egui::ScrollArea::vertical().id_source("ima_list").max_height(200.0).show(ui, |ui| {
+            for entry in &ima_list.entries {
+                ui.horizontal(|ui| {
+                    let text = format!(
+                        "{}: PCR{} {} {}:{} {}",
+                        entry.position,
+                        entry.pcr_index,
+                        entry.template_name,
+                        entry.file_digest_algorithm,
+                        hex::encode(&entry.file_digest),
+                        entry.file_name
+                    );
+                    if entry.is_violation() {
+                        ui.colored_label(egui::Color32::YELLOW, format!("{} (violation)", text));
+                    } else if !entry.template_hash_matches() {
+                        ui.colored_label(egui::Color32::RED, format!("{} (template hash mismatch)", text));
+                    } else {
+                        ui.label(text);
+                    }
+                });
+            }
+        });
+        
+        ui.label(format!(
+            "Replaying resets PCR {} and extends the recomputed template hash of every entry in every active bank.",
+            IMA_PCR
+        ));
+        ui.horizontal(|ui| {
+            if ui.button(format!("Replay into PCR {}", IMA_PCR)).clicked() {
+                let events = self.file_processor.ima_events(&self.simulator.active_algorithms());
+                self.replay_error = self.simulator.replay(IMA_PCR, events).err().map(|e| e.to_string());
+            }
+            if ui.button("Debug Replay").clicked() {
+                let events = self.file_processor.ima_events(&self.simulator.active_algorithms());
+                self.open_debugger(ReplayDebugger::for_pcr(&self.simulator, IMA_PCR, events));
+            }
+        });
+    }
+
+    /// Display export format selection and export buttons
+    fn show_export(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Format:");
+            for format in ExportFormat::all() {
+                ui.selectable_value(&mut self.export_format, format, format.name());
+            }
+        });
+        ui.weak(match self.export_format {
+            ExportFormat::Json => "PCR values as a list of bank, PCR and value; the log with every event field.",
+            ExportFormat::Csv => "PCR values as bank,pcr,value rows; the log as a measurement table that can be loaded again.",
+            ExportFormat::Yaml => "PCR values as tpm2_pcrread output; the log in tpm2_eventlog style.",
+        });
+        
+        ui.horizontal(|ui| {
+            if ui.button("Export PCR Values").clicked() {
+                self.export_dialog = Some(ExportContent::PcrValues);
+            }
+            if ui.button("Export Measurement Log").clicked() {
+                self.export_dialog = Some(ExportContent::MeasurementLog);
+            }
+            if ui.button("Copy PCR Values").clicked() {
+                ui.output_mut(|o| o.copied_text = self.simulator.export_pcr_values(self.export_format));
+                self.export_status = Some(Ok("PCR values copied to the clipboard".to_string()));
+            }
+        });
+        
+        match self.export_status {
+            Some(Ok(ref message)) => {
+                ui.label(message);
+            },
+            Some(Err(ref error)) => {
+                ui.colored_label(egui::Color32::RED, error);
+            },
+            None => {},
+        }
+    }
+    
+    /// Display golden value verification UI
+    fn show_verification(&mut self, ui: &mut egui::Ui) {
+        ui.label("Expected PCR values, one per line as '[bank:]pcr: value', or tpm2_pcrread output:");
+        let text_edit = ui.add_sized(
+            [ui.available_width(), 80.0],
+            egui::TextEdit::multiline(&mut self.golden_text)
+                .font(egui::TextStyle::Monospace)
+                .hint_text("sha256:\n  0 : 0x...\n  7 : 0x...")
+        );
+        if text_edit.changed() {
+            self.golden_error = None;
+        }
+        
+        ui.horizontal(|ui| {
+            if ui.button("Load File").clicked() {
+                self.show_golden_dialog = true;
+            }
+            
+            if ui.button("Verify").clicked() {
+                match GoldenValues::parse(&self.golden_text, &self.selected_algorithm) {
+                    Ok(golden_values) if golden_values.is_empty() => {
+                        self.golden_error = Some("Please enter at least one expected PCR value".to_string());
+                        self.verification = None;
+                    }
+                    Ok(golden_values) => {
+                        self.golden_error = None;
+                        self.verification = Some(self.simulator.verify(&golden_values));
+                    }
+                    Err(err) => {
+                        self.golden_error = Some(err.to_string());
+                        self.verification = None;
+                    }
+                }
             }
+        });
+        
+        if let Some(ref error) = self.golden_error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+        
+        let Some(ref report) = self.verification else {
+            return;
+        };
+        
+        if report.all_match() {
+            ui.colored_label(egui::Color32::GREEN, format!("All {} PCR values match", report.results.len()));
         } else {
-            ui.label("Please select a file containing measurements to simulate PCR extension");
+            ui.colored_label(
+                egui::Color32::RED,
+                format!("{} of {} PCR values do not match", report.mismatch_count(), report.results.len())
+            );
+        }
+        for error in &report.errors {
+            ui.colored_label(egui::Color32::RED, error);
         }
+        
+        egui::ScrollArea::vertical().id_source("verification_results").max_height(200.0).show(ui, |ui| {
+            for result in &report.results {
+                ui.horizontal(|ui| {
+                    if result.is_match() {
+                        ui.colored_label(egui::Color32::GREEN, "MATCH");
+                    } else {
+                        ui.colored_label(egui::Color32::RED, "MISMATCH");
+                    }
+                    ui.label(format!("PCR{} {}: {}", result.pcr_index, result.algorithm.name(), result.summary()));
+                });
+                if !result.is_match() {
+                    ui.horizontal(|ui| {
+                        ui.label("    expected:");
+                        ui.monospace(hex::encode(&result.expected));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("    replayed:");
+                        ui.monospace(hex::encode(&result.replayed));
+                    });
+                    if let Some(event) = result.first_divergent_event.and_then(|i| self.simulator.measurement_log().get(i)) {
+                        ui.label(format!("    divergent event: {} ({})", event.description, event.source));
+                    }
+                }
+            }
+        });
     }
 
     /// Display measurement log
+    /// Remember which PCRs changed since the previous frame, i.e. by the last operation
+    fn track_changes(&mut self) {
+        let changed: Vec<(HashAlgorithm, usize)> = self.simulator.banks()
+            .iter()
+            .flat_map(|bank| {
+                // A bank that was just activated starts out unchanged
+                let previous = self.last_banks.iter().find(|previous| previous.algorithm == bank.algorithm);
+                (0..PCR_COUNT)
+                    .filter(move |&pcr| previous.is_some_and(|previous| previous.pcr_values[pcr] != bank.pcr_values[pcr]))
+                    .map(move |pcr| (bank.algorithm.clone(), pcr))
+            })
+            .collect();
+        
+        let banks_changed = self.last_banks.len() != self.simulator.banks().len()
+            || self.last_banks.iter().zip(self.simulator.banks()).any(|(previous, bank)| previous.algorithm != bank.algorithm);
+        if !changed.is_empty() || banks_changed {
+            self.changed_pcrs = changed;
+            self.last_banks = self.simulator.banks().to_vec();
+        }
+    }
+    
+    /// Grid of every PCR in every active bank; clicking a cell filters the log to that PCR
+    fn show_pcr_overview(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.weak("Highlighted PCRs were changed by the last operation. Click a PCR to filter the log.");
+        });
+        
+        let mut clicked = None;
+        egui::ScrollArea::vertical().id_source("pcr_overview").max_height(300.0).show(ui, |ui| {
+            egui::Grid::new("pcr_overview_grid").striped(true).show(ui, |ui| {
+                ui.strong("PCR");
+                for bank in self.simulator.banks() {
+                    ui.strong(bank.algorithm.name());
+                }
+                ui.end_row();
+                
+                for pcr in 0..PCR_COUNT {
+                    let selected = self.log_filter == Some(pcr);
+                    if ui.selectable_label(selected, format!("PCR {}", pcr)).clicked() {
+                        clicked = Some(pcr);
+                    }
+                    
+                    for bank in self.simulator.banks() {
+                        let value = hex::encode(&bank.pcr_values[pcr]);
+                        let extends = self.simulator.extend_count(pcr, &bank.algorithm);
+                        let initial = self.simulator.is_initial(&bank.algorithm, pcr).unwrap_or(false);
+                        let state = if initial {
+                            "initial".to_string()
+                        } else {
+                            format!("{} extend{}", extends, if extends == 1 { "" } else { "s" })
+                        };
+                        
+                        let mut text = egui::RichText::new(format!("{}…  {}", &value[..16], state)).monospace();
+                        if initial {
+                            text = text.weak();
+                        }
+                        let mut cell = egui::Button::new(text).selected(selected);
+                        if self.changed_pcrs.contains(&(bank.algorithm.clone(), pcr)) {
+                            cell = cell.fill(egui::Color32::from_rgb(110, 90, 20));
+                        }
+                        
+                        let hover = format!("{} PCR {}\n{}\n{} logged extends", bank.algorithm.name(), pcr, value, extends);
+                        if ui.add(cell).on_hover_text(hover).clicked() {
+                            clicked = Some(pcr);
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+        
+        if let Some(pcr) = clicked {
+            self.selected_pcr = pcr;
+            self.log_filter = Some(pcr);
+            self.show_details = true;
+        }
+    }
+    
     fn show_measurement_log(&mut self, ui: &mut egui::Ui) {
         ui.group(|ui| {
             ui.heading("Measurement Log");
             
-            if self.simulator.measurement_log.is_empty() {
+            if let Some(pcr) = self.log_filter {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Showing PCR {} only", pcr));
+                    if ui.button("Show All").clicked() {
+                        self.log_filter = None;
+                    }
+                });
+            }
+            
+            if self.simulator.measurement_log().is_empty() {
                 ui.label("No measurement records");
                 return;
             }
             
-            egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
-                for (i, (description, value, pcr_index)) in self.simulator.measurement_log.iter().enumerate() {
+            let log_filter = self.log_filter;
+            egui::ScrollArea::vertical().id_source("measurement_log").max_height(200.0).show(ui, |ui| {
+                for (i, event) in self.simulator.measurement_log().iter().enumerate() {
+                    if log_filter.is_some_and(|pcr| pcr != event.pcr_index) {
+                        continue;
+                    }
                     ui.horizontal(|ui| {
-                        ui.label(format!("#{}: PCR{} ", i+1, pcr_index));
-                        ui.label(description);
-                        ui.monospace(hex::encode(value));
+                        ui.label(format!("#{}: PCR{} ", i+1, event.pcr_index));
+                        ui.label(event.event_type_name());
+                        ui.label(&event.description);
                     });
+                    ui.horizontal(|ui| {
+                        ui.weak(format!(
+                            "    {} UTC, source: {}, {} bytes event data",
+                            event.time_of_day(),
+                            event.source,
+                            event.event_data.len()
+                        ));
+                    });
+                    match event.decode_event_data() {
+                        Some(Ok(decoded)) => {
+                            for (name, value) in decoded.fields() {
+                                ui.horizontal(|ui| {
+                                    ui.label(format!("    {}:", name));
+                                    ui.monospace(value);
+                                });
+                            }
+                        },
+                        Some(Err(err)) => {
+                            ui.colored_label(egui::Color32::RED, format!("    Invalid event data: {}", err));
+                        },
+                        None => {},
+                    }
+                    for tagged in &event.digests {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("    {}:", tagged.algorithm.name()));
+                            ui.monospace(hex::encode(&tagged.digest));
+                        });
+                    }
+                    match event.data_digest_mismatches() {
+                        Some(mismatches) if mismatches.is_empty() => {
+                            ui.colored_label(egui::Color32::GREEN, "    ✓ Digests match the event data");
+                        },
+                        Some(mismatches) => {
+                            let names: Vec<&str> = mismatches.iter().map(|algorithm| algorithm.name()).collect();
+                            ui.colored_label(egui::Color32::RED, format!(
+                                "    ✗ {} digest does not match the event data",
+                                names.join(", ")
+                            ));
+                        },
+                        None => {},
+                    }
                 }
             });
         });
@@ -1,14 +1,113 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
 use eframe::egui;
-use crate::algorithms::{InputType, HashType, calculate_hash};
+use eggs::{
+    InputType, HashType, ExpectedDigest, calculate_hash, calculate_hmac, digest_matrix, digest_matrix_file,
+    hash_file, hmac_file,
+};
+use eggs::Error;
 
 pub struct HashCalculatorApp {
     input: String,
-    input_type: InputType,
+    input_mode: InputMode,
     hash_type: HashType,
     result: String,
     error_msg: Option<String>,
     copied: bool,
     active_tab: HashCategory,
+    file_path: String,
+    file_job: Option<FileHashJob>,
+    hmac_enabled: bool,
+    key_mode: InputMode,
+    key_input: String,
+    key_file_path: String,
+    matrix_mode: bool,
+    matrix: Vec<(HashType, String)>,
+    copied_row: Option<HashType>,
+    expected_input: String,
+    expected: Option<ExpectedDigest>,
+    expected_error: Option<String>,
+}
+
+/// Source of the data to hash, or of the HMAC key
+#[derive(PartialEq, Clone, Copy)]
+enum InputMode {
+    Text,
+    Hex,
+    File,
+}
+
+/// Digest of the selected algorithm, or the digest matrix of all of them
+enum HashOutput {
+    Single(String),
+    Matrix(Vec<(HashType, String)>),
+}
+
+/// File being hashed on a background thread
+struct FileHashJob {
+    hash_type: HashType,
+    hmac: bool,
+    matrix: bool,
+    total_bytes: u64,
+    processed: Arc<AtomicU64>,
+    cancel: Arc<AtomicBool>,
+    result: mpsc::Receiver<eggs::Result<HashOutput>>,
+}
+
+impl FileHashJob {
+    /// Start hashing a file in chunks on a background thread
+    ///
+    /// Computes an HMAC when a key is given, and every algorithm at once in matrix mode.
+    fn start(path: &str, hash_type: HashType, key: Option<Vec<u8>>, matrix: bool) -> Self {
+        let total_bytes = std::fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0);
+        let processed = Arc::new(AtomicU64::new(0));
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = mpsc::channel();
+
+        let path = path.to_string();
+        let thread_processed = Arc::clone(&processed);
+        let thread_cancel = Arc::clone(&cancel);
+        let hmac = key.is_some();
+        thread::spawn(move || {
+            let progress = |bytes| {
+                thread_processed.store(bytes, Ordering::Relaxed);
+                !thread_cancel.load(Ordering::Relaxed)
+            };
+            let result = if matrix {
+                digest_matrix_file(&path, key.as_deref(), progress).map(HashOutput::Matrix)
+            } else {
+                match key {
+                    Some(key) => hmac_file(&path, &key, hash_type, progress),
+                    None => hash_file(&path, hash_type, progress),
+                }
+                .map(HashOutput::Single)
+            };
+            let _ = sender.send(result);
+        });
+
+        Self {
+            hash_type,
+            hmac,
+            matrix,
+            total_bytes,
+            processed,
+            cancel,
+            result: receiver,
+        }
+    }
+
+    fn progress(&self) -> f32 {
+        if self.total_bytes == 0 {
+            return 0.0;
+        }
+        self.processed.load(Ordering::Relaxed) as f32 / self.total_bytes as f32
+    }
+
+    fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
 }
 
 #[derive(PartialEq, Clone, Copy)]
@@ -23,18 +122,32 @@ impl Default for HashCalculatorApp {
     fn default() -> Self {
         Self {
             input: String::new(),
-            input_type: InputType::Text,
+            input_mode: InputMode::Text,
             hash_type: HashType::Sha256,
             result: String::new(),
             error_msg: None,
             copied: false,
             active_tab: HashCategory::SHA2,
+            file_path: String::new(),
+            file_job: None,
+            hmac_enabled: false,
+            key_mode: InputMode::Text,
+            key_input: String::new(),
+            key_file_path: String::new(),
+            matrix_mode: false,
+            matrix: Vec::new(),
+            copied_row: None,
+            expected_input: String::new(),
+            expected: None,
+            expected_error: None,
         }
     }
 }
 
 impl HashCalculatorApp {
     pub fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.poll_file_job(ctx);
+
         egui::CentralPanel::default().show(ctx, |ui| {
             // Title
             ui.vertical_centered(|ui| {
@@ -47,102 +160,140 @@ impl HashCalculatorApp {
                 // Input type selector
                 ui.horizontal(|ui| {
                     ui.label("Input Type:");
-                    ui.radio_value(&mut self.input_type, InputType::Text, "Text");
-                    ui.radio_value(&mut self.input_type, InputType::Hex, "Hex");
+                    let previous_mode = self.input_mode;
+                    ui.radio_value(&mut self.input_mode, InputMode::Text, "Text");
+                    ui.radio_value(&mut self.input_mode, InputMode::Hex, "Hex");
+                    ui.radio_value(&mut self.input_mode, InputMode::File, "File");
+                    if self.input_mode != previous_mode {
+                        self.cancel_file_job();
+                        self.clear_output();
+                        self.error_msg = None;
+                    }
                 });
                 
                 ui.add_space(10.0);
                 
-                // Hash algorithm categories
-                ui.horizontal(|ui| {
-                    if ui.selectable_label(self.active_tab == HashCategory::SHA1, "SHA1").clicked() {
-                        self.active_tab = HashCategory::SHA1;
-                        if self.hash_type != HashType::Sha1 {
-                            self.hash_type = HashType::Sha1;
-                            self.compute_hash();
+                // All algorithms at once
+                if ui.checkbox(&mut self.matrix_mode, "All Algorithms (digest matrix)").changed() {
+                    self.compute_hash();
+                    self.copied = false;
+                }
+                
+                ui.add_space(5.0);
+                
+                // The algorithm selection does not apply to the digest matrix
+                ui.add_enabled_ui(!self.matrix_mode, |ui| {
+                    // Hash algorithm categories
+                    ui.horizontal(|ui| {
+                        if ui.selectable_label(self.active_tab == HashCategory::SHA1, "SHA1").clicked() {
+                            self.active_tab = HashCategory::SHA1;
+                            if self.hash_type != HashType::Sha1 {
+                                self.hash_type = HashType::Sha1;
+                                self.compute_hash();
+                            }
                         }
-                    }
-                    if ui.selectable_label(self.active_tab == HashCategory::SHA2, "SHA2").clicked() {
-                        self.active_tab = HashCategory::SHA2;
-                        if self.hash_type != HashType::Sha256 {
-                            self.hash_type = HashType::Sha256;
-                            self.compute_hash();
+                        if ui.selectable_label(self.active_tab == HashCategory::SHA2, "SHA2").clicked() {
+                            self.active_tab = HashCategory::SHA2;
+                            if self.hash_type != HashType::Sha256 {
+                                self.hash_type = HashType::Sha256;
+                                self.compute_hash();
+                            }
                         }
-                    }
-                    if ui.selectable_label(self.active_tab == HashCategory::SHA3, "SHA3").clicked() {
-                        self.active_tab = HashCategory::SHA3;
-                        if self.hash_type != HashType::Sha3_256 {
-                            self.hash_type = HashType::Sha3_256;
-                            self.compute_hash();
+                        if ui.selectable_label(self.active_tab == HashCategory::SHA3, "SHA3").clicked() {
+                            self.active_tab = HashCategory::SHA3;
+                            if self.hash_type != HashType::Sha3_256 {
+                                self.hash_type = HashType::Sha3_256;
+                                self.compute_hash();
+                            }
                         }
-                    }
-                    if ui.selectable_label(self.active_tab == HashCategory::SM, "SM").clicked() {
-                        self.active_tab = HashCategory::SM;
-                        if self.hash_type != HashType::Sm3 {
-                            self.hash_type = HashType::Sm3;
-                            self.compute_hash();
+                        if ui.selectable_label(self.active_tab == HashCategory::SM, "SM").clicked() {
+                            self.active_tab = HashCategory::SM;
+                            if self.hash_type != HashType::Sm3 {
+                                self.hash_type = HashType::Sm3;
+                                self.compute_hash();
+                            }
                         }
-                    }
-                });
+                    });
                 
-                ui.add_space(5.0);
+                    ui.add_space(5.0);
                 
-                // Hash algorithm selection
-                match self.active_tab {
-                    HashCategory::SHA1 => {
-                        ui.horizontal(|ui| {
-                            ui.selectable_value(&mut self.hash_type, HashType::Sha1, "SHA-1");
-                        });
-                    },
-                    HashCategory::SHA2 => {
-                        ui.horizontal(|ui| {
-                            ui.selectable_value(&mut self.hash_type, HashType::Sha224, "SHA-224");
-                            ui.selectable_value(&mut self.hash_type, HashType::Sha256, "SHA-256");
-                            ui.selectable_value(&mut self.hash_type, HashType::Sha384, "SHA-384");
-                            ui.selectable_value(&mut self.hash_type, HashType::Sha512, "SHA-512");
-                        });
-                    },
-                    HashCategory::SHA3 => {
-                        ui.horizontal(|ui| {
-                            ui.selectable_value(&mut self.hash_type, HashType::Sha3_224, "SHA3-224");
-                            ui.selectable_value(&mut self.hash_type, HashType::Sha3_256, "SHA3-256");
-                            ui.selectable_value(&mut self.hash_type, HashType::Sha3_384, "SHA3-384");
-                            ui.selectable_value(&mut self.hash_type, HashType::Sha3_512, "SHA3-512");
-                        });
-                    },
-                    HashCategory::SM => {
-                        ui.horizontal(|ui| {
-                            ui.selectable_value(&mut self.hash_type, HashType::Sm3, "SM3");
-                        });
-                    },
-                }
+                    // Hash algorithm selection
+                    match self.active_tab {
+                        HashCategory::SHA1 => {
+                            ui.horizontal(|ui| {
+                                ui.selectable_value(&mut self.hash_type, HashType::Sha1, "SHA-1");
+                            });
+                        },
+                        HashCategory::SHA2 => {
+                            ui.horizontal(|ui| {
+                                ui.selectable_value(&mut self.hash_type, HashType::Sha224, "SHA-224");
+                                ui.selectable_value(&mut self.hash_type, HashType::Sha256, "SHA-256");
+                                ui.selectable_value(&mut self.hash_type, HashType::Sha384, "SHA-384");
+                                ui.selectable_value(&mut self.hash_type, HashType::Sha512, "SHA-512");
+                            });
+                        },
+                        HashCategory::SHA3 => {
+                            ui.horizontal(|ui| {
+                                ui.selectable_value(&mut self.hash_type, HashType::Sha3_224, "SHA3-224");
+                                ui.selectable_value(&mut self.hash_type, HashType::Sha3_256, "SHA3-256");
+                                ui.selectable_value(&mut self.hash_type, HashType::Sha3_384, "SHA3-384");
+                                ui.selectable_value(&mut self.hash_type, HashType::Sha3_512, "SHA3-512");
+                            });
+                        },
+                        HashCategory::SM => {
+                            ui.horizontal(|ui| {
+                                ui.selectable_value(&mut self.hash_type, HashType::Sm3, "SM3");
+                            });
+                        },
+                    }
+                });
                 
                 ui.add_space(10.0);
                 
-                // Input field
-                ui.label("Input:");
-                let text_edit = ui.add_sized(
-                    [ui.available_width(), 100.0],
-                    egui::TextEdit::multiline(&mut self.input)
-                        .hint_text("Enter text or hex to hash...")
-                        .desired_width(f32::INFINITY)
-                );
-                
-                if text_edit.changed() {
+                // HMAC key
+                if ui.checkbox(&mut self.hmac_enabled, "HMAC").changed() {
                     self.compute_hash();
                     self.copied = false;
                 }
+                if self.hmac_enabled {
+                    self.show_key_input(ui);
+                }
                 
                 ui.add_space(10.0);
                 
-                // Compute button
-                if ui.button("Calculate Hash").clicked() {
-                    self.compute_hash();
-                    self.copied = false;
+                if self.input_mode == InputMode::File {
+                    self.show_file_input(ui);
+                } else {
+                    // Input field
+                    ui.label("Input:");
+                    let text_edit = ui.add_sized(
+                        [ui.available_width(), 100.0],
+                        egui::TextEdit::multiline(&mut self.input)
+                            .hint_text("Enter text or hex to hash...")
+                            .desired_width(f32::INFINITY)
+                    );
+                    
+                    if text_edit.changed() {
+                        self.compute_hash();
+                        self.copied = false;
+                    }
+                    
+                    ui.add_space(10.0);
+                    
+                    // Compute button
+                    if ui.button("Calculate Hash").clicked() {
+                        self.compute_hash();
+                        self.copied = false;
+                    }
                 }
                 
                 ui.add_space(10.0);
                 
+                // Expected digest
+                self.show_expected_input(ui);
+                
+                ui.add_space(10.0);
+                
                 // Display error if any
                 if let Some(ref error) = self.error_msg {
                     ui.colored_label(egui::Color32::RED, error);
@@ -150,82 +301,393 @@ impl HashCalculatorApp {
                 }
                 
                 // Result section
-                ui.group(|ui| {
-                    ui.horizontal(|ui| {
-                        ui.label("Result:");
-                        
-                        // Hash type label
-                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                            let hash_name = match self.hash_type {
-                                HashType::Sha1 => "SHA-1",
-                                HashType::Sha224 => "SHA-224",
-                                HashType::Sha256 => "SHA-256",
-                                HashType::Sha384 => "SHA-384",
-                                HashType::Sha512 => "SHA-512",
-                                HashType::Sha3_224 => "SHA3-224",
-                                HashType::Sha3_256 => "SHA3-256",
-                                HashType::Sha3_384 => "SHA3-384",
-                                HashType::Sha3_512 => "SHA3-512",
-                                HashType::Sm3 => "SM3",
-                            };
-                            ui.label(hash_name);
-                        });
-                    });
-                    
-                    // Result display
-                    let mut result_copy = self.result.clone();
-                    ui.add_sized(
-                        [ui.available_width(), 80.0],
-                        egui::TextEdit::multiline(&mut result_copy)
-                            .font(egui::TextStyle::Monospace)
-                            .desired_width(f32::INFINITY)
-                            .interactive(false)
-                    );
-                    
-                    // Copy button with feedback
-                    let copy_label = if self.copied { "✓ Copied!" } else { "Copy Result" };
-                    if ui.button(copy_label).clicked() {
-                        ui.output_mut(|o| o.copied_text = self.result.clone());
-                        self.copied = true;
-                    }
-                });
-                
-                // Hash length information
-                if !self.result.is_empty() {
-                    ui.horizontal(|ui| {
-                        ui.label(format!("Hash Length: {} characters ({} bytes)", 
-                            self.result.len(), 
-                            self.result.len() / 2));
-                    });
+                if self.matrix_mode {
+                    self.show_matrix(ui);
+                } else {
+                    self.show_result(ui);
                 }
             });
             
             // Information at the bottom
             ui.vertical_centered(|ui| {
                 ui.add_space(10.0);
-                ui.label("This tool calculates cryptographic hashes using various algorithms.");
-                ui.label("Input can be plain text or hexadecimal values.");
+                ui.label("This tool calculates cryptographic hashes and HMACs using various algorithms.");
+                ui.label("Input can be plain text, hexadecimal values or a file of any size.");
             });
         });
     }
 
-    fn compute_hash(&mut self) {
-        // Skip hash calculation if input is empty
-        if self.input.trim().is_empty() {
-            self.result = String::new();
-            self.error_msg = None;
-            return;
+    /// Digest of the selected algorithm
+    fn show_result(&mut self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.label("Result:");
+                
+                // Hash type label
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if self.hmac_enabled {
+                        ui.label(format!("HMAC-{}", self.hash_type.name()));
+                    } else {
+                        ui.label(self.hash_type.name());
+                    }
+                });
+            });
+            
+            // Result display
+            let mut result_copy = self.result.clone();
+            ui.add_sized(
+                [ui.available_width(), 80.0],
+                egui::TextEdit::multiline(&mut result_copy)
+                    .font(egui::TextStyle::Monospace)
+                    .desired_width(f32::INFINITY)
+                    .interactive(false)
+            );
+            
+            // Copy button with feedback
+            let copy_label = if self.copied { "✓ Copied!" } else { "Copy Result" };
+            if ui.button(copy_label).clicked() {
+                ui.output_mut(|o| o.copied_text = self.result.clone());
+                self.copied = true;
+            }
+        });
+        
+        // Verification verdict
+        if let Some(ref expected) = self.expected {
+            if !self.result.is_empty() {
+                if expected.matches_hex(&self.result) {
+                    ui.colored_label(egui::Color32::GREEN, "✔ PASS: matches the expected digest");
+                } else {
+                    ui.colored_label(egui::Color32::RED, "✘ FAIL: does not match the expected digest");
+                }
+            }
         }
         
-        match calculate_hash(&self.input, self.input_type, self.hash_type) {
-            Ok(hash) => {
+        // Hash length information
+        if !self.result.is_empty() {
+            ui.horizontal(|ui| {
+                ui.label(format!("Hash Length: {} characters ({} bytes)", 
+                    self.result.len(), 
+                    self.result.len() / 2));
+            });
+        }
+    }
+
+    /// Digests of every algorithm, one row each
+    fn show_matrix(&mut self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.label(if self.hmac_enabled { "HMAC Results:" } else { "Results:" });
+            egui::Grid::new("digest_matrix")
+                .num_columns(4)
+                .striped(true)
+                .spacing([10.0, 4.0])
+                .show(ui, |ui| {
+                    for (hash_type, digest) in &self.matrix {
+                        ui.label(hash_type.name());
+                        ui.monospace(digest);
+                        match self.expected {
+                            Some(ref expected) if expected.matches_hex(digest) => {
+                                ui.colored_label(egui::Color32::GREEN, "✔ match");
+                            },
+                            _ => {
+                                ui.label("");
+                            },
+                        }
+                        let copy_label = if self.copied_row == Some(*hash_type) { "✓ Copied!" } else { "Copy" };
+                        if ui.button(copy_label).clicked() {
+                            ui.output_mut(|o| o.copied_text = digest.clone());
+                            self.copied_row = Some(*hash_type);
+                        }
+                        ui.end_row();
+                    }
+                });
+
+            // Which algorithm produced the expected value
+            if let Some(ref expected) = self.expected {
+                if !self.matrix.is_empty() {
+                    let matching: Vec<&str> = self.matrix
+                        .iter()
+                        .filter(|(_, digest)| expected.matches_hex(digest))
+                        .map(|(hash_type, _)| hash_type.name())
+                        .collect();
+                    if matching.is_empty() {
+                        ui.colored_label(egui::Color32::RED, "✘ No algorithm produced the expected digest");
+                    } else {
+                        ui.colored_label(
+                            egui::Color32::GREEN,
+                            format!("✔ Expected digest produced by {}", matching.join(", "))
+                        );
+                    }
+                }
+            }
+
+            if !self.matrix.is_empty() && ui.button("Copy All").clicked() {
+                let all = self.matrix
+                    .iter()
+                    .map(|(hash_type, digest)| format!("{}: {}", hash_type.name(), digest))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                ui.output_mut(|o| o.copied_text = all);
+                self.copied_row = None;
+            }
+        });
+    }
+
+    /// Expected digest to verify the result against
+    fn show_expected_input(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Verify Against:");
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.expected_input)
+                    .hint_text("Expected digest, e.g. sha256:e3b0c442...")
+                    .font(egui::TextStyle::Monospace)
+                    .desired_width(400.0)
+            );
+            if response.changed() {
+                self.update_expected();
+            }
+            if ui.add_enabled(self.expected.is_some(), egui::Button::new("Try All Algorithms")).clicked() {
+                self.matrix_mode = true;
+                self.compute_hash();
+            }
+        });
+
+        if let Some(ref error) = self.expected_error {
+            ui.colored_label(egui::Color32::RED, error);
+        } else if let Some(ref expected) = self.expected {
+            let names: Vec<&str> = expected.candidates().iter().map(|hash_type| hash_type.name()).collect();
+            ui.label(format!("Likely algorithm: {}", names.join(" or ")));
+        }
+    }
+
+    /// Parse the expected digest and switch to the algorithm it most likely belongs to
+    fn update_expected(&mut self) {
+        self.expected = None;
+        self.expected_error = None;
+        if self.expected_input.trim().is_empty() {
+            return;
+        }
+
+        match ExpectedDigest::parse(&self.expected_input) {
+            Ok(expected) => {
+                let candidates = expected.candidates();
+                let switch_to = candidates.first().copied().filter(|_| !candidates.contains(&self.hash_type));
+                self.expected = Some(expected);
+                if let Some(hash_type) = switch_to {
+                    if !self.matrix_mode {
+                        self.select_hash_type(hash_type);
+                        self.compute_hash();
+                    }
+                }
+            },
+            Err(err) => self.expected_error = Some(err.to_string()),
+        }
+    }
+
+    /// Select a hash type together with its category tab
+    fn select_hash_type(&mut self, hash_type: HashType) {
+        self.hash_type = hash_type;
+        self.active_tab = match hash_type {
+            HashType::Sha1 => HashCategory::SHA1,
+            HashType::Sha224 | HashType::Sha256 | HashType::Sha384 | HashType::Sha512 => HashCategory::SHA2,
+            HashType::Sha3_224 | HashType::Sha3_256 | HashType::Sha3_384 | HashType::Sha3_512 => HashCategory::SHA3,
+            HashType::Sm3 => HashCategory::SM,
+        };
+    }
+
+    /// HMAC key as text, hex or the contents of a file
+    fn show_key_input(&mut self, ui: &mut egui::Ui) {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Key Type:");
+            changed |= ui.radio_value(&mut self.key_mode, InputMode::Text, "Text").changed();
+            changed |= ui.radio_value(&mut self.key_mode, InputMode::Hex, "Hex").changed();
+            changed |= ui.radio_value(&mut self.key_mode, InputMode::File, "File").changed();
+        });
+        ui.horizontal(|ui| {
+            ui.label("Key:");
+            if self.key_mode == InputMode::File {
+                changed |= ui
+                    .add(egui::TextEdit::singleline(&mut self.key_file_path).desired_width(400.0))
+                    .lost_focus();
+                if ui.button("Browse...").clicked() {
+                    if let Some(path) = rfd::FileDialog::new().pick_file() {
+                        self.key_file_path = path.display().to_string();
+                        changed = true;
+                    }
+                }
+            } else {
+                changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut self.key_input)
+                            .hint_text("Enter the HMAC key...")
+                            .desired_width(400.0)
+                    )
+                    .changed();
+            }
+        });
+
+        // Large files are hashed on demand only
+        if changed && self.input_mode != InputMode::File {
+            self.compute_hash();
+            self.copied = false;
+        }
+    }
+
+    /// HMAC key bytes, or `None` for a plain hash
+    fn hmac_key(&self) -> Result<Option<Vec<u8>>, String> {
+        if !self.hmac_enabled {
+            return Ok(None);
+        }
+        let key = match self.key_mode {
+            InputMode::Text => InputType::Text.decode(&self.key_input),
+            InputMode::Hex => InputType::Hex.decode(&self.key_input),
+            InputMode::File => std::fs::read(self.key_file_path.trim()).map_err(Error::from),
+        };
+        key.map(Some).map_err(|err| format!("Invalid HMAC key: {}", err))
+    }
+
+    /// File selection, progress and cancellation
+    fn show_file_input(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("File:");
+            ui.add(egui::TextEdit::singleline(&mut self.file_path).desired_width(400.0));
+            if ui.button("Browse...").clicked() {
+                if let Some(path) = rfd::FileDialog::new().pick_file() {
+                    self.file_path = path.display().to_string();
+                    self.compute_hash();
+                    self.copied = false;
+                }
+            }
+        });
+
+        ui.add_space(10.0);
+
+        let mut cancel = false;
+        if let Some(ref job) = self.file_job {
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::ProgressBar::new(job.progress())
+                        .desired_width(400.0)
+                        .show_percentage()
+                );
+                cancel = ui.button("Cancel").clicked();
+            });
+            let algorithm = if job.matrix { "all algorithms" } else { job.hash_type.name() };
+            ui.label(format!(
+                "Hashing with {}{}: {} of {} bytes",
+                if job.hmac { "HMAC-" } else { "" },
+                algorithm,
+                job.processed.load(Ordering::Relaxed),
+                job.total_bytes
+            ));
+        } else if ui.add_enabled(!self.file_path.trim().is_empty(), egui::Button::new("Calculate Hash")).clicked() {
+            self.compute_hash();
+            self.copied = false;
+        }
+
+        if cancel {
+            self.cancel_file_job();
+            self.error_msg = Some("Hashing cancelled".to_string());
+        }
+    }
+
+    /// Collect the result of a finished file hash
+    fn poll_file_job(&mut self, ctx: &egui::Context) {
+        let Some(ref job) = self.file_job else {
+            return;
+        };
+
+        match job.result.try_recv() {
+            Ok(Err(Error::Cancelled)) => self.file_job = None,
+            Ok(output) => {
+                self.file_job = None;
+                self.set_output(output);
+            },
+            Err(mpsc::TryRecvError::Empty) => ctx.request_repaint_after(Duration::from_millis(100)),
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.error_msg = Some("Hashing stopped unexpectedly".to_string());
+                self.file_job = None;
+            },
+        }
+    }
+
+    fn cancel_file_job(&mut self) {
+        if let Some(job) = self.file_job.take() {
+            job.cancel();
+        }
+    }
+
+    fn clear_output(&mut self) {
+        self.result = String::new();
+        self.matrix.clear();
+        self.copied = false;
+        self.copied_row = None;
+    }
+
+    fn set_output(&mut self, output: eggs::Result<HashOutput>) {
+        self.clear_output();
+        match output {
+            Ok(HashOutput::Single(hash)) => {
                 self.result = hash;
                 self.error_msg = None;
             },
+            Ok(HashOutput::Matrix(matrix)) => {
+                self.matrix = matrix;
+                self.error_msg = None;
+            },
+            Err(err) => self.error_msg = Some(err.to_string()),
+        }
+    }
+
+    fn compute_hash(&mut self) {
+        let key = match self.hmac_key() {
+            Ok(key) => key,
             Err(err) => {
-                self.result = String::new();
+                self.cancel_file_job();
+                self.clear_output();
                 self.error_msg = Some(err);
+                return;
+            }
+        };
+
+        let input_type = match self.input_mode {
+            InputMode::Text => InputType::Text,
+            InputMode::Hex => InputType::Hex,
+            InputMode::File => {
+                // Restart in the background with the current file, algorithm and key
+                self.cancel_file_job();
+                self.clear_output();
+                self.error_msg = None;
+                if !self.file_path.trim().is_empty() {
+                    self.file_job = Some(FileHashJob::start(
+                        self.file_path.trim(),
+                        self.hash_type,
+                        key,
+                        self.matrix_mode
+                    ));
+                }
+                return;
             },
+        };
+
+        // Skip hash calculation if input is empty
+        if self.input.trim().is_empty() {
+            self.clear_output();
+            self.error_msg = None;
+            return;
         }
+        
+        let output = if self.matrix_mode {
+            input_type
+                .decode(&self.input)
+                .map(|bytes| HashOutput::Matrix(digest_matrix(&bytes, key.as_deref())))
+        } else {
+            match key {
+                Some(key) => calculate_hmac(&self.input, input_type, &key, self.hash_type),
+                None => calculate_hash(&self.input, input_type, self.hash_type),
+            }
+            .map(HashOutput::Single)
+        };
+        self.set_output(output);
     }
-} 
\ No newline at end of file
+}
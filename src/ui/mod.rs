@@ -1,5 +1,6 @@
 pub mod hash_calculator;
 pub mod boot_replay;
+pub mod policy_calculator;
 pub mod app;
 
 // Export main application
@@ -0,0 +1,313 @@
+use eframe::egui;
+use eggs::{
+    HashAlgorithm, PcrSimulator, marshal_pcr_selection, PcrSelection, PolicyCalculator, PolicyCommand,
+};
+
+/// Policy Calculator - builds a policyDigest from the Boot Replay PCR values
+pub struct PolicyCalculatorApp {
+    calculator: PolicyCalculator,
+    command_kind: CommandKind,
+
+    // Command input
+    pcr_bank: HashAlgorithm,
+    pcr_list: String,
+    or_branches: String,
+    command_code: String,
+    name_input: String,
+    policy_ref_input: String,
+
+    error: Option<String>,
+    copied: Option<String>, // Digest last copied to the clipboard
+}
+
+/// Policy command to add
+#[derive(PartialEq, Debug, Clone, Copy)]
+enum CommandKind {
+    Pcr,
+    Or,
+    AuthValue,
+    Password,
+    CommandCode,
+    Secret,
+    Signed,
+    Authorize,
+}
+
+impl CommandKind {
+    fn all() -> [CommandKind; 8] {
+        [
+            CommandKind::Pcr,
+            CommandKind::Or,
+            CommandKind::AuthValue,
+            CommandKind::Password,
+            CommandKind::CommandCode,
+            CommandKind::Secret,
+            CommandKind::Signed,
+            CommandKind::Authorize,
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            CommandKind::Pcr => "PolicyPCR",
+            CommandKind::Or => "PolicyOR",
+            CommandKind::AuthValue => "PolicyAuthValue",
+            CommandKind::Password => "PolicyPassword",
+            CommandKind::CommandCode => "PolicyCommandCode",
+            CommandKind::Secret => "PolicySecret",
+            CommandKind::Signed => "PolicySigned",
+            CommandKind::Authorize => "PolicyAuthorize",
+        }
+    }
+}
+
+impl Default for PolicyCalculatorApp {
+    fn default() -> Self {
+        Self {
+            calculator: PolicyCalculator::new(HashAlgorithm::SHA256),
+            command_kind: CommandKind::Pcr,
+            pcr_bank: HashAlgorithm::SHA256,
+            pcr_list: "0,2,4,7".to_string(),
+            or_branches: String::new(),
+            command_code: String::new(),
+            name_input: String::new(),
+            policy_ref_input: String::new(),
+            error: None,
+            copied: None,
+        }
+    }
+}
+
+impl PolicyCalculatorApp {
+    /// Show the tab; PolicyPCR reads the PCR values of `simulator`
+    pub fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame, simulator: &PcrSimulator) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.heading("TPM2 Policy Calculator");
+                ui.label("Compute the policyDigest of a trial session, e.g. to seal a secret to the replayed PCRs");
+                ui.add_space(10.0);
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Policy Hash:");
+                egui::ComboBox::from_id_source("policy_algorithm_select")
+                    .selected_text(self.calculator.algorithm.name())
+                    .show_ui(ui, |ui| {
+                        for name in HashAlgorithm::all_algorithms() {
+                            if let Ok(algorithm) = name.parse::<HashAlgorithm>() {
+                                ui.selectable_value(&mut self.calculator.algorithm, algorithm, name);
+                            }
+                        }
+                    });
+            });
+
+            ui.add_space(5.0);
+
+            ui.group(|ui| self.show_add_command(ui, simulator));
+
+            ui.add_space(5.0);
+
+            egui::ScrollArea::vertical().id_source("policy_steps").max_height(350.0).show(ui, |ui| self.show_steps(ui, simulator));
+        });
+    }
+
+    /// Command selection with the fields of the selected command
+    fn show_add_command(&mut self, ui: &mut egui::Ui, simulator: &PcrSimulator) {
+        ui.horizontal(|ui| {
+            ui.label("Command:");
+            egui::ComboBox::from_id_source("policy_command_select")
+                .selected_text(self.command_kind.name())
+                .show_ui(ui, |ui| {
+                    for kind in CommandKind::all() {
+                        ui.selectable_value(&mut self.command_kind, kind, kind.name());
+                    }
+                });
+        });
+
+        match self.command_kind {
+            CommandKind::Pcr => {
+                ui.horizontal(|ui| {
+                    ui.label("Bank:");
+                    egui::ComboBox::from_id_source("policy_pcr_bank_select")
+                        .selected_text(self.pcr_bank.name())
+                        .show_ui(ui, |ui| {
+                            for algorithm in simulator.active_algorithms() {
+                                let name = algorithm.name();
+                                ui.selectable_value(&mut self.pcr_bank, algorithm, name);
+                            }
+                        });
+                    ui.label("PCRs:");
+                    ui.text_edit_singleline(&mut self.pcr_list);
+                });
+                ui.weak("Uses the current PCR values of the Boot Replay simulator, e.g. '0,2,4-7'.");
+            },
+            CommandKind::Or => {
+                ui.label("Branch policy digests, one per line:");
+                ui.add_sized(
+                    [ui.available_width(), 60.0],
+                    egui::TextEdit::multiline(&mut self.or_branches).font(egui::TextStyle::Monospace)
+                );
+            },
+            CommandKind::CommandCode => {
+                ui.horizontal(|ui| {
+                    ui.label("Command Code (hex):");
+                    ui.add(egui::TextEdit::singleline(&mut self.command_code).hint_text("0000015E (Unseal)"));
+                });
+            },
+            CommandKind::Secret | CommandKind::Signed | CommandKind::Authorize => {
+                ui.horizontal(|ui| {
+                    ui.label(if self.command_kind == CommandKind::Secret { "Auth Object Name (hex):" } else { "Key Name (hex):" });
+                    ui.add(egui::TextEdit::singleline(&mut self.name_input).hint_text("000b... or 4000000b"));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Policy Ref (hex):");
+                    ui.text_edit_singleline(&mut self.policy_ref_input);
+                });
+            },
+            CommandKind::AuthValue | CommandKind::Password => {},
+        }
+
+        if ui.button("Add Command").clicked() {
+            match self.build_command(simulator) {
+                Ok(command) => {
+                    self.calculator.commands.push(command);
+                    self.error = None;
+                }
+                Err(err) => self.error = Some(err),
+            }
+        }
+
+        if let Some(ref error) = self.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+    }
+
+    fn build_command(&self, simulator: &PcrSimulator) -> Result<PolicyCommand, String> {
+        let hex_input = |text: &str| PcrSimulator::parse_hex(text).map_err(|e| e.to_string());
+
+        match self.command_kind {
+            CommandKind::Pcr => {
+                let selection = PcrSelection::parse(self.pcr_bank.clone(), &self.pcr_list).map_err(|e| e.to_string())?;
+                PolicyCommand::pcr(simulator, vec![selection]).map_err(|e| e.to_string())
+            },
+            CommandKind::Or => {
+                let branches = self.or_branches
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(hex_input)
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(PolicyCommand::Or(branches))
+            },
+            CommandKind::AuthValue => Ok(PolicyCommand::AuthValue),
+            CommandKind::Password => Ok(PolicyCommand::Password),
+            CommandKind::CommandCode => {
+                let text = self.command_code.trim().trim_start_matches("0x");
+                u32::from_str_radix(text, 16)
+                    .map(PolicyCommand::CommandCode)
+                    .map_err(|_| format!("Invalid command code '{}'", self.command_code.trim()))
+            },
+            CommandKind::Secret | CommandKind::Signed | CommandKind::Authorize => {
+                let name = hex_input(&self.name_input)?;
+                if name.is_empty() {
+                    return Err("Please enter a Name".to_string());
+                }
+                let policy_ref = hex_input(&self.policy_ref_input)?;
+                Ok(match self.command_kind {
+                    CommandKind::Secret => PolicyCommand::Secret { auth_name: name, policy_ref },
+                    CommandKind::Signed => PolicyCommand::Signed { key_name: name, policy_ref },
+                    _ => PolicyCommand::Authorize { key_name: name, policy_ref },
+                })
+            },
+        }
+    }
+
+    /// Every command with the policyDigest after it, and the final digest
+    fn show_steps(&mut self, ui: &mut egui::Ui, simulator: &PcrSimulator) {
+        let algorithm = self.calculator.algorithm.clone();
+        let steps = match self.calculator.steps() {
+            Ok(steps) => steps,
+            Err(err) => {
+                ui.colored_label(egui::Color32::RED, err.to_string());
+                Vec::new()
+            }
+        };
+
+        ui.label(format!("Initial policyDigest: {}", hex::encode(vec![0u8; algorithm.output_size_bytes()])));
+
+        let mut remove = None;
+        for (i, step) in steps.iter().enumerate() {
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.strong(format!("{}. {}", i + 1, step.command));
+                if ui.small_button("Remove").clicked() {
+                    remove = Some(i);
+                }
+            });
+            if let PolicyCommand::Pcr { ref selections, .. } = step.command {
+                ui.horizontal(|ui| {
+                    ui.weak("    pcrSelection:");
+                    ui.monospace(hex::encode(marshal_pcr_selection(selections)));
+                });
+                ui.horizontal(|ui| {
+                    ui.weak("    pcrDigest:");
+                    ui.monospace(hex::encode(step.command.pcr_digest(&algorithm).unwrap_or_default()));
+                });
+            }
+            ui.horizontal(|ui| {
+                ui.label("    policyDigest:");
+                ui.monospace(hex::encode(&step.policy_digest));
+            });
+        }
+        if let Some(i) = remove {
+            self.calculator.commands.remove(i);
+        }
+
+        ui.separator();
+        let final_digest = hex::encode(steps.last().map_or_else(
+            || vec![0u8; algorithm.output_size_bytes()],
+            |step| step.policy_digest.clone(),
+        ));
+        ui.horizontal(|ui| {
+            ui.strong("Policy Digest:");
+            ui.monospace(&final_digest);
+        });
+
+        ui.horizontal(|ui| {
+            let copy_label = if self.copied.as_ref() == Some(&final_digest) { "✓ Copied!" } else { "Copy" };
+            if ui.button(copy_label).clicked() {
+                ui.output_mut(|o| o.copied_text = final_digest.clone());
+                self.copied = Some(final_digest.clone());
+            }
+
+            if ui.button("Add as OR Branch").on_hover_text("Append this digest to the PolicyOR branches").clicked() {
+                if !self.or_branches.is_empty() && !self.or_branches.ends_with('\n') {
+                    self.or_branches.push('\n');
+                }
+                self.or_branches.push_str(&final_digest);
+                self.or_branches.push('\n');
+            }
+
+            if ui.button("Refresh PCR Values").on_hover_text("Re-read the PCR values of every PolicyPCR").clicked() {
+                self.refresh_pcr_values(simulator);
+            }
+
+            if ui.button("Clear").clicked() {
+                self.calculator.commands.clear();
+                self.error = None;
+            }
+        });
+    }
+
+    /// Rebuild every PolicyPCR from the current simulator values
+    fn refresh_pcr_values(&mut self, simulator: &PcrSimulator) {
+        self.error = None;
+        for command in &mut self.calculator.commands {
+            if let PolicyCommand::Pcr { ref selections, .. } = *command {
+                match PolicyCommand::pcr(simulator, selections.clone()) {
+                    Ok(refreshed) => *command = refreshed,
+                    Err(err) => self.error = Some(err.to_string()),
+                }
+            }
+        }
+    }
+}
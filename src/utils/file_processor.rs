@@ -1,12 +1,48 @@
 use std::path::PathBuf;
 use std::fs;
+use crate::error::{Error, Result};
+use crate::models::measurement_event::{EventSource, MeasurementEvent};
+use crate::models::pcr_simulator::{DigestPadding, HashAlgorithm, PcrSimulator, TaggedDigest};
+use crate::models::verification::GoldenValues;
+use crate::utils::ima::ImaMeasurementList;
+use crate::utils::measurement_table::MeasurementTable;
+use crate::utils::tcg_event_log::TcgEventLog;
+use crate::utils::tpm2_tools;
+
+/// Measurement parsed from a line of a text file
+#[derive(Debug, Clone)]
+pub struct ParsedMeasurement {
+    pub line_number: usize, // 1-based
+    pub value: String,
+}
+
+/// Problem found on a line of a text measurement file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineDiagnostic {
+    pub line_number: usize, // 1-based
+    pub text: String,
+    pub reason: String,
+}
 
 /// Measurement File Processor - For reading and parsing measurement files
+#[derive(Debug)]
 pub struct MeasurementFileProcessor {
     file_path: Option<PathBuf>,
     file_content: Vec<String>,
-    parsed_measurements: Vec<String>,
+    parsed_measurements: Vec<ParsedMeasurement>,
+    diagnostics: Vec<LineDiagnostic>,
+    measurement_table: Option<MeasurementTable>,
+    event_log: Option<TcgEventLog>,
+    ima_list: Option<ImaMeasurementList>,
+    expected_values: Option<GoldenValues>, // PCR values read from a TPM by tpm2-tools
     error: Option<String>,
+    padding: DigestPadding,
+}
+
+impl Default for MeasurementFileProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MeasurementFileProcessor {
@@ -15,30 +51,152 @@ impl MeasurementFileProcessor {
             file_path: None,
             file_content: Vec::new(),
             parsed_measurements: Vec::new(),
+            diagnostics: Vec::new(),
+            measurement_table: None,
+            event_log: None,
+            ima_list: None,
+            expected_values: None,
             error: None,
+            padding: DigestPadding::default(),
+        }
+    }
+
+    /// Padding policy for measurements shorter than the digest size of the bank
+    pub fn set_padding(&mut self, padding: DigestPadding) {
+        self.padding = padding;
+        // Measurement tables check digest sizes while parsing
+        if self.measurement_table.is_some() {
+            self.parse_measurement_table();
+        }
+    }
+
+    pub fn get_padding(&self) -> DigestPadding {
+        self.padding
+    }
+    
+    /// Load file - a text measurement file, a binary TCG event log, an IMA measurement list
+    /// or `tpm2_eventlog` / `tpm2_pcrread` output
+    pub fn load_file(&mut self, path_str: &str) -> Result<()> {
+        self.clear_parsed();
+        self.error = None;
+        
+        let result = self.read_file(path_str);
+        if result.is_err() {
+            // Don't leave a half-parsed file behind the error
+            self.clear_parsed();
         }
+        result
     }
     
-    /// Load file
-    pub fn load_file(&mut self, path_str: &str) -> Result<(), String> {
+    /// Forget the current file and everything parsed from it
+    fn clear_parsed(&mut self) {
+        self.file_path = None;
         self.file_content.clear();
         self.parsed_measurements.clear();
-        self.error = None;
+        self.diagnostics.clear();
+        self.measurement_table = None;
+        self.event_log = None;
+        self.ima_list = None;
+        self.expected_values = None;
+    }
+    
+    /// Read and parse a file, detecting its format
+    fn read_file(&mut self, path_str: &str) -> Result<()> {
+        let data = match fs::read(path_str) {
+            Ok(data) => data,
+            Err(err) => {
+                self.error = Some(format!("Error reading file: {}", err));
+                return Err(Error::Io(err));
+            }
+        };
+        self.file_path = Some(PathBuf::from(path_str));
+        
+        // Binary formats that may happen to be valid UTF-8
+        if TcgEventLog::detect_format(&data).is_some() {
+            return self.parse_event_log(&data);
+        }
+        if ImaMeasurementList::is_binary_list(&data) {
+            return self.set_ima_list(ImaMeasurementList::parse_binary(&data));
+        }
         
-        match fs::read_to_string(path_str) {
+        match String::from_utf8(data) {
             Ok(content) => {
                 // Store file content
-                self.file_path = Some(PathBuf::from(path_str));
                 self.file_content = content.lines().map(String::from).collect();
                 
+                // tpm2-tools YAML: an event log with the PCR values read at the time, or just the values
+                if tpm2_tools::is_eventlog_output(&content) {
+                    let result = tpm2_tools::parse_eventlog_output(&content).map(|(event_log, values, diagnostics)| {
+                        self.diagnostics = diagnostics;
+                        (Some(event_log), values)
+                    });
+                    return self.set_tpm2_output(result);
+                }
+                if tpm2_tools::is_pcrread_output(&content) {
+                    return self.set_tpm2_output(tpm2_tools::parse_pcrread_output(&content).map(|values| (None, values)));
+                }
+                
+                if ImaMeasurementList::is_ascii_list(&content) {
+                    return self.set_ima_list(ImaMeasurementList::parse_ascii(&content));
+                }
+                
+                // Lines with their own PCR, algorithm and event type
+                if MeasurementTable::is_table(&content) {
+                    self.parse_measurement_table();
+                    return Ok(());
+                }
+                
                 // Parse measurements
                 self.parse_measurements();
                 Ok(())
             },
+            Err(_) => {
+                let err = Error::Parse("Unrecognized binary file: not a TCG event log or IMA measurement list".to_string());
+                self.error = Some(err.to_string());
+                Err(err)
+            },
+        }
+    }
+    
+    /// Store parsed tpm2-tools output
+    fn set_tpm2_output(&mut self, result: Result<(Option<TcgEventLog>, GoldenValues)>) -> Result<()> {
+        match result {
+            Ok((event_log, expected_values)) => {
+                self.event_log = event_log;
+                self.expected_values = Some(expected_values);
+                Ok(())
+            },
+            Err(err) => {
+                self.error = Some(format!("Error parsing tpm2-tools output: {}", err));
+                Err(err)
+            }
+        }
+    }
+    
+    /// Store a parsed IMA measurement list
+    fn set_ima_list(&mut self, result: Result<ImaMeasurementList>) -> Result<()> {
+        match result {
+            Ok(ima_list) => {
+                self.ima_list = Some(ima_list);
+                Ok(())
+            },
+            Err(err) => {
+                self.error = Some(format!("Error parsing IMA measurement list: {}", err));
+                Err(err)
+            }
+        }
+    }
+    
+    /// Parse a binary TCG event log
+    fn parse_event_log(&mut self, data: &[u8]) -> Result<()> {
+        match TcgEventLog::parse(data) {
+            Ok(event_log) => {
+                self.event_log = Some(event_log);
+                Ok(())
+            },
             Err(err) => {
-                let error_msg = format!("Error reading file: {}", err);
-                self.error = Some(error_msg.clone());
-                Err(error_msg)
+                self.error = Some(format!("Error parsing event log: {}", err));
+                Err(err)
             }
         }
     }
@@ -46,47 +204,70 @@ impl MeasurementFileProcessor {
     /// Parse measurements from file
     fn parse_measurements(&mut self) {
         self.parsed_measurements.clear();
+        self.diagnostics.clear();
         
-        for line in &self.file_content {
-            let line = line.trim();
+        for (i, line) in self.file_content.iter().enumerate() {
+            let text = line.trim();
             
             // Skip empty lines and comments
-            if line.is_empty() || line.starts_with("#") {
+            if text.is_empty() || text.starts_with("#") {
                 continue;
             }
             
-            // Check if it's a valid hex string
-            if line.chars().all(|c| c.is_digit(16) || c.is_whitespace()) {
-                // Remove spaces
-                let hex_only = line.replace(" ", "");
-                if !hex_only.is_empty() {
-                    if let Ok(validated) = self.validate_hex(&hex_only) {
-                        self.parsed_measurements.push(validated);
-                    }
-                }
+            // Remove whitespace; the length is checked against the bank when applied
+            let hex_only: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+            match Self::check_line(&hex_only) {
+                Ok(()) => self.parsed_measurements.push(ParsedMeasurement {
+                    line_number: i + 1,
+                    value: hex_only.to_lowercase(),
+                }),
+                Err(reason) => self.diagnostics.push(LineDiagnostic {
+                    line_number: i + 1,
+                    text: line.clone(),
+                    reason,
+                }),
             }
         }
     }
+
+    fn parse_measurement_table(&mut self) {
+        let (table, diagnostics) = MeasurementTable::parse(&self.file_content.join("\n"), self.padding);
+        self.measurement_table = Some(table);
+        self.diagnostics = diagnostics;
+    }
+
+    /// Reason why a line is not a hex measurement, if it is not
+    fn check_line(hex_only: &str) -> std::result::Result<(), String> {
+        if hex_only.starts_with("0x") || hex_only.starts_with("0X") {
+            return Err("Unexpected '0x' prefix; measurements are plain hex".to_string());
+        }
+        if let Some((position, c)) = hex_only.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+            return Err(format!("Invalid hex character '{}' at position {}", c, position + 1));
+        }
+        if !hex_only.len().is_multiple_of(2) {
+            return Err(format!("Odd number of hex digits ({})", hex_only.len()));
+        }
+        Ok(())
+    }
     
-    /// Validate hex measurement
-    pub fn validate_hex(&self, hex: &str) -> Result<String, &'static str> {
+    /// Validate a hex measurement against the digest size of `algorithm`, returning lowercase hex
+    pub fn validate_hex(&self, hex: &str, algorithm: &HashAlgorithm) -> Result<String> {
         let hex = hex.replace(" ", "");
-        if hex.len() > 64 {
-            return Err("Measurement must be at most 64 hex characters");
-        }
         
         // Validate hex characters
-        if !hex.chars().all(|c| c.is_digit(16)) {
-            return Err("Invalid hex characters");
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(Error::InvalidHex("Invalid hex characters".to_string()));
+        }
+        if !hex.len().is_multiple_of(2) {
+            return Err(Error::InvalidHex(format!("Odd number of hex digits ({})", hex.len())));
         }
         
-        // Pad to 64 characters
-        let padded = format!("{:0>64}", hex);
-        Ok(padded)
+        // Check the digest size of the bank, padding only if the policy allows it
+        let digest = algorithm.check_digest(PcrSimulator::parse_hex(&hex)?, self.padding)?;
+        Ok(hex::encode(digest))
     }
     
     /// Get file path
-    #[allow(dead_code)]
     pub fn get_file_path(&self) -> Option<String> {
         self.file_path.as_ref().map(|p| p.to_string_lossy().to_string())
     }
@@ -97,12 +278,191 @@ impl MeasurementFileProcessor {
     }
     
     /// Get parsed measurements
-    pub fn get_parsed_measurements(&self) -> &[String] {
+    pub fn get_measurement_table(&self) -> Option<&MeasurementTable> {
+        self.measurement_table.as_ref()
+    }
+
+    /// Lines of a text measurement file that could not be parsed
+    pub fn get_diagnostics(&self) -> &[LineDiagnostic] {
+        &self.diagnostics
+    }
+
+    /// Parsed measurements whose size does not fit `algorithm` under the padding policy
+    pub fn size_diagnostics(&self, algorithm: &HashAlgorithm) -> Vec<LineDiagnostic> {
+        self.parsed_measurements
+            .iter()
+            .filter_map(|measurement| {
+                let err = self.validate_hex(&measurement.value, algorithm).err()?;
+                Some(LineDiagnostic {
+                    line_number: measurement.line_number,
+                    text: self.file_content.get(measurement.line_number - 1).cloned().unwrap_or_default(),
+                    reason: err.to_string(),
+                })
+            })
+            .collect()
+    }
+    
+    pub fn get_parsed_measurements(&self) -> &[ParsedMeasurement] {
         &self.parsed_measurements
     }
     
+    /// Get parsed binary event log
+    pub fn get_event_log(&self) -> Option<&TcgEventLog> {
+        self.event_log.as_ref()
+    }
+    
+    /// Expected PCR values from `tpm2_pcrread` output or the `pcrs` section of `tpm2_eventlog` output
+    pub fn get_expected_values(&self) -> Option<&GoldenValues> {
+        self.expected_values.as_ref()
+    }
+    
+    /// Get parsed IMA measurement list
+    pub fn get_ima_list(&self) -> Option<&ImaMeasurementList> {
+        self.ima_list.as_ref()
+    }
+    
+    /// Build simulator events from the parsed IMA measurement list, with template hashes
+    /// recomputed for every given bank
+    pub fn ima_events(&self, algorithms: &[HashAlgorithm]) -> Vec<MeasurementEvent> {
+        let path = self.get_file_path().unwrap_or_default();
+        match self.ima_list {
+            Some(ref ima_list) => ima_list.to_measurement_events(algorithms, &path),
+            None => Vec::new(),
+        }
+    }
+    
+    /// Build a simulator event from a parsed measurement, as a digest of one bank
+    pub fn measurement_event(&self, measurement: &ParsedMeasurement, pcr_index: usize, algorithm: &HashAlgorithm) -> Result<MeasurementEvent> {
+        let digest = self.validate_hex(&measurement.value, algorithm)
+            .and_then(|hex| PcrSimulator::parse_hex(&hex))
+            .map_err(|err| Error::Parse(format!("Line {}: {}", measurement.line_number, err)))?;
+        let source = EventSource::File {
+            path: self.get_file_path().unwrap_or_default(),
+            line: measurement.line_number,
+        };
+        
+        Ok(MeasurementEvent::new(
+            pcr_index,
+            vec![TaggedDigest::new(algorithm.clone(), digest)],
+            format!("File Input: line {}", measurement.line_number),
+        ).with_source(source))
+    }
+    
+    /// Build simulator events from the parsed measurement table, each for its own PCR
+    pub fn measurement_table_events(&self) -> Vec<MeasurementEvent> {
+        let path = self.get_file_path().unwrap_or_default();
+        match self.measurement_table {
+            Some(ref table) => table.to_measurement_events(&path),
+            None => Vec::new(),
+        }
+    }
+    
+    /// Build simulator events from the parsed binary event log
+    pub fn event_log_events(&self) -> Vec<MeasurementEvent> {
+        let path = self.get_file_path().unwrap_or_default();
+        match self.event_log {
+            Some(ref event_log) => event_log.events
+                .iter()
+                .enumerate()
+                .map(|(index, event)| event.to_measurement_event(event_log.uintn_size(), EventSource::EventLog {
+                    path: path.clone(),
+                    index,
+                }))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+    
     /// Get error message
     pub fn get_error(&self) -> Option<&str> {
         self.error.as_deref()
     }
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Load `content` from a temporary file
+    fn load(name: &str, content: &str) -> (MeasurementFileProcessor, Result<()>) {
+        let path = std::env::temp_dir().join(format!("eggs-file-{}-{}.txt", name, std::process::id()));
+        fs::write(&path, content).unwrap();
+        let mut processor = MeasurementFileProcessor::new();
+        let result = processor.load_file(&path.to_string_lossy());
+        fs::remove_file(path).unwrap();
+        (processor, result)
+    }
+
+    #[test]
+    fn reports_invalid_lines_of_plain_files() {
+        let sha256 = "AB".repeat(32);
+        let content = format!(
+            "# measurements\n{}\n\n0x{}\n{}zz\n{}0\n{} {}\n",
+            sha256, sha256, &sha256[..10], sha256, &sha256[..32], &sha256[32..]
+        );
+        let (processor, result) = load("diagnostics", &content);
+        assert!(result.is_ok());
+
+        let measurements: Vec<(usize, &str)> = processor
+            .get_parsed_measurements()
+            .iter()
+            .map(|measurement| (measurement.line_number, measurement.value.as_str()))
+            .collect();
+        let lowercase = sha256.to_lowercase();
+        assert_eq!(measurements, [(2, lowercase.as_str()), (7, lowercase.as_str())]);
+
+        let diagnostics: Vec<(usize, &str)> = processor
+            .get_diagnostics()
+            .iter()
+            .map(|diagnostic| (diagnostic.line_number, diagnostic.reason.as_str()))
+            .collect();
+        assert_eq!(diagnostics, [
+            (4, "Unexpected '0x' prefix; measurements are plain hex"),
+            (5, "Invalid hex character 'z' at position 11"),
+            (6, "Odd number of hex digits (65)"),
+        ]);
+        assert_eq!(processor.get_diagnostics()[0].text, format!("0x{}", sha256));
+    }
+
+    #[test]
+    fn reports_measurements_of_the_wrong_size() {
+        let content = format!("{}\n{}\n", "11".repeat(32), "22".repeat(20));
+        let (mut processor, _) = load("sizes", &content);
+        assert!(processor.get_diagnostics().is_empty());
+
+        let diagnostics = processor.size_diagnostics(&HashAlgorithm::SHA256);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!((diagnostics[0].line_number, diagnostics[0].text.as_str()), (2, content.lines().nth(1).unwrap()));
+        assert!(processor.size_diagnostics(&HashAlgorithm::SHA1).iter().map(|diagnostic| diagnostic.line_number).eq([1]));
+
+        processor.set_padding(DigestPadding::ZeroPad);
+        assert!(processor.size_diagnostics(&HashAlgorithm::SHA256).is_empty());
+        let event = processor.measurement_event(&processor.get_parsed_measurements()[1], 8, &HashAlgorithm::SHA256).unwrap();
+        assert_eq!(event.digests[0].digest, [vec![0; 12], vec![0x22; 20]].concat());
+    }
+
+    #[test]
+    fn reports_invalid_table_lines() {
+        let digest = "33".repeat(32);
+        let content = format!("pcr algorithm digest\n0 sha256 {}\n24 sha256 {}\n4 sha256 {}\n", digest, digest, &digest[..40]);
+        let (processor, result) = load("table", &content);
+        assert!(result.is_ok());
+        assert_eq!(processor.get_measurement_table().unwrap().lines.len(), 1);
+        let lines: Vec<usize> = processor.get_diagnostics().iter().map(|diagnostic| diagnostic.line_number).collect();
+        assert_eq!(lines, [3, 4]);
+    }
+
+    #[test]
+    fn failed_load_clears_the_previous_file() {
+        let (mut processor, _) = load("previous", &format!("{}\n", "44".repeat(32)));
+        assert_eq!(processor.get_parsed_measurements().len(), 1);
+
+        let path = std::env::temp_dir().join(format!("eggs-file-binary-{}", std::process::id()));
+        fs::write(&path, [0xff, 0xfe, 0xfd, 0xfc]).unwrap();
+        assert!(processor.load_file(&path.to_string_lossy()).is_err());
+        fs::remove_file(path).unwrap();
+
+        assert!(processor.get_parsed_measurements().is_empty());
+        assert!(processor.get_file_path().is_none());
+        assert!(processor.get_error().is_some());
+    }
+}
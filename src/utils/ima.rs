@@ -0,0 +1,440 @@
+use crate::error::{Error, Result};
+use crate::models::measurement_event::{EventSource, MeasurementEvent};
+use crate::models::pcr_simulator::{HashAlgorithm, PcrSimulator, TaggedDigest};
+
+/// PCR that IMA extends by default
+pub const IMA_PCR: usize = 10;
+
+/// Size of the SHA1 template hash recorded in the measurement list
+const TEMPLATE_HASH_SIZE: usize = 20;
+
+/// Size of the file name buffer hashed for the legacy 'ima' template (IMA_EVENT_NAME_LEN_MAX + 1)
+const IMA_NAME_BUFFER_SIZE: usize = 256;
+
+/// Result of parsing a single line or record, before the position is added to the error
+type EntryResult<T> = std::result::Result<T, String>;
+
+/// Single entry of an IMA measurement list
+#[derive(Debug, Clone)]
+pub struct ImaEntry {
+    pub pcr_index: u32,
+    pub template_hash: Vec<u8>, // SHA1 template hash as recorded in the list
+    pub template_name: String,
+    pub template_data: Vec<u8>, // Template data as in binary_runtime_measurements
+    pub file_digest_algorithm: String,
+    pub file_digest: Vec<u8>,
+    pub file_name: String,
+    pub signature: Vec<u8>,
+    pub position: usize, // 1-based line (ascii) or 0-based record index (binary)
+}
+
+impl ImaEntry {
+    /// Whether the entry records a measurement violation (all-zero template hash)
+    pub fn is_violation(&self) -> bool {
+        self.template_hash.iter().all(|&b| b == 0)
+    }
+
+    /// Recompute the template hash from the template data with the given algorithm
+    pub fn compute_template_hash(&self, algorithm: &HashAlgorithm) -> Vec<u8> {
+        if self.template_name == "ima" {
+            // The legacy template hashes the digest and a zero-padded file name buffer
+            let mut data = self.file_digest.clone();
+            let mut name = self.file_name.as_bytes().to_vec();
+            name.resize(IMA_NAME_BUFFER_SIZE, 0);
+            data.extend_from_slice(&name);
+            PcrSimulator::hash(algorithm, &data)
+        } else {
+            PcrSimulator::hash(algorithm, &self.template_data)
+        }
+    }
+
+    /// Check that the recorded template hash matches the template data
+    pub fn template_hash_matches(&self) -> bool {
+        self.is_violation() || self.compute_template_hash(&HashAlgorithm::SHA1) == self.template_hash
+    }
+
+    /// Digest extended into the given bank (violations extend all-ones)
+    pub fn extend_digest(&self, algorithm: &HashAlgorithm) -> Vec<u8> {
+        if self.is_violation() {
+            vec![0xFF; algorithm.output_size_bytes()]
+        } else {
+            self.compute_template_hash(algorithm)
+        }
+    }
+
+    /// Convert to a simulator event with a recomputed digest for every given bank
+    pub fn to_measurement_event(&self, algorithms: &[HashAlgorithm], source: EventSource) -> MeasurementEvent {
+        let digests = algorithms
+            .iter()
+            .map(|algorithm| TaggedDigest::new(algorithm.clone(), self.extend_digest(algorithm)))
+            .collect();
+        let description = if self.is_violation() {
+            format!("IMA {} (violation): {}", self.template_name, self.file_name)
+        } else {
+            format!("IMA {}: {}", self.template_name, self.file_name)
+        };
+
+        MeasurementEvent::new(self.pcr_index as usize, digests, description)
+            .with_event_data(self.template_data.clone())
+            .with_source(source)
+    }
+}
+
+/// Parsed IMA runtime measurement list
+#[derive(Debug, Clone)]
+pub struct ImaMeasurementList {
+    pub entries: Vec<ImaEntry>,
+    pub binary: bool,
+}
+
+impl ImaMeasurementList {
+    /// Check whether text looks like ascii_runtime_measurements
+    pub fn is_ascii_list(text: &str) -> bool {
+        let Some(line) = text.lines().map(str::trim).find(|line| !line.is_empty()) else {
+            return false;
+        };
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        tokens.len() >= 4
+            && tokens[0].parse::<u32>().is_ok()
+            && tokens[1].len() == TEMPLATE_HASH_SIZE * 2
+            && tokens[1].chars().all(|c| c.is_ascii_hexdigit())
+            && tokens[2].starts_with("ima")
+    }
+
+    /// Check whether data looks like binary_runtime_measurements
+    pub fn is_binary_list(data: &[u8]) -> bool {
+        // pcr(4) + template hash(20) + name length(4) precede the template name
+        let name_offset = 4 + TEMPLATE_HASH_SIZE + 4;
+        if data.len() < name_offset + 3 {
+            return false;
+        }
+        let pcr = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+        let name_len = u32::from_le_bytes([data[24], data[25], data[26], data[27]]) as usize;
+        pcr < 24
+            && (3..=16).contains(&name_len)
+            && data.len() >= name_offset + name_len
+            && data[name_offset..name_offset + 3] == *b"ima"
+    }
+
+    /// Parse the ascii_runtime_measurements format
+    pub fn parse_ascii(text: &str) -> Result<Self> {
+        let mut entries = Vec::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            // Keep trailing spaces: an empty last field leaves one behind
+            let line = line.trim_start().trim_end_matches('\r');
+            if line.trim_end().is_empty() {
+                continue;
+            }
+            let entry = Self::parse_ascii_line(line, line_number)
+                .map_err(|err| Error::Parse(format!("Line {}: {}", line_number, err)))?;
+            entries.push(entry);
+        }
+
+        Ok(Self {
+            entries,
+            binary: false,
+        })
+    }
+
+    /// Parse one line of the ascii format: `pcr template-hash template-name fields...`
+    fn parse_ascii_line(line: &str, line_number: usize) -> EntryResult<ImaEntry> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.len() < 4 {
+            return Err("expected 'pcr template-hash template-name fields...'".to_string());
+        }
+
+        let pcr_index = tokens[0]
+            .parse::<u32>()
+            .map_err(|_| format!("invalid PCR index '{}'", tokens[0]))?;
+        let template_hash = decode_hex(tokens[1], "template hash")?;
+        if template_hash.len() != TEMPLATE_HASH_SIZE {
+            return Err(format!("template hash must be {} bytes", TEMPLATE_HASH_SIZE));
+        }
+        let template_name = tokens[2].to_string();
+        let fields = &tokens[3..];
+
+        let (file_digest_algorithm, file_digest, file_name, signature) = match template_name.as_str() {
+            "ima" => {
+                let digest = decode_hex(fields[0], "file digest")?;
+                ("sha1".to_string(), digest, fields[1..].join(" "), Vec::new())
+            },
+            "ima-ng" => {
+                let (algorithm, digest) = parse_ng_digest(fields[0])?;
+                (algorithm, digest, fields[1..].join(" "), Vec::new())
+            },
+            "ima-sig" | "ima-buf" => {
+                // Fields are separated by single spaces: digest, file name (which may hold
+                // spaces) and the hex signature or buffer, which is last and may be empty.
+                // An empty last field may also have lost its separator, leaving a file name
+                // whose last word is not hex.
+                let fields_text = line.splitn(4, ' ').nth(3).unwrap_or_default();
+                let (digest_field, rest) = fields_text
+                    .split_once(' ')
+                    .ok_or_else(|| "expected 'digest file-name signature'".to_string())?;
+                let (file_name, trailing) = match rest.rsplit_once(' ') {
+                    Some((file_name, trailing)) if hex::decode(trailing).is_ok() => (file_name, trailing),
+                    _ => (rest, ""),
+                };
+                let (algorithm, digest) = parse_ng_digest(digest_field)?;
+                let what = if template_name == "ima-sig" { "signature" } else { "buffer" };
+                (algorithm, digest, file_name.to_string(), decode_hex(trailing, what)?)
+            },
+            other => return Err(format!("unsupported IMA template '{}'", other)),
+        };
+
+        let mut entry = ImaEntry {
+            pcr_index,
+            template_hash,
+            template_name,
+            template_data: Vec::new(),
+            file_digest_algorithm,
+            file_digest,
+            file_name,
+            signature,
+            position: line_number,
+        };
+        entry.template_data = build_template_data(&entry);
+        Ok(entry)
+    }
+
+    /// Parse the binary_runtime_measurements format
+    pub fn parse_binary(data: &[u8]) -> Result<Self> {
+        let mut entries = Vec::new();
+        let mut pos = 0;
+
+        while pos < data.len() {
+            let index = entries.len();
+            let entry = Self::parse_binary_entry(data, &mut pos, index)
+                .map_err(|err| Error::Parse(format!("Record {}: {}", index, err)))?;
+            entries.push(entry);
+        }
+
+        Ok(Self {
+            entries,
+            binary: true,
+        })
+    }
+
+    /// Parse one record of the binary format
+    fn parse_binary_entry(data: &[u8], pos: &mut usize, index: usize) -> EntryResult<ImaEntry> {
+        let pcr_index = read_u32(data, pos)?;
+        let template_hash = read_bytes(data, pos, TEMPLATE_HASH_SIZE)?.to_vec();
+        let name_len = read_u32(data, pos)? as usize;
+        let template_name = String::from_utf8_lossy(read_bytes(data, pos, name_len)?).to_string();
+
+        let mut entry = ImaEntry {
+            pcr_index,
+            template_hash,
+            template_name,
+            template_data: Vec::new(),
+            file_digest_algorithm: String::new(),
+            file_digest: Vec::new(),
+            file_name: String::new(),
+            signature: Vec::new(),
+            position: index,
+        };
+
+        if entry.template_name == "ima" {
+            // The legacy template has no data length: digest, then length-prefixed name
+            let start = *pos;
+            entry.file_digest_algorithm = "sha1".to_string();
+            entry.file_digest = read_bytes(data, pos, TEMPLATE_HASH_SIZE)?.to_vec();
+            let file_name_len = read_u32(data, pos)? as usize;
+            entry.file_name = String::from_utf8_lossy(read_bytes(data, pos, file_name_len)?).to_string();
+            entry.template_data = data[start..*pos].to_vec();
+            return Ok(entry);
+        }
+
+        let data_len = read_u32(data, pos)? as usize;
+        entry.template_data = read_bytes(data, pos, data_len)?.to_vec();
+
+        // Every field of the template data is length-prefixed
+        let mut fields = Vec::new();
+        let mut field_pos = 0;
+        while field_pos < entry.template_data.len() {
+            let len = read_u32(&entry.template_data, &mut field_pos)? as usize;
+            fields.push(read_bytes(&entry.template_data, &mut field_pos, len)?.to_vec());
+        }
+
+        if let Some(digest_field) = fields.first() {
+            match digest_field.windows(2).position(|w| w == b":\0") {
+                Some(sep) => {
+                    entry.file_digest_algorithm = String::from_utf8_lossy(&digest_field[..sep]).to_string();
+                    entry.file_digest = digest_field[sep + 2..].to_vec();
+                },
+                None => {
+                    entry.file_digest_algorithm = "sha1".to_string();
+                    entry.file_digest = digest_field.clone();
+                },
+            }
+        }
+        if let Some(name_field) = fields.get(1) {
+            let name = name_field.strip_suffix(&[0]).unwrap_or(name_field);
+            entry.file_name = String::from_utf8_lossy(name).to_string();
+        }
+        if let Some(signature) = fields.get(2) {
+            entry.signature = signature.clone();
+        }
+
+        Ok(entry)
+    }
+
+    /// Get the entries whose template hash does not match their template data
+    pub fn mismatched_entries(&self) -> Vec<&ImaEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| !entry.template_hash_matches())
+            .collect()
+    }
+
+    /// Convert to simulator events with recomputed digests for every given bank
+    pub fn to_measurement_events(&self, algorithms: &[HashAlgorithm], path: &str) -> Vec<MeasurementEvent> {
+        self.entries
+            .iter()
+            .map(|entry| {
+                let source = if self.binary {
+                    EventSource::EventLog { path: path.to_string(), index: entry.position }
+                } else {
+                    EventSource::File { path: path.to_string(), line: entry.position }
+                };
+                entry.to_measurement_event(algorithms, source)
+            })
+            .collect()
+    }
+}
+
+/// Build the binary template data of an entry parsed from the ascii format
+fn build_template_data(entry: &ImaEntry) -> Vec<u8> {
+    if entry.template_name == "ima" {
+        let mut data = entry.file_digest.clone();
+        data.extend_from_slice(&(entry.file_name.len() as u32).to_le_bytes());
+        data.extend_from_slice(entry.file_name.as_bytes());
+        return data;
+    }
+
+    // d-ng: "<algorithm>:\0" followed by the digest
+    let mut digest_field = format!("{}:", entry.file_digest_algorithm).into_bytes();
+    digest_field.push(0);
+    digest_field.extend_from_slice(&entry.file_digest);
+
+    // n-ng: NUL-terminated file name
+    let mut name_field = entry.file_name.as_bytes().to_vec();
+    name_field.push(0);
+
+    let mut fields = vec![digest_field, name_field];
+    if entry.template_name != "ima-ng" {
+        // sig or buf
+        fields.push(entry.signature.clone());
+    }
+
+    let mut data = Vec::new();
+    for field in fields {
+        data.extend_from_slice(&(field.len() as u32).to_le_bytes());
+        data.extend_from_slice(&field);
+    }
+    data
+}
+
+/// Parse an `algorithm:hex` file digest
+fn parse_ng_digest(field: &str) -> EntryResult<(String, Vec<u8>)> {
+    let (algorithm, digest) = field
+        .split_once(':')
+        .ok_or_else(|| format!("expected 'algorithm:digest', got '{}'", field))?;
+    Ok((algorithm.to_string(), decode_hex(digest, "file digest")?))
+}
+
+fn decode_hex(text: &str, what: &str) -> EntryResult<Vec<u8>> {
+    hex::decode(text).map_err(|e| format!("invalid {}: {}", what, e))
+}
+
+fn read_bytes<'a>(data: &'a [u8], pos: &mut usize, len: usize) -> EntryResult<&'a [u8]> {
+    if data.len() - *pos < len {
+        return Err(format!("unexpected end of data at offset {}", *pos));
+    }
+    let bytes = &data[*pos..*pos + len];
+    *pos += len;
+    Ok(bytes)
+}
+
+fn read_u32(data: &[u8], pos: &mut usize) -> EntryResult<u32> {
+    let bytes = read_bytes(data, pos, 4)?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // One line per template; the template hashes are SHA1 of the template data
+    const IMA: &str = "10 fe86666834e28c0a8c11668e6814f1ba06539b5f ima 5c73b0c6f476ded38de389f894770f06f4d02b2f boot_aggregate";
+    const IMA_NG: &str = "10 3e1ac97aa9bd05d1e52f9619f3b3d4477e06147f ima-ng \
+        sha256:82c0aad1330a0c428ee3f9b7ff0d5ba7e5238c550c58c17aa4ed27382902e002 /usr/bin/bash";
+    const IMA_SIG: &str = "10 8acc39806a7fef948a7b8aeb41ad4eb04b377a68 ima-sig \
+        sha256:33ad1f5af51230990fb70d9aa54be3596c0e72744f715cbfccee3ee25a47d3ca /usr/local/bin/my script 030204aabbccdd0002";
+    const IMA_BUF: &str = "10 c52c0b2f19fd68f247568070b7c83b8b7ab1db13 ima-buf \
+        sha256:85012513b06e240cd0011b60d6cfe3e4be774c0d9a66c2a527e4a96b786b10fd .builtin_trusted_keys 6b65792064617461";
+    const VIOLATION: &str = "10 0000000000000000000000000000000000000000 ima-ng \
+        sha256:0000000000000000000000000000000000000000000000000000000000000000 /var/log/audit.log";
+
+    fn parse_line(line: &str) -> ImaEntry {
+        let list = ImaMeasurementList::parse_ascii(line).unwrap();
+        assert_eq!(list.entries.len(), 1);
+        list.entries.into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn parses_every_template() {
+        let ima = parse_line(IMA);
+        assert_eq!((ima.file_digest_algorithm.as_str(), ima.file_name.as_str()), ("sha1", "boot_aggregate"));
+
+        let ima_ng = parse_line(IMA_NG);
+        assert_eq!((ima_ng.file_digest_algorithm.as_str(), ima_ng.file_name.as_str()), ("sha256", "/usr/bin/bash"));
+
+        let ima_sig = parse_line(IMA_SIG);
+        assert_eq!(ima_sig.file_name, "/usr/local/bin/my script");
+        assert_eq!(ima_sig.signature, [0x03, 0x02, 0x04, 0xaa, 0xbb, 0xcc, 0xdd, 0x00, 0x02]);
+
+        let ima_buf = parse_line(IMA_BUF);
+        assert_eq!(ima_buf.file_name, ".builtin_trusted_keys");
+        assert_eq!(ima_buf.signature, b"key data");
+
+        for entry in [ima, ima_ng, ima_sig, ima_buf] {
+            assert_eq!(entry.pcr_index, IMA_PCR as u32);
+            assert!(!entry.is_violation());
+            assert!(entry.template_hash_matches(), "{}", entry.template_name);
+        }
+    }
+
+    #[test]
+    fn empty_last_field_with_or_without_separator() {
+        let line = "10 68de6bd288a7db126069fcbdf0cdeed6b58149fd ima-sig \
+            sha256:33ad1f5af51230990fb70d9aa54be3596c0e72744f715cbfccee3ee25a47d3ca /usr/local/bin/my script";
+        for line in [line.to_string(), format!("{} ", line)] {
+            let entry = parse_line(&line);
+            assert_eq!(entry.file_name, "/usr/local/bin/my script");
+            assert!(entry.signature.is_empty());
+            assert!(entry.template_hash_matches());
+        }
+
+        let entry = parse_line("10 a401f5110ba7e3f17c108f6a2c2bfafed449644c ima-buf \
+            sha256:85012513b06e240cd0011b60d6cfe3e4be774c0d9a66c2a527e4a96b786b10fd kexec-cmdline");
+        assert_eq!(entry.file_name, "kexec-cmdline");
+        assert!(entry.template_hash_matches());
+    }
+
+    #[test]
+    fn violation_extends_all_ones() {
+        let entry = parse_line(VIOLATION);
+        assert!(entry.is_violation());
+        assert!(entry.template_hash_matches());
+        assert_eq!(entry.extend_digest(&HashAlgorithm::SHA256), vec![0xFF; 32]);
+
+        let list = ImaMeasurementList::parse_ascii(&[IMA_NG, VIOLATION].join("\n")).unwrap();
+        assert!(list.mismatched_entries().is_empty());
+        let events = list.to_measurement_events(&[HashAlgorithm::SHA1], "ascii_runtime_measurements");
+        assert_eq!(events[1].description, "IMA ima-ng (violation): /var/log/audit.log");
+        assert_eq!(events[1].digests[0].digest, vec![0xFF; 20]);
+    }
+}
@@ -0,0 +1,247 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::error::{Error, Result};
+use crate::models::measurement_event::{EventSource, MeasurementEvent};
+use crate::models::pcr_simulator::{DigestPadding, HashAlgorithm, PcrSimulator, PCR_COUNT};
+use crate::models::verification::{GoldenValue, GoldenValues, VerificationReport};
+use crate::utils::tcg_event_log::TcgEventLog;
+
+/// Binary event log exposed by the Linux kernel
+pub const DEFAULT_EVENT_LOG_PATH: &str = "/sys/kernel/security/tpm0/binary_bios_measurements";
+/// TPM device directory holding one `pcr-<alg>/<n>` file per PCR
+pub const DEFAULT_PCR_DIR: &str = "/sys/class/tpm/tpm0";
+
+/// Local TPM as exposed by the Linux kernel in securityfs and sysfs
+///
+/// The paths can point at a copy of the files, e.g. a fake sysfs tree for testing or
+/// files collected from another machine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinuxTpm {
+    pub event_log_path: PathBuf,
+    pub pcr_dir: PathBuf,
+}
+
+impl Default for LinuxTpm {
+    fn default() -> Self {
+        Self {
+            event_log_path: PathBuf::from(DEFAULT_EVENT_LOG_PATH),
+            pcr_dir: PathBuf::from(DEFAULT_PCR_DIR),
+        }
+    }
+}
+
+/// Replay of the live event log compared with the live PCR values
+#[derive(Debug, Clone)]
+pub struct MachineVerification {
+    pub simulator: PcrSimulator,   // Replayed banks that the TPM also exposes
+    pub live_values: GoldenValues, // Every PCR of every bank read from the TPM
+    pub report: VerificationReport, // PCRs the event log extends
+}
+
+impl LinuxTpm {
+    pub fn new(event_log_path: impl Into<PathBuf>, pcr_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            event_log_path: event_log_path.into(),
+            pcr_dir: pcr_dir.into(),
+        }
+    }
+
+    /// Read and parse the binary event log
+    pub fn read_event_log(&self) -> Result<TcgEventLog> {
+        TcgEventLog::parse(&read_file(&self.event_log_path)?)
+    }
+
+    /// Events of the binary event log as simulator events
+    pub fn read_events(&self) -> Result<Vec<MeasurementEvent>> {
+        Ok(self.measurement_events(&self.read_event_log()?))
+    }
+
+    /// Banks with a `pcr-<alg>` directory, in TCG algorithm ID order
+    pub fn pcr_banks(&self) -> Result<Vec<HashAlgorithm>> {
+        Ok(self.bank_dirs()?.into_iter().map(|(algorithm, _)| algorithm).collect())
+    }
+
+    /// Read one PCR of one bank
+    pub fn read_pcr(&self, algorithm: &HashAlgorithm, pcr_index: usize) -> Result<Vec<u8>> {
+        if pcr_index >= PCR_COUNT {
+            return Err(Error::InvalidPcrIndex(pcr_index));
+        }
+
+        let bank_dir = self.bank_dirs()?
+            .into_iter()
+            .find(|(bank, _)| bank == algorithm)
+            .map(|(_, dir)| dir)
+            .ok_or_else(|| Error::InactiveBank(algorithm.clone()))?;
+        read_pcr_file(algorithm, &bank_dir, pcr_index)
+    }
+
+    /// Read every PCR of every bank
+    pub fn read_pcr_values(&self) -> Result<GoldenValues> {
+        let mut values = Vec::new();
+        for (algorithm, bank_dir) in self.bank_dirs()? {
+            for pcr_index in 0..PCR_COUNT {
+                values.push(GoldenValue {
+                    value: read_pcr_file(&algorithm, &bank_dir, pcr_index)?,
+                    algorithm: algorithm.clone(),
+                    pcr_index,
+                });
+            }
+        }
+        Ok(GoldenValues::new(values))
+    }
+
+
+    /// Replay the event log in every bank the TPM exposes and compare the PCRs it extends
+    /// with the live values
+    pub fn verify(&self) -> Result<MachineVerification> {
+        let event_log = self.read_event_log()?;
+        let live_values = self.read_pcr_values()?;
+        if live_values.is_empty() {
+            return Err(Error::Parse(format!("No PCR banks found in {}", self.pcr_dir.display())));
+        }
+
+        let banks: Vec<HashAlgorithm> = event_log
+            .algorithms()
+            .into_iter()
+            .filter(|algorithm| live_values.values.iter().any(|golden| golden.algorithm == *algorithm))
+            .collect();
+        if banks.is_empty() {
+            return Err(Error::Parse("The event log has no digests for any bank the TPM exposes".to_string()));
+        }
+
+        let mut simulator = PcrSimulator::with_banks(banks);
+        simulator.replay_events(self.measurement_events(&event_log), None)?;
+
+        // PCRs extended after boot (e.g. by IMA) cannot be reproduced from the firmware log
+        let expected = GoldenValues::new(live_values.values
+            .iter()
+            .filter(|golden| simulator.bank(&golden.algorithm).is_some())
+            .filter(|golden| simulator.extend_count(golden.pcr_index, &golden.algorithm) > 0)
+            .cloned()
+            .collect());
+        let report = simulator.verify(&expected);
+
+        Ok(MachineVerification { simulator, live_values, report })
+    }
+
+    fn measurement_events(&self, event_log: &TcgEventLog) -> Vec<MeasurementEvent> {
+        let path = self.event_log_path.to_string_lossy().to_string();
+        event_log.events
+            .iter()
+            .enumerate()
+            .map(|(index, event)| event.to_measurement_event(event_log.uintn_size(), EventSource::EventLog {
+                path: path.clone(),
+                index,
+            }))
+            .collect()
+    }
+
+    /// `pcr-<alg>` directories by bank; the kernel names SM3 banks "sm3", tpm2-tools "sm3_256"
+    fn bank_dirs(&self) -> Result<Vec<(HashAlgorithm, PathBuf)>> {
+        let entries = fs::read_dir(&self.pcr_dir).map_err(|err| path_error(&self.pcr_dir, err))?;
+        let mut banks: Vec<(HashAlgorithm, PathBuf)> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let algorithm = name.strip_prefix("pcr-")?.parse::<HashAlgorithm>().ok()?;
+                Some((algorithm, entry.path()))
+            })
+            .collect();
+        banks.sort_by_key(|(algorithm, _)| algorithm.tcg_alg_id());
+        Ok(banks)
+    }
+}
+
+/// Read a PCR from the `pcr-<alg>` directory of its bank
+fn read_pcr_file(algorithm: &HashAlgorithm, bank_dir: &Path, pcr_index: usize) -> Result<Vec<u8>> {
+    let path = bank_dir.join(pcr_index.to_string());
+    let text = String::from_utf8_lossy(&read_file(&path)?).trim().to_string();
+    let value = hex::decode(&text)
+        .map_err(|err| Error::Parse(format!("{}: invalid PCR value: {}", path.display(), err)))?;
+    algorithm
+        .check_digest(value, DigestPadding::Exact)
+        .map_err(|err| Error::Parse(format!("{}: {}", path.display(), err)))
+}
+
+/// Read a file, naming it in the error
+fn read_file(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path).map_err(|err| path_error(path, err))
+}
+
+fn path_error(path: &Path, err: io::Error) -> Error {
+    Error::Io(io::Error::new(err.kind(), format!("{}: {}", path.display(), err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::tcg_event_log::{EV_NO_ACTION, EV_SEPARATOR};
+
+    /// Crypto-agile log with a SHA256 Spec ID header and one separator in PCR 0
+    fn event_log(separator_digest: &[u8]) -> Vec<u8> {
+        let mut spec_id = b"Spec ID Event03\0".to_vec();
+        spec_id.extend_from_slice(&0u32.to_le_bytes()); // platformClass
+        spec_id.extend_from_slice(&[0, 2, 0, 2]); // version 2.0, errata 0, UINT64
+        spec_id.extend_from_slice(&1u32.to_le_bytes());
+        spec_id.extend_from_slice(&HashAlgorithm::SHA256.tcg_alg_id().to_le_bytes());
+        spec_id.extend_from_slice(&32u16.to_le_bytes());
+        spec_id.push(0); // vendorInfoSize
+
+        let mut log = Vec::new();
+        log.extend_from_slice(&0u32.to_le_bytes());
+        log.extend_from_slice(&EV_NO_ACTION.to_le_bytes());
+        log.extend_from_slice(&[0; 20]);
+        log.extend_from_slice(&(spec_id.len() as u32).to_le_bytes());
+        log.extend_from_slice(&spec_id);
+
+        log.extend_from_slice(&0u32.to_le_bytes());
+        log.extend_from_slice(&EV_SEPARATOR.to_le_bytes());
+        log.extend_from_slice(&1u32.to_le_bytes());
+        log.extend_from_slice(&HashAlgorithm::SHA256.tcg_alg_id().to_le_bytes());
+        log.extend_from_slice(separator_digest);
+        log.extend_from_slice(&4u32.to_le_bytes());
+        log.extend_from_slice(&[0; 4]);
+        log
+    }
+
+    /// Fake securityfs/sysfs tree with the log and a SHA256 bank
+    fn fake_tpm(name: &str, pcr0: &[u8]) -> LinuxTpm {
+        let root = std::env::temp_dir().join(format!("eggs-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let bank_dir = root.join("tpm0").join("pcr-sha256");
+        fs::create_dir_all(&bank_dir).unwrap();
+        for pcr_index in 0..PCR_COUNT {
+            let value = if pcr_index == 0 { pcr0.to_vec() } else { vec![0; 32] };
+            fs::write(bank_dir.join(pcr_index.to_string()), format!("{}\n", hex::encode_upper(value))).unwrap();
+        }
+
+        let separator_digest = PcrSimulator::hash(&HashAlgorithm::SHA256, &[0; 4]);
+        fs::write(root.join("binary_bios_measurements"), event_log(&separator_digest)).unwrap();
+        LinuxTpm::new(root.join("binary_bios_measurements"), root.join("tpm0"))
+    }
+
+    fn expected_pcr0() -> Vec<u8> {
+        let separator_digest = PcrSimulator::hash(&HashAlgorithm::SHA256, &[0; 4]);
+        PcrSimulator::hash(&HashAlgorithm::SHA256, &[vec![0; 32], separator_digest].concat())
+    }
+
+    #[test]
+    fn verify_matches_live_pcrs() {
+        let tpm = fake_tpm("verify-match", &expected_pcr0());
+        let verification = tpm.verify().unwrap();
+        assert_eq!(verification.live_values.values.len(), PCR_COUNT);
+        assert_eq!(verification.report.results.len(), 1);
+        assert!(verification.report.all_match());
+        fs::remove_dir_all(tpm.pcr_dir.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn verify_reports_changed_pcr() {
+        let tpm = fake_tpm("verify-mismatch", &[0xff; 32]);
+        let verification = tpm.verify().unwrap();
+        assert_eq!(verification.report.mismatch_count(), 1);
+        assert_eq!(verification.report.results[0].pcr_index, 0);
+        fs::remove_dir_all(tpm.pcr_dir.parent().unwrap()).unwrap();
+    }
+}
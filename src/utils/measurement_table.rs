@@ -0,0 +1,283 @@
+use crate::models::measurement_event::{EventSource, MeasurementEvent};
+use crate::models::pcr_simulator::{DigestPadding, HashAlgorithm, PcrSimulator, TaggedDigest, PCR_COUNT};
+use crate::utils::file_processor::LineDiagnostic;
+use crate::utils::tcg_event_log::{event_type_from_name, event_type_name};
+
+/// Separator between the algorithms, and between the digests, of a multi-bank line
+const BANK_SEPARATOR: char = '+';
+
+/// One event of a measurement table
+#[derive(Debug, Clone)]
+pub struct MeasurementTableLine {
+    pub line_number: usize, // 1-based
+    pub pcr_index: usize,
+    pub digests: Vec<TaggedDigest>,
+    pub event_type: Option<u32>,
+    pub description: String,
+}
+
+impl MeasurementTableLine {
+    /// Convert to a simulator event extended into its own PCR
+    pub fn to_measurement_event(&self, path: &str) -> MeasurementEvent {
+        let mut event = MeasurementEvent::new(self.pcr_index, self.digests.clone(), self.description.clone())
+            .with_source(EventSource::File {
+                path: path.to_string(),
+                line: self.line_number,
+            });
+        if let Some(event_type) = self.event_type {
+            event = event.with_event_type(event_type);
+        }
+        event
+    }
+}
+
+/// Measurement file with a PCR index, algorithm, digest, event type and description per line
+///
+/// Columns are separated by tabs, commas (CSV, with optional double quotes) or whitespace,
+/// in which case the description is the rest of the line:
+///
+/// ```text
+/// # pcr  algorithm    digest           event type         description
+/// 0      sha256       3d4589...        EV_POST_CODE       BIOS
+/// 4      sha1+sha256  a1b2...+c3d4...  EV_EFI_BOOT_SERVICES_APPLICATION  shim
+/// ```
+///
+/// The event type (a name such as `EV_SEPARATOR` or a number) and the description are
+/// optional, `-` leaves the event type empty. A header line starting with `pcr` is skipped.
+#[derive(Debug, Clone, Default)]
+pub struct MeasurementTable {
+    pub lines: Vec<MeasurementTableLine>,
+}
+
+impl MeasurementTable {
+    /// Check whether text looks like a measurement table rather than bare digests
+    pub fn is_table(content: &str) -> bool {
+        content
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'))
+            .is_some_and(|line| {
+                let fields = split_fields(line);
+                Self::is_header(&fields)
+                    || (fields.len() >= 3
+                        && fields[0].parse::<usize>().is_ok()
+                        && parse_algorithms(&fields[1]).is_some())
+            })
+    }
+
+    /// Parse a measurement table, collecting a diagnostic for every line that cannot be used
+    pub fn parse(content: &str, padding: DigestPadding) -> (Self, Vec<LineDiagnostic>) {
+        let mut lines = Vec::new();
+        let mut diagnostics = Vec::new();
+
+        for (i, text) in content.lines().enumerate() {
+            let trimmed = text.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let fields = split_fields(trimmed);
+            if Self::is_header(&fields) {
+                continue;
+            }
+
+            match Self::parse_line(i + 1, &fields, padding) {
+                Ok(line) => lines.push(line),
+                Err(reason) => diagnostics.push(LineDiagnostic {
+                    line_number: i + 1,
+                    text: text.to_string(),
+                    reason,
+                }),
+            }
+        }
+
+        (Self { lines }, diagnostics)
+    }
+
+    /// Banks with a digest on every line, in the order of the first line
+    pub fn algorithms(&self) -> Vec<HashAlgorithm> {
+        let Some(first) = self.lines.first() else {
+            return Vec::new();
+        };
+        first.digests
+            .iter()
+            .map(|tagged| tagged.algorithm.clone())
+            .filter(|algorithm| {
+                self.lines.iter().all(|line| line.digests.iter().any(|tagged| &tagged.algorithm == algorithm))
+            })
+            .collect()
+    }
+
+    /// Convert every line to a simulator event
+    pub fn to_measurement_events(&self, path: &str) -> Vec<MeasurementEvent> {
+        self.lines.iter().map(|line| line.to_measurement_event(path)).collect()
+    }
+
+    fn is_header(fields: &[String]) -> bool {
+        fields.first().is_some_and(|field| field.eq_ignore_ascii_case("pcr"))
+    }
+
+    fn parse_line(line_number: usize, fields: &[String], padding: DigestPadding) -> Result<MeasurementTableLine, String> {
+        if fields.len() < 3 {
+            return Err(format!("Expected at least 3 columns (pcr, algorithm, digest), got {}", fields.len()));
+        }
+
+        let pcr_index = match fields[0].trim_start_matches("PCR").trim_start_matches("pcr").parse::<usize>() {
+            Ok(index) if index < PCR_COUNT => index,
+            _ => return Err(format!("Invalid PCR index '{}' (expected 0-{})", fields[0], PCR_COUNT - 1)),
+        };
+
+        let algorithms = parse_algorithms(&fields[1])
+            .ok_or_else(|| format!("Unknown algorithm '{}'", fields[1]))?;
+        let hex_values: Vec<&str> = fields[2].split(BANK_SEPARATOR).collect();
+        if hex_values.len() != algorithms.len() {
+            return Err(format!(
+                "{} algorithms but {} digests; separate multiple banks with '{}'",
+                algorithms.len(),
+                hex_values.len(),
+                BANK_SEPARATOR
+            ));
+        }
+
+        let mut digests = Vec::new();
+        for (algorithm, hex_value) in algorithms.into_iter().zip(hex_values) {
+            let digest = PcrSimulator::parse_hex(hex_value.trim_start_matches("0x"))
+                .and_then(|digest| algorithm.check_digest(digest, padding))
+                .map_err(|err| err.to_string())?;
+            digests.push(TaggedDigest::new(algorithm, digest));
+        }
+
+        let event_type = match fields.get(3).map(String::as_str) {
+            None | Some("") | Some("-") => None,
+            Some(name) => Some(event_type_from_name(name).ok_or_else(|| format!("Unknown event type '{}'", name))?),
+        };
+
+        let description = match fields.get(4).filter(|description| !description.is_empty()) {
+            Some(description) => description.clone(),
+            None => match event_type {
+                Some(event_type) => event_type_name(event_type).to_string(),
+                None => format!("Line {}", line_number),
+            },
+        };
+
+        Ok(MeasurementTableLine {
+            line_number,
+            pcr_index,
+            digests,
+            event_type,
+            description,
+        })
+    }
+}
+
+/// Parse one algorithm or several joined with [`BANK_SEPARATOR`]
+fn parse_algorithms(text: &str) -> Option<Vec<HashAlgorithm>> {
+    text.split(BANK_SEPARATOR).map(|name| name.trim().parse::<HashAlgorithm>().ok()).collect()
+}
+
+/// Split a line into at most five columns, the description taking the rest of the line
+///
+/// Tabs or commas only separate columns when the text before the first one is the PCR
+/// column, so a comma in the description of a whitespace-separated line is kept.
+fn split_fields(line: &str) -> Vec<String> {
+    let leads_with_pcr = |delimiter: char| {
+        line.split_once(delimiter).is_some_and(|(first, _)| {
+            let first = first.trim().trim_matches('"');
+            first.eq_ignore_ascii_case("pcr")
+                || first.trim_start_matches("PCR").trim_start_matches("pcr").parse::<usize>().is_ok()
+        })
+    };
+
+    if leads_with_pcr('\t') {
+        line.splitn(5, '\t').map(|field| field.trim().to_string()).collect()
+    } else if leads_with_pcr(',') {
+        split_csv(line)
+    } else {
+        // Whitespace separated; the description is the rest of the line
+        let mut fields = Vec::new();
+        let mut rest = line.trim();
+        while fields.len() < 4 && !rest.is_empty() {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            fields.push(rest[..end].to_string());
+            rest = rest[end..].trim_start();
+        }
+        if !rest.is_empty() {
+            fields.push(rest.trim_matches('"').to_string());
+        }
+        fields
+    }
+}
+
+/// Split a CSV line into at most five columns, honouring double quotes ("" is a literal
+/// quote); unquoted commas after the fourth belong to the description
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            },
+            '"' => quoted = !quoted,
+            ',' if !quoted && fields.len() < 4 => fields.push(std::mem::take(&mut field).trim().to_string()),
+            c => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGEST: &str = "3d5f6a1e8c0b4f2a9e7d6c5b4a39281706f5e4d3c2b1a09f8e7d6c5b4a392817";
+
+    fn parse(content: &str) -> MeasurementTable {
+        let (table, diagnostics) = MeasurementTable::parse(content, DigestPadding::Exact);
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        table
+    }
+
+    #[test]
+    fn whitespace_line_keeps_commas_in_the_description() {
+        let content = format!("0 sha256 {} EV_POST_CODE BIOS, version 2\n", DIGEST);
+        assert!(MeasurementTable::is_table(&content));
+
+        let table = parse(&content);
+        assert_eq!(table.lines.len(), 1);
+        assert_eq!(table.lines[0].pcr_index, 0);
+        assert_eq!(table.lines[0].event_type, event_type_from_name("EV_POST_CODE"));
+        assert_eq!(table.lines[0].description, "BIOS, version 2");
+    }
+
+    #[test]
+    fn csv_line_joins_trailing_fields_into_the_description() {
+        let content = format!("pcr,algorithm,digest,event,description\nPCR4,sha256,{},EV_POST_CODE,BIOS, version 2, build 7\n", DIGEST);
+        assert!(MeasurementTable::is_table(&content));
+
+        let table = parse(&content);
+        assert_eq!(table.lines.len(), 1);
+        assert_eq!(table.lines[0].pcr_index, 4);
+        assert_eq!(table.lines[0].description, "BIOS, version 2, build 7");
+    }
+
+    #[test]
+    fn csv_line_honours_quoted_descriptions() {
+        let content = format!("7,sha256,{},EV_POST_CODE,\"Setup \"\"Secure\"\", on\"\n", DIGEST);
+        let table = parse(&content);
+        assert_eq!(table.lines[0].pcr_index, 7);
+        assert_eq!(table.lines[0].description, "Setup \"Secure\", on");
+    }
+
+    #[test]
+    fn tab_line_keeps_tabs_in_the_description() {
+        let content = format!("2\tsha256\t{}\tEV_POST_CODE\tBIOS\tversion 2\n", DIGEST);
+        let table = parse(&content);
+        assert_eq!(table.lines[0].pcr_index, 2);
+        assert_eq!(table.lines[0].description, "BIOS\tversion 2");
+    }
+}
@@ -1 +1,7 @@
-pub mod file_processor; 
\ No newline at end of file
+pub mod file_processor;
+pub mod ima;
+pub mod linux_tpm;
+pub mod measurement_table;
+pub mod tcg_event_log;
+pub mod tpm2_tools;
+pub mod uefi_event;
\ No newline at end of file
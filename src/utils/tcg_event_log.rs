@@ -0,0 +1,570 @@
+use crate::error::{Error, Result};
+use crate::models::measurement_event::{EventSource, MeasurementEvent};
+use crate::models::pcr_simulator::{HashAlgorithm, TaggedDigest, PCR_COUNT};
+use crate::utils::uefi_event::UefiEvent;
+
+/// TCG PC Client event types
+pub const EV_PREBOOT_CERT: u32 = 0x0000_0000;
+pub const EV_POST_CODE: u32 = 0x0000_0001;
+pub const EV_UNUSED: u32 = 0x0000_0002;
+pub const EV_NO_ACTION: u32 = 0x0000_0003;
+pub const EV_SEPARATOR: u32 = 0x0000_0004;
+pub const EV_ACTION: u32 = 0x0000_0005;
+pub const EV_EVENT_TAG: u32 = 0x0000_0006;
+pub const EV_S_CRTM_CONTENTS: u32 = 0x0000_0007;
+pub const EV_S_CRTM_VERSION: u32 = 0x0000_0008;
+pub const EV_CPU_MICROCODE: u32 = 0x0000_0009;
+pub const EV_PLATFORM_CONFIG_FLAGS: u32 = 0x0000_000A;
+pub const EV_TABLE_OF_DEVICES: u32 = 0x0000_000B;
+pub const EV_COMPACT_HASH: u32 = 0x0000_000C;
+pub const EV_IPL: u32 = 0x0000_000D;
+pub const EV_IPL_PARTITION_DATA: u32 = 0x0000_000E;
+pub const EV_NONHOST_CODE: u32 = 0x0000_000F;
+pub const EV_NONHOST_CONFIG: u32 = 0x0000_0010;
+pub const EV_NONHOST_INFO: u32 = 0x0000_0011;
+pub const EV_OMIT_BOOT_DEVICE_EVENTS: u32 = 0x0000_0012;
+pub const EV_EFI_EVENT_BASE: u32 = 0x8000_0000;
+pub const EV_EFI_VARIABLE_DRIVER_CONFIG: u32 = 0x8000_0001;
+pub const EV_EFI_VARIABLE_BOOT: u32 = 0x8000_0002;
+pub const EV_EFI_BOOT_SERVICES_APPLICATION: u32 = 0x8000_0003;
+pub const EV_EFI_BOOT_SERVICES_DRIVER: u32 = 0x8000_0004;
+pub const EV_EFI_RUNTIME_SERVICES_DRIVER: u32 = 0x8000_0005;
+pub const EV_EFI_GPT_EVENT: u32 = 0x8000_0006;
+pub const EV_EFI_ACTION: u32 = 0x8000_0007;
+pub const EV_EFI_PLATFORM_FIRMWARE_BLOB: u32 = 0x8000_0008;
+pub const EV_EFI_HANDOFF_TABLES: u32 = 0x8000_0009;
+pub const EV_EFI_PLATFORM_FIRMWARE_BLOB2: u32 = 0x8000_000A;
+pub const EV_EFI_HANDOFF_TABLES2: u32 = 0x8000_000B;
+pub const EV_EFI_VARIABLE_BOOT2: u32 = 0x8000_000C;
+pub const EV_EFI_HCRTM_EVENT: u32 = 0x8000_0010;
+pub const EV_EFI_VARIABLE_AUTHORITY: u32 = 0x8000_00E0;
+pub const EV_EFI_SPDM_FIRMWARE_BLOB: u32 = 0x8000_00E1;
+pub const EV_EFI_SPDM_FIRMWARE_CONFIG: u32 = 0x8000_00E2;
+
+/// Signature of the Spec ID header event of a crypto-agile log
+const SPEC_ID_SIGNATURE: &[u8] = b"Spec ID Event03\0";
+
+/// Size of the SHA1 digest used by the legacy event format
+const SHA1_DIGEST_SIZE: usize = 20;
+
+/// Get the name of a TCG event type
+pub fn event_type_name(event_type: u32) -> &'static str {
+    match event_type {
+        EV_PREBOOT_CERT => "EV_PREBOOT_CERT",
+        EV_POST_CODE => "EV_POST_CODE",
+        EV_UNUSED => "EV_UNUSED",
+        EV_NO_ACTION => "EV_NO_ACTION",
+        EV_SEPARATOR => "EV_SEPARATOR",
+        EV_ACTION => "EV_ACTION",
+        EV_EVENT_TAG => "EV_EVENT_TAG",
+        EV_S_CRTM_CONTENTS => "EV_S_CRTM_CONTENTS",
+        EV_S_CRTM_VERSION => "EV_S_CRTM_VERSION",
+        EV_CPU_MICROCODE => "EV_CPU_MICROCODE",
+        EV_PLATFORM_CONFIG_FLAGS => "EV_PLATFORM_CONFIG_FLAGS",
+        EV_TABLE_OF_DEVICES => "EV_TABLE_OF_DEVICES",
+        EV_COMPACT_HASH => "EV_COMPACT_HASH",
+        EV_IPL => "EV_IPL",
+        EV_IPL_PARTITION_DATA => "EV_IPL_PARTITION_DATA",
+        EV_NONHOST_CODE => "EV_NONHOST_CODE",
+        EV_NONHOST_CONFIG => "EV_NONHOST_CONFIG",
+        EV_NONHOST_INFO => "EV_NONHOST_INFO",
+        EV_OMIT_BOOT_DEVICE_EVENTS => "EV_OMIT_BOOT_DEVICE_EVENTS",
+        EV_EFI_EVENT_BASE => "EV_EFI_EVENT_BASE",
+        EV_EFI_VARIABLE_DRIVER_CONFIG => "EV_EFI_VARIABLE_DRIVER_CONFIG",
+        EV_EFI_VARIABLE_BOOT => "EV_EFI_VARIABLE_BOOT",
+        EV_EFI_BOOT_SERVICES_APPLICATION => "EV_EFI_BOOT_SERVICES_APPLICATION",
+        EV_EFI_BOOT_SERVICES_DRIVER => "EV_EFI_BOOT_SERVICES_DRIVER",
+        EV_EFI_RUNTIME_SERVICES_DRIVER => "EV_EFI_RUNTIME_SERVICES_DRIVER",
+        EV_EFI_GPT_EVENT => "EV_EFI_GPT_EVENT",
+        EV_EFI_ACTION => "EV_EFI_ACTION",
+        EV_EFI_PLATFORM_FIRMWARE_BLOB => "EV_EFI_PLATFORM_FIRMWARE_BLOB",
+        EV_EFI_HANDOFF_TABLES => "EV_EFI_HANDOFF_TABLES",
+        EV_EFI_PLATFORM_FIRMWARE_BLOB2 => "EV_EFI_PLATFORM_FIRMWARE_BLOB2",
+        EV_EFI_HANDOFF_TABLES2 => "EV_EFI_HANDOFF_TABLES2",
+        EV_EFI_VARIABLE_BOOT2 => "EV_EFI_VARIABLE_BOOT2",
+        EV_EFI_HCRTM_EVENT => "EV_EFI_HCRTM_EVENT",
+        EV_EFI_VARIABLE_AUTHORITY => "EV_EFI_VARIABLE_AUTHORITY",
+        EV_EFI_SPDM_FIRMWARE_BLOB => "EV_EFI_SPDM_FIRMWARE_BLOB",
+        EV_EFI_SPDM_FIRMWARE_CONFIG => "EV_EFI_SPDM_FIRMWARE_CONFIG",
+        _ => "UNKNOWN",
+    }
+}
+
+/// Every event type with a name, in numeric order
+const EVENT_TYPES: [u32; 36] = [
+    EV_PREBOOT_CERT, EV_POST_CODE, EV_UNUSED, EV_NO_ACTION, EV_SEPARATOR, EV_ACTION,
+    EV_EVENT_TAG, EV_S_CRTM_CONTENTS, EV_S_CRTM_VERSION, EV_CPU_MICROCODE,
+    EV_PLATFORM_CONFIG_FLAGS, EV_TABLE_OF_DEVICES, EV_COMPACT_HASH, EV_IPL,
+    EV_IPL_PARTITION_DATA, EV_NONHOST_CODE, EV_NONHOST_CONFIG, EV_NONHOST_INFO,
+    EV_OMIT_BOOT_DEVICE_EVENTS, EV_EFI_EVENT_BASE, EV_EFI_VARIABLE_DRIVER_CONFIG,
+    EV_EFI_VARIABLE_BOOT, EV_EFI_BOOT_SERVICES_APPLICATION, EV_EFI_BOOT_SERVICES_DRIVER,
+    EV_EFI_RUNTIME_SERVICES_DRIVER, EV_EFI_GPT_EVENT, EV_EFI_ACTION,
+    EV_EFI_PLATFORM_FIRMWARE_BLOB, EV_EFI_HANDOFF_TABLES, EV_EFI_PLATFORM_FIRMWARE_BLOB2,
+    EV_EFI_HANDOFF_TABLES2, EV_EFI_VARIABLE_BOOT2, EV_EFI_HCRTM_EVENT,
+    EV_EFI_VARIABLE_AUTHORITY, EV_EFI_SPDM_FIRMWARE_BLOB, EV_EFI_SPDM_FIRMWARE_CONFIG,
+];
+
+/// Look up a TCG event type by name ("EV_SEPARATOR", case-insensitive, prefix optional)
+/// or by number ("4", "0x80000001")
+pub fn event_type_from_name(name: &str) -> Option<u32> {
+    let name = name.trim();
+    if let Some(hex_value) = name.strip_prefix("0x").or_else(|| name.strip_prefix("0X")) {
+        return u32::from_str_radix(hex_value, 16).ok();
+    }
+    if let Ok(value) = name.parse::<u32>() {
+        return Some(value);
+    }
+
+    let upper = name.to_uppercase();
+    let full_name = if upper.starts_with("EV_") { upper } else { format!("EV_{}", upper) };
+    EVENT_TYPES
+        .into_iter()
+        .find(|event_type| event_type_name(*event_type) == full_name)
+}
+
+/// Event log format
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventLogFormat {
+    /// TCG PC Client crypto-agile format (TCG_PCR_EVENT2)
+    CryptoAgile,
+    /// Legacy SHA1-only format (TCG_PCClientPCREvent)
+    Sha1,
+}
+
+/// Spec ID header event (TCG_EfiSpecIDEvent)
+#[derive(Debug, Clone)]
+pub struct SpecIdEvent {
+    pub platform_class: u32,
+    pub spec_version_minor: u8,
+    pub spec_version_major: u8,
+    pub spec_errata: u8,
+    pub uintn_size: u8,
+    pub digest_sizes: Vec<(u16, u16)>, // (TPM_ALG_ID, digest size)
+    pub vendor_info: Vec<u8>,
+}
+
+/// Single event of a TCG event log
+#[derive(Debug, Clone)]
+pub struct TcgEvent {
+    pub pcr_index: u32,
+    pub event_type: u32,
+    pub digests: Vec<(u16, Vec<u8>)>, // (TPM_ALG_ID, digest)
+    pub event_data: Vec<u8>,
+}
+
+impl TcgEvent {
+    /// Get event type name
+    pub fn event_type_name(&self) -> &'static str {
+        event_type_name(self.event_type)
+    }
+
+    /// Get the digest recorded for the given bank
+    pub fn digest_for(&self, algorithm: &HashAlgorithm) -> Option<&[u8]> {
+        let alg_id = algorithm.tcg_alg_id();
+        self.digests
+            .iter()
+            .find(|(id, _)| *id == alg_id)
+            .map(|(_, digest)| digest.as_slice())
+    }
+
+    /// Convert to a simulator event (digests of unknown algorithms are dropped); `uintn_size`
+    /// is the UINTN width of the log, see [`TcgEventLog::uintn_size`]
+    pub fn to_measurement_event(&self, uintn_size: usize, source: EventSource) -> MeasurementEvent {
+        let digests = self.digests
+            .iter()
+            .filter_map(|(alg_id, digest)| {
+                HashAlgorithm::from_tcg_alg_id(*alg_id)
+                    .map(|algorithm| TaggedDigest::new(algorithm, digest.clone()))
+            })
+            .collect();
+
+        // Decoded UEFI data names the event, e.g. the variable or the image's device path
+        let description = match UefiEvent::decode(self.event_type, &self.event_data, uintn_size) {
+            Some(Ok(decoded)) if !decoded.summary().is_empty() => decoded.summary(),
+            _ => self.event_type_name().to_string(),
+        };
+
+        MeasurementEvent::new(self.pcr_index as usize, digests, description)
+            .with_event_type(self.event_type)
+            .with_event_data(self.event_data.clone())
+            .with_uintn_size(uintn_size)
+            .with_source(source)
+    }
+}
+
+/// Parsed TCG event log
+#[derive(Debug, Clone)]
+pub struct TcgEventLog {
+    pub format: EventLogFormat,
+    pub spec_id: Option<SpecIdEvent>,
+    pub events: Vec<TcgEvent>,
+}
+
+impl TcgEventLog {
+    /// Check whether the data starts with a crypto-agile Spec ID header event
+    pub fn is_crypto_agile(data: &[u8]) -> bool {
+        // pcrIndex(4) + eventType(4) + digest(20) + eventSize(4) precede the signature
+        let header_len = 8 + SHA1_DIGEST_SIZE + 4;
+        data.len() >= header_len + SPEC_ID_SIGNATURE.len()
+            && data[0..4] == [0, 0, 0, 0]
+            && u32::from_le_bytes([data[4], data[5], data[6], data[7]]) == EV_NO_ACTION
+            && &data[header_len..header_len + SPEC_ID_SIGNATURE.len()] == SPEC_ID_SIGNATURE
+    }
+
+    /// Detect the format from the first event header (TCG_PCR_EVENT): a Spec ID signature
+    /// makes the log crypto-agile, any other event with a PCR index, a known event type and
+    /// data within the input starts a legacy SHA1 log
+    pub fn detect_format(data: &[u8]) -> Option<EventLogFormat> {
+        if Self::is_crypto_agile(data) {
+            return Some(EventLogFormat::CryptoAgile);
+        }
+
+        let mut reader = ByteReader::new(data);
+        let pcr_index = reader.read_u32().ok()? as usize;
+        let event_type = reader.read_u32().ok()?;
+        reader.read_bytes(SHA1_DIGEST_SIZE).ok()?;
+        let event_size = reader.read_u32().ok()? as usize;
+        let plausible = pcr_index < PCR_COUNT
+            && EVENT_TYPES.contains(&event_type)
+            && reader.read_bytes(event_size).is_ok();
+        plausible.then_some(EventLogFormat::Sha1)
+    }
+
+    /// Width of a UEFI UINTN in bytes: the Spec ID event's uintnSize is 1 for UINT32 and
+    /// 2 for UINT64; logs without one are taken as 64-bit
+    pub fn uintn_size(&self) -> usize {
+        match self.spec_id {
+            Some(ref spec_id) if spec_id.uintn_size == 1 => 4,
+            _ => 8,
+        }
+    }
+
+    /// Parse a binary event log (crypto-agile or legacy SHA1 format)
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.is_empty() {
+            return Err(Error::Parse("Event log is empty".to_string()));
+        }
+
+        let format = Self::detect_format(data)
+            .ok_or_else(|| Error::Parse("Not a TCG event log: invalid first event header".to_string()))?;
+        let mut reader = ByteReader::new(data);
+        let mut events = Vec::new();
+
+        if format == EventLogFormat::Sha1 {
+            // Legacy log: every event uses the SHA1 format
+            while !reader.is_at_end() {
+                events.push(Self::read_sha1_event(&mut reader)?);
+            }
+            return Ok(Self {
+                format: EventLogFormat::Sha1,
+                spec_id: None,
+                events,
+            });
+        }
+
+        // The header event always uses the SHA1 format
+        let header = Self::read_sha1_event(&mut reader)?;
+        let spec_id = Self::parse_spec_id(&header.event_data)?;
+        events.push(header);
+
+        while !reader.is_at_end() {
+            events.push(Self::read_event2(&mut reader, &spec_id)?);
+        }
+
+        Ok(Self {
+            format: EventLogFormat::CryptoAgile,
+            spec_id: Some(spec_id),
+            events,
+        })
+    }
+
+    /// Get the banks recorded in the log
+    pub fn algorithms(&self) -> Vec<HashAlgorithm> {
+        match &self.spec_id {
+            Some(spec_id) => spec_id
+                .digest_sizes
+                .iter()
+                .filter_map(|(alg_id, _)| HashAlgorithm::from_tcg_alg_id(*alg_id))
+                .collect(),
+            // Without a Spec ID header, the banks the events have digests for
+            None => {
+                let mut algorithms = Vec::new();
+                for (alg_id, _) in self.events.iter().flat_map(|event| &event.digests) {
+                    if let Some(algorithm) = HashAlgorithm::from_tcg_alg_id(*alg_id) {
+                        if !algorithms.contains(&algorithm) {
+                            algorithms.push(algorithm);
+                        }
+                    }
+                }
+                if algorithms.is_empty() {
+                    algorithms.push(HashAlgorithm::SHA1);
+                }
+                algorithms
+            },
+        }
+    }
+
+    /// Read an event in the legacy TCG_PCClientPCREvent format
+    fn read_sha1_event(reader: &mut ByteReader) -> Result<TcgEvent> {
+        let pcr_index = reader.read_u32()?;
+        let event_type = reader.read_u32()?;
+        let digest = reader.read_bytes(SHA1_DIGEST_SIZE)?.to_vec();
+        let event_size = reader.read_u32()? as usize;
+        let event_data = reader.read_bytes(event_size)?.to_vec();
+
+        Ok(TcgEvent {
+            pcr_index,
+            event_type,
+            digests: vec![(HashAlgorithm::SHA1.tcg_alg_id(), digest)],
+            event_data,
+        })
+    }
+
+    /// Read an event in the crypto-agile TCG_PCR_EVENT2 format
+    fn read_event2(reader: &mut ByteReader, spec_id: &SpecIdEvent) -> Result<TcgEvent> {
+        let offset = reader.position();
+        let pcr_index = reader.read_u32()?;
+        let event_type = reader.read_u32()?;
+
+        // TPML_DIGEST_VALUES
+        let count = reader.read_u32()?;
+        let mut digests = Vec::new();
+        for _ in 0..count {
+            let alg_id = reader.read_u16()?;
+            let size = spec_id
+                .digest_sizes
+                .iter()
+                .find(|(id, _)| *id == alg_id)
+                .map(|(_, size)| *size as usize)
+                .ok_or_else(|| Error::Parse(format!(
+                    "Event at offset {} uses algorithm 0x{:04x} not declared in the Spec ID event",
+                    offset, alg_id
+                )))?;
+            digests.push((alg_id, reader.read_bytes(size)?.to_vec()));
+        }
+
+        let event_size = reader.read_u32()? as usize;
+        let event_data = reader.read_bytes(event_size)?.to_vec();
+
+        Ok(TcgEvent {
+            pcr_index,
+            event_type,
+            digests,
+            event_data,
+        })
+    }
+
+    /// Parse the TCG_EfiSpecIDEvent structure
+    fn parse_spec_id(data: &[u8]) -> Result<SpecIdEvent> {
+        let mut reader = ByteReader::new(data);
+        let signature = reader.read_bytes(SPEC_ID_SIGNATURE.len())?;
+        if signature != SPEC_ID_SIGNATURE {
+            return Err(Error::Parse("Invalid Spec ID event signature".to_string()));
+        }
+
+        let platform_class = reader.read_u32()?;
+        let spec_version_minor = reader.read_u8()?;
+        let spec_version_major = reader.read_u8()?;
+        let spec_errata = reader.read_u8()?;
+        let uintn_size = reader.read_u8()?;
+
+        let algorithm_count = reader.read_u32()?;
+        let mut digest_sizes = Vec::new();
+        for _ in 0..algorithm_count {
+            let alg_id = reader.read_u16()?;
+            let size = reader.read_u16()?;
+            digest_sizes.push((alg_id, size));
+        }
+
+        let vendor_info_size = reader.read_u8()? as usize;
+        let vendor_info = reader.read_bytes(vendor_info_size)?.to_vec();
+
+        Ok(SpecIdEvent {
+            platform_class,
+            spec_version_minor,
+            spec_version_major,
+            spec_errata,
+            uintn_size,
+            digest_sizes,
+            vendor_info,
+        })
+    }
+}
+
+/// Little-endian reader over a byte slice
+pub(crate) struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    pub(crate) fn position(&self) -> usize {
+        self.pos
+    }
+
+    pub(crate) fn is_at_end(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    pub(crate) fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.data.len() - self.pos < len {
+            return Err(Error::Parse(format!(
+                "Unexpected end of event log at offset {} (needed {} bytes, {} left)",
+                self.pos,
+                len,
+                self.data.len() - self.pos
+            )));
+        }
+        let bytes = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    pub(crate) fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub(crate) fn read_u16(&mut self) -> Result<u16> {
+        let bytes = self.read_bytes(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    pub(crate) fn read_u32(&mut self) -> Result<u32> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub(crate) fn read_u64(&mut self) -> Result<u64> {
+        let low = self.read_u32()? as u64;
+        Ok((self.read_u32()? as u64) << 32 | low)
+    }
+
+    /// Read a UEFI UINTN of the given width in bytes
+    pub(crate) fn read_uintn(&mut self, size: usize) -> Result<u64> {
+        match size {
+            4 => Ok(self.read_u32()? as u64),
+            _ => self.read_u64(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHA256_ALG_ID: u16 = 0x000B;
+
+    /// Legacy TCG_PCClientPCREvent
+    fn sha1_event(pcr_index: u32, event_type: u32, digest: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut event = Vec::new();
+        event.extend_from_slice(&pcr_index.to_le_bytes());
+        event.extend_from_slice(&event_type.to_le_bytes());
+        event.extend_from_slice(digest);
+        event.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        event.extend_from_slice(data);
+        event
+    }
+
+    /// Crypto-agile log with SHA1 and SHA256 banks, a 32-bit UINTN and one EV_S_CRTM_VERSION event
+    fn crypto_agile_log() -> Vec<u8> {
+        let mut spec_id = SPEC_ID_SIGNATURE.to_vec();
+        spec_id.extend_from_slice(&0u32.to_le_bytes()); // platformClass
+        spec_id.extend_from_slice(&[0, 2, 0, 1]); // version 2.0, errata 0, UINT32
+        spec_id.extend_from_slice(&2u32.to_le_bytes());
+        spec_id.extend_from_slice(&0x0004u16.to_le_bytes());
+        spec_id.extend_from_slice(&20u16.to_le_bytes());
+        spec_id.extend_from_slice(&SHA256_ALG_ID.to_le_bytes());
+        spec_id.extend_from_slice(&32u16.to_le_bytes());
+        spec_id.extend_from_slice(&[2, 0xAA, 0xBB]); // vendorInfo
+
+        let mut log = sha1_event(0, EV_NO_ACTION, &[0; 20], &spec_id);
+        log.extend_from_slice(&0u32.to_le_bytes());
+        log.extend_from_slice(&EV_S_CRTM_VERSION.to_le_bytes());
+        log.extend_from_slice(&2u32.to_le_bytes());
+        log.extend_from_slice(&0x0004u16.to_le_bytes());
+        log.extend_from_slice(&[0x11; 20]);
+        log.extend_from_slice(&SHA256_ALG_ID.to_le_bytes());
+        log.extend_from_slice(&[0x22; 32]);
+        log.extend_from_slice(&2u32.to_le_bytes());
+        log.extend_from_slice(&[0x31, 0x00]);
+        log
+    }
+
+    #[test]
+    fn parses_crypto_agile_log() {
+        let data = crypto_agile_log();
+        assert_eq!(TcgEventLog::detect_format(&data), Some(EventLogFormat::CryptoAgile));
+
+        let log = TcgEventLog::parse(&data).unwrap();
+        assert_eq!(log.format, EventLogFormat::CryptoAgile);
+        let spec_id = log.spec_id.as_ref().unwrap();
+        assert_eq!((spec_id.spec_version_major, spec_id.spec_version_minor), (2, 0));
+        assert_eq!(spec_id.vendor_info, [0xAA, 0xBB]);
+        assert_eq!(log.uintn_size(), 4);
+        assert_eq!(log.algorithms(), [HashAlgorithm::SHA1, HashAlgorithm::SHA256]);
+
+        assert_eq!(log.events.len(), 2);
+        let event = &log.events[1];
+        assert_eq!((event.pcr_index, event.event_type_name()), (0, "EV_S_CRTM_VERSION"));
+        assert_eq!(event.digest_for(&HashAlgorithm::SHA1), Some(&[0x11; 20][..]));
+        assert_eq!(event.digest_for(&HashAlgorithm::SHA256), Some(&[0x22; 32][..]));
+        assert_eq!(event.event_data, [0x31, 0x00]);
+    }
+
+    #[test]
+    fn parses_sha1_log_that_is_valid_text() {
+        // Every byte is ASCII, so only the event header tells this apart from a text file
+        let mut data = sha1_event(0, EV_S_CRTM_VERSION, &[0x41; 20], b"1.00");
+        data.extend(sha1_event(4, EV_SEPARATOR, &[0x42; 20], &[0; 4]));
+        assert!(std::str::from_utf8(&data).is_ok());
+        assert_eq!(TcgEventLog::detect_format(&data), Some(EventLogFormat::Sha1));
+
+        let log = TcgEventLog::parse(&data).unwrap();
+        assert_eq!(log.format, EventLogFormat::Sha1);
+        assert!(log.spec_id.is_none());
+        assert_eq!(log.uintn_size(), 8);
+        assert_eq!(log.algorithms(), [HashAlgorithm::SHA1]);
+        assert_eq!(log.events.len(), 2);
+        assert_eq!(log.events[1].pcr_index, 4);
+        assert_eq!(log.events[1].digest_for(&HashAlgorithm::SHA1), Some(&[0x42; 20][..]));
+    }
+
+    #[test]
+    fn text_is_not_an_event_log() {
+        let text = b"0 sha256 3d5f6a1e8c0b4f2a9e7d6c5b4a39281706f5e4d3c2b1a09f8e7d6c5b4a392817\n";
+        assert_eq!(TcgEventLog::detect_format(text), None);
+        assert!(matches!(TcgEventLog::parse(text), Err(Error::Parse(_))));
+        assert!(matches!(TcgEventLog::parse(&[]), Err(Error::Parse(_))));
+    }
+
+    #[test]
+    fn rejects_truncated_log() {
+        let data = crypto_agile_log();
+        for len in [data.len() - 1, data.len() - 20, 40] {
+            assert!(matches!(TcgEventLog::parse(&data[..len]), Err(Error::Parse(_))), "length {}", len);
+        }
+    }
+
+    #[test]
+    fn rejects_oversized_event() {
+        // An event claiming more data than the log holds
+        let mut data = crypto_agile_log();
+        let size_offset = data.len() - 6;
+        data[size_offset..size_offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(TcgEventLog::parse(&data), Err(Error::Parse(_))));
+
+        let mut legacy = sha1_event(0, EV_S_CRTM_VERSION, &[0; 20], b"1.00");
+        legacy[28..32].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(TcgEventLog::detect_format(&legacy), None);
+
+        let mut legacy = sha1_event(0, EV_S_CRTM_VERSION, &[0; 20], b"1.00");
+        legacy.extend(sha1_event(1, EV_POST_CODE, &[0; 20], &[0; 8]));
+        let second_size = legacy.len() - 12;
+        legacy[second_size..second_size + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(TcgEventLog::parse(&legacy), Err(Error::Parse(_))));
+    }
+}
@@ -0,0 +1,413 @@
+use crate::error::{Error, Result};
+use crate::models::pcr_simulator::HashAlgorithm;
+use crate::models::verification::GoldenValues;
+use crate::utils::file_processor::LineDiagnostic;
+use crate::utils::tcg_event_log::{event_type_from_name, EventLogFormat, SpecIdEvent, TcgEvent, TcgEventLog};
+
+/// Line of the YAML subset written by tpm2-tools
+struct YamlLine<'a> {
+    line_number: usize, // 1-based
+    text: &'a str,      // The whole line
+    indent: usize,      // Column of the key, after any "- " list marker
+    item: bool,         // Starts a list item
+    key: &'a str,
+    value: &'a str,     // Unquoted, empty for nested mappings
+}
+
+/// Split tpm2-tools YAML into key/value lines, skipping blank lines, comments and document markers
+fn yaml_lines(content: &str) -> Vec<YamlLine<'_>> {
+    let mut lines = Vec::new();
+    for (i, raw) in content.lines().enumerate() {
+        let text = raw.trim_start();
+        if text.is_empty() || text.starts_with('#') || text.starts_with("---") {
+            continue;
+        }
+
+        let mut indent = raw.len() - text.len();
+        let (item, text) = match text.strip_prefix("- ") {
+            Some(rest) => {
+                indent += 2;
+                (true, rest.trim_start())
+            },
+            None => (false, text),
+        };
+        let (key, value) = text.split_once(':').unwrap_or((text, ""));
+        lines.push(YamlLine {
+            line_number: i + 1,
+            text: raw,
+            indent,
+            item,
+            key: key.trim(),
+            value: value.trim().trim_matches('"').trim_matches('\''),
+        });
+    }
+    lines
+}
+
+/// Check whether text is `tpm2_eventlog` output
+pub fn is_eventlog_output(content: &str) -> bool {
+    let lines = yaml_lines(content);
+    lines.iter().any(|line| line.indent == 0 && line.key == "events")
+        && lines.iter().any(|line| line.item && line.key == "EventNum")
+}
+
+/// Check whether text is `tpm2_pcrread` output (a bank such as `sha256:` followed by `0 : 0x...` lines)
+pub fn is_pcrread_output(content: &str) -> bool {
+    let lines = yaml_lines(content);
+    match lines.as_slice() {
+        [bank, pcr, ..] => {
+            bank.value.is_empty()
+                && bank.key.parse::<HashAlgorithm>().is_ok()
+                && pcr.indent > bank.indent
+                && pcr.key.parse::<usize>().is_ok()
+                && pcr.value.starts_with("0x")
+        },
+        _ => false,
+    }
+}
+
+/// Parse `tpm2_pcrread` output into expected PCR values
+pub fn parse_pcrread_output(content: &str) -> Result<GoldenValues> {
+    // Every bank names its algorithm, the default is never used
+    GoldenValues::parse(content, &HashAlgorithm::SHA256)
+}
+
+/// Parse `tpm2_eventlog` output into an event log and the PCR values of its `pcrs` section,
+/// with a diagnostic for every event whose data cannot be used
+///
+/// Events keep their PCR index, event type, per-bank digests and hex event data; decoded
+/// event fields (variable names, device paths) are not converted back to raw event data.
+pub fn parse_eventlog_output(content: &str) -> Result<(TcgEventLog, GoldenValues, Vec<LineDiagnostic>)> {
+    let lines = yaml_lines(content);
+    let mut diagnostics = Vec::new();
+    let mut events = Vec::new();
+    let mut spec_id: Option<SpecIdEvent> = None;
+    let mut crypto_agile = false;
+    let mut pcrs_text = String::new();
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = &lines[i];
+
+        if line.indent == 0 && line.key == "pcrs" {
+            // The rest of the section is tpm2_pcrread output
+            let start = lines.get(i + 1).map_or(0, |line| line.line_number - 1);
+            let end = lines[i + 1..]
+                .iter()
+                .find(|line| line.indent == 0)
+                .map_or(usize::MAX, |line| line.line_number - 1);
+            pcrs_text = content.lines().skip(start).take(end.saturating_sub(start)).collect::<Vec<_>>().join("\n");
+            i += 1;
+            continue;
+        }
+        if !(line.item && line.key == "EventNum") {
+            i += 1;
+            continue;
+        }
+
+        // Fields of the event are indented like EventNum, nested blocks further
+        let field_indent = line.indent;
+        let end = lines[i + 1..]
+            .iter()
+            .position(|next| next.indent < field_indent || (next.indent == field_indent && next.item))
+            .map_or(lines.len(), |offset| i + 1 + offset);
+        let (event, event_spec_id) = parse_event(&lines[i..end], field_indent, &mut diagnostics)?;
+        // Only crypto-agile events list their digests by algorithm
+        crypto_agile |= event_spec_id.is_some()
+            || lines[i..end].iter().any(|line| line.indent == field_indent && line.key == "Digests");
+        if spec_id.is_none() {
+            spec_id = event_spec_id;
+        }
+        events.push(event);
+        i = end;
+    }
+
+    if events.is_empty() {
+        return Err(Error::Parse("tpm2_eventlog output contains no events".to_string()));
+    }
+    let pcrs = parse_pcrread_output(&pcrs_text)
+        .map_err(|err| Error::Parse(format!("tpm2_eventlog pcrs section: {}", err)))?;
+
+    let event_log = TcgEventLog {
+        format: if crypto_agile { EventLogFormat::CryptoAgile } else { EventLogFormat::Sha1 },
+        spec_id,
+        events,
+    };
+    Ok((event_log, pcrs, diagnostics))
+}
+
+/// Parse the lines of one event, returning its Spec ID header if it has one
+fn parse_event(lines: &[YamlLine<'_>], field_indent: usize, diagnostics: &mut Vec<LineDiagnostic>) -> Result<(TcgEvent, Option<SpecIdEvent>)> {
+    let first_line = lines[0].line_number;
+    let error = |line_number: usize, reason: String| Error::Parse(format!("tpm2_eventlog line {}: {}", line_number, reason));
+
+    let mut pcr_index = None;
+    let mut event_type = None;
+    let mut digests = Vec::new();
+    let mut event_data = Vec::new();
+    let mut spec_id: Option<SpecIdEvent> = None;
+    let mut block = "";
+    let mut algorithm = None;
+
+    for line in lines {
+        if line.indent == field_indent && !line.item {
+            block = line.key;
+        }
+
+        match (block, line.key) {
+            (_, "PCRIndex") if line.indent == field_indent => {
+                pcr_index = Some(line.value.parse::<u32>()
+                    .map_err(|_| error(line.line_number, format!("invalid PCRIndex '{}'", line.value)))?);
+            },
+            (_, "EventType") if line.indent == field_indent => {
+                event_type = Some(event_type_from_name(line.value)
+                    .ok_or_else(|| error(line.line_number, format!("unknown EventType '{}'", line.value)))?);
+            },
+            // Legacy events carry a single SHA1 digest
+            ("Digest", "Digest") => {
+                digests.push((HashAlgorithm::SHA1.tcg_alg_id(), hex_digest(line)?));
+            },
+            ("Digests", "AlgorithmId") => {
+                algorithm = Some(line.value.parse::<HashAlgorithm>()
+                    .map_err(|_| error(line.line_number, format!("unknown AlgorithmId '{}'", line.value)))?);
+            },
+            ("Digests", "Digest") => {
+                let algorithm = algorithm.take()
+                    .ok_or_else(|| error(line.line_number, "Digest without AlgorithmId".to_string()))?;
+                digests.push((algorithm.tcg_alg_id(), hex_digest(line)?));
+            },
+            ("Event", "Event") if !line.value.is_empty() => match hex::decode(line.value) {
+                Ok(data) => event_data = data,
+                Err(err) => diagnostics.push(LineDiagnostic {
+                    line_number: line.line_number,
+                    text: line.text.to_string(),
+                    reason: format!("invalid event data: {}", err),
+                }),
+            },
+            ("SpecID", key) => {
+                let spec_id = spec_id.get_or_insert_with(|| SpecIdEvent {
+                    platform_class: 0,
+                    spec_version_minor: 0,
+                    spec_version_major: 0,
+                    spec_errata: 0,
+                    uintn_size: 0,
+                    digest_sizes: Vec::new(),
+                    vendor_info: Vec::new(),
+                });
+                let number = line.value.parse::<u32>().unwrap_or(0);
+                match key {
+                    "platformClass" => spec_id.platform_class = number,
+                    "specVersionMinor" => spec_id.spec_version_minor = number as u8,
+                    "specVersionMajor" => spec_id.spec_version_major = number as u8,
+                    "specErrata" => spec_id.spec_errata = number as u8,
+                    "uintnSize" => spec_id.uintn_size = number as u8,
+                    "algorithmId" | "AlgorithmId" => algorithm = line.value.parse::<HashAlgorithm>().ok(),
+                    "digestSize" => {
+                        if let Some(algorithm) = algorithm.take() {
+                            spec_id.digest_sizes.push((algorithm.tcg_alg_id(), number as u16));
+                        }
+                    },
+                    _ => {},
+                }
+            },
+            _ => {},
+        }
+    }
+
+    let event = TcgEvent {
+        pcr_index: pcr_index.ok_or_else(|| error(first_line, "event has no PCRIndex".to_string()))?,
+        event_type: event_type.ok_or_else(|| error(first_line, "event has no EventType".to_string()))?,
+        digests,
+        event_data,
+    };
+    Ok((event, spec_id))
+}
+
+fn hex_digest(line: &YamlLine<'_>) -> Result<Vec<u8>> {
+    hex::decode(line.value.trim_start_matches("0x"))
+        .map_err(|err| Error::Parse(format!("tpm2_eventlog line {}: invalid digest: {}", line.line_number, err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PCRREAD: &str = "\
+sha1:
+  0 : 0x54EC63857393A187BE45CB169606057BDF006988
+  10: 0x0000000000000000000000000000000000000000
+sha256:
+  0 : 0x59986CF0B6EA8F5A8CAFE8F36B487B284158D1639EC482E8DD70DC38D51AA735
+";
+
+    /// Spec ID header, a hex event, an event with decoded data, and a `pcrs` section
+    const EVENTLOG: &str = r#"---
+version: 1
+events:
+- EventNum: 0
+  PCRIndex: 0
+  EventType: EV_NO_ACTION
+  Digest: "0000000000000000000000000000000000000000"
+  EventSize: 37
+  SpecID:
+  - Signature: Spec ID Event03
+    platformClass: 0
+    specVersionMinor: 0
+    specVersionMajor: 2
+    specErrata: 0
+    uintnSize: 2
+    numberOfAlgorithms: 2
+    Algorithms:
+    - Algorithm[0]:
+      algorithmId: sha1
+      digestSize: 20
+    - Algorithm[1]:
+      algorithmId: sha256
+      digestSize: 32
+    vendorInfoSize: 0
+- EventNum: 1
+  PCRIndex: 0
+  EventType: EV_S_CRTM_VERSION
+  DigestCount: 2
+  Digests:
+  - AlgorithmId: sha1
+    Digest: "1489f923c4dca729178b3e3233458550d8dddf29"
+  - AlgorithmId: sha256
+    Digest: "96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7"
+  EventSize: 2
+  Event: "0000"
+- EventNum: 2
+  PCRIndex: 7
+  EventType: EV_EFI_VARIABLE_DRIVER_CONFIG
+  DigestCount: 2
+  Digests:
+  - AlgorithmId: sha1
+    Digest: "b70482a9c35b236639019cd8b2ecb03a9ee7db09"
+  - AlgorithmId: sha256
+    Digest: "b831b33e0c05b45e15bbdd9b3bfa43825fee0aa0b6e5a54e31e2bd8b073b76b7"
+  EventSize: 2
+  Event:
+    VariableName: 8be4df61-93ca-11d2-aa0d-00e098032b8c
+    UnicodeName: SecureBoot
+pcrs:
+  sha1:
+    0 : 0x54EC63857393A187BE45CB169606057BDF006988
+  sha256:
+    0 : 0x59986CF0B6EA8F5A8CAFE8F36B487B284158D1639EC482E8DD70DC38D51AA735
+"#;
+
+    /// Legacy SHA1 log event
+    const SHA1_EVENTLOG: &str = r#"events:
+- EventNum: 0
+  PCRIndex: 4
+  EventType: EV_EFI_BOOT_SERVICES_APPLICATION
+  Digest: "089017cedf29ba7955e32e88c25d3948b4f3fdea"
+  EventSize: 4
+  Event: "7368696d"
+"#;
+
+    #[test]
+    fn detects_pcrread_and_eventlog_output() {
+        assert!(is_pcrread_output(PCRREAD));
+        assert!(!is_eventlog_output(PCRREAD));
+        assert!(is_eventlog_output(EVENTLOG));
+        assert!(is_eventlog_output(SHA1_EVENTLOG));
+        assert!(!is_pcrread_output(EVENTLOG));
+
+        assert!(!is_pcrread_output("0 sha256 96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7"));
+        assert!(!is_pcrread_output("md5:\n  0 : 0x00\n"));
+        assert!(!is_eventlog_output("events:\n"));
+    }
+
+    #[test]
+    fn parses_pcrread_output() {
+        let golden_values = parse_pcrread_output(PCRREAD).unwrap();
+        let values: Vec<(HashAlgorithm, usize, String)> = golden_values.values
+            .iter()
+            .map(|golden| (golden.algorithm.clone(), golden.pcr_index, hex::encode(&golden.value)))
+            .collect();
+        assert_eq!(values, vec![
+            (HashAlgorithm::SHA1, 0, "54ec63857393a187be45cb169606057bdf006988".to_string()),
+            (HashAlgorithm::SHA1, 10, "00".repeat(20)),
+            (HashAlgorithm::SHA256, 0, "59986cf0b6ea8f5a8cafe8f36b487b284158d1639ec482e8dd70dc38d51aa735".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn parses_crypto_agile_eventlog_output() {
+        let (event_log, pcrs, diagnostics) = parse_eventlog_output(EVENTLOG).unwrap();
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        assert_eq!(event_log.format, EventLogFormat::CryptoAgile);
+
+        let spec_id = event_log.spec_id.unwrap();
+        assert_eq!((spec_id.spec_version_major, spec_id.uintn_size), (2, 2));
+        assert_eq!(spec_id.digest_sizes, vec![
+            (HashAlgorithm::SHA1.tcg_alg_id(), 20),
+            (HashAlgorithm::SHA256.tcg_alg_id(), 32),
+        ]);
+
+        let events = &event_log.events;
+        assert_eq!(events.len(), 3);
+        assert_eq!((events[0].pcr_index, events[0].event_type_name()), (0, "EV_NO_ACTION"));
+        assert_eq!((events[1].pcr_index, events[1].event_type_name()), (0, "EV_S_CRTM_VERSION"));
+        assert_eq!(
+            events[1].digest_for(&HashAlgorithm::SHA256).map(hex::encode).as_deref(),
+            Some("96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7")
+        );
+        assert_eq!(events[1].event_data, vec![0, 0]);
+        // Decoded event data is not converted back to raw bytes
+        assert_eq!(events[2].pcr_index, 7);
+        assert_eq!(events[2].digests.len(), 2);
+        assert!(events[2].event_data.is_empty());
+
+        assert_eq!(pcrs.values.len(), 2);
+        assert_eq!(pcrs.values[1].algorithm, HashAlgorithm::SHA256);
+    }
+
+    #[test]
+    fn parses_sha1_eventlog_output_without_pcrs() {
+        let (event_log, pcrs, diagnostics) = parse_eventlog_output(SHA1_EVENTLOG).unwrap();
+        assert!(diagnostics.is_empty());
+        assert!(pcrs.is_empty());
+        assert_eq!(event_log.format, EventLogFormat::Sha1);
+        assert!(event_log.spec_id.is_none());
+
+        let event = &event_log.events[0];
+        assert_eq!(event.pcr_index, 4);
+        assert_eq!(event.digests, vec![(HashAlgorithm::SHA1.tcg_alg_id(), hex::decode("089017cedf29ba7955e32e88c25d3948b4f3fdea").unwrap())]);
+        assert_eq!(event.event_data, b"shim");
+    }
+
+    #[test]
+    fn invalid_event_data_is_a_diagnostic() {
+        let content = SHA1_EVENTLOG.replace("\"7368696d\"", "\"73zz\"");
+        let (event_log, _, diagnostics) = parse_eventlog_output(&content).unwrap();
+
+        assert_eq!(event_log.events.len(), 1);
+        assert!(event_log.events[0].event_data.is_empty());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line_number, 7);
+        assert_eq!(diagnostics[0].text, "  Event: \"73zz\"");
+        assert!(diagnostics[0].reason.starts_with("invalid event data"));
+    }
+
+    #[test]
+    fn rejects_unusable_eventlog_output() {
+        let cases = [
+            (SHA1_EVENTLOG.replace("  PCRIndex: 4\n", ""), "tpm2_eventlog line 2: event has no PCRIndex"),
+            (SHA1_EVENTLOG.replace("  PCRIndex: 4", "  PCRIndex: four"), "tpm2_eventlog line 3: invalid PCRIndex 'four'"),
+            (SHA1_EVENTLOG.replace("EV_EFI_BOOT_SERVICES_APPLICATION", "EV_BOGUS"), "tpm2_eventlog line 4: unknown EventType 'EV_BOGUS'"),
+            (EVENTLOG.replace("AlgorithmId: sha1", "AlgorithmId: md5"), "tpm2_eventlog line 30: unknown AlgorithmId 'md5'"),
+            (EVENTLOG.replace("  - AlgorithmId: sha256\n", ""), "tpm2_eventlog line 32: Digest without AlgorithmId"),
+            ("events:\n".to_string(), "tpm2_eventlog output contains no events"),
+        ];
+        for (content, expected) in cases {
+            assert_eq!(parse_eventlog_output(&content).unwrap_err().to_string(), expected);
+        }
+
+        let bad_digest = SHA1_EVENTLOG.replace("089017ce", "0890zzce");
+        assert!(parse_eventlog_output(&bad_digest).unwrap_err().to_string().starts_with("tpm2_eventlog line 5: invalid digest"));
+        let bad_pcrs = EVENTLOG.replace("    0 : 0x5998", "    0 : 0xZZ98");
+        assert!(parse_eventlog_output(&bad_pcrs).unwrap_err().to_string().starts_with("tpm2_eventlog pcrs section: "));
+    }
+}
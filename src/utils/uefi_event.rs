@@ -0,0 +1,571 @@
+use std::fmt;
+use crate::error::{Error, Result};
+use crate::utils::tcg_event_log::{
+    ByteReader, EV_ACTION, EV_EFI_ACTION, EV_EFI_BOOT_SERVICES_APPLICATION, EV_EFI_BOOT_SERVICES_DRIVER,
+    EV_EFI_GPT_EVENT, EV_EFI_PLATFORM_FIRMWARE_BLOB, EV_EFI_PLATFORM_FIRMWARE_BLOB2,
+    EV_EFI_RUNTIME_SERVICES_DRIVER, EV_EFI_VARIABLE_AUTHORITY, EV_EFI_VARIABLE_BOOT,
+    EV_EFI_VARIABLE_BOOT2, EV_EFI_VARIABLE_DRIVER_CONFIG, EV_IPL, EV_SEPARATOR,
+};
+
+/// EFI_GUID, stored in its little-endian wire format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EfiGuid(pub [u8; 16]);
+
+impl EfiGuid {
+    pub const GLOBAL_VARIABLE: EfiGuid = EfiGuid::new(0x8be4df61, 0x93ca, 0x11d2, [0xaa, 0x0d, 0x00, 0xe0, 0x98, 0x03, 0x2b, 0x8c]);
+    pub const IMAGE_SECURITY_DATABASE: EfiGuid = EfiGuid::new(0xd719b2cb, 0x3d3a, 0x4596, [0xa3, 0xbc, 0xda, 0xd0, 0x0e, 0x67, 0x65, 0x6f]);
+    pub const SHIM_LOCK: EfiGuid = EfiGuid::new(0x605dab50, 0xe046, 0x4300, [0xab, 0xb6, 0x3d, 0xd8, 0x10, 0xdd, 0x8b, 0x23]);
+
+    /// GUID from its textual fields, e.g. `8be4df61-93ca-11d2-aa0d-00e098032b8c`
+    pub const fn new(data1: u32, data2: u16, data3: u16, data4: [u8; 8]) -> Self {
+        let d1 = data1.to_le_bytes();
+        let d2 = data2.to_le_bytes();
+        let d3 = data3.to_le_bytes();
+        EfiGuid([
+            d1[0], d1[1], d1[2], d1[3], d2[0], d2[1], d3[0], d3[1],
+            data4[0], data4[1], data4[2], data4[3], data4[4], data4[5], data4[6], data4[7],
+        ])
+    }
+
+    fn read(reader: &mut ByteReader) -> Result<Self> {
+        let mut guid = [0u8; 16];
+        guid.copy_from_slice(reader.read_bytes(16)?);
+        Ok(EfiGuid(guid))
+    }
+
+    /// Name of a well-known vendor GUID
+    pub fn name(&self) -> Option<&'static str> {
+        match *self {
+            EfiGuid::GLOBAL_VARIABLE => Some("EFI_GLOBAL_VARIABLE"),
+            EfiGuid::IMAGE_SECURITY_DATABASE => Some("EFI_IMAGE_SECURITY_DATABASE"),
+            EfiGuid::SHIM_LOCK => Some("SHIM_LOCK"),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for EfiGuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let g = &self.0;
+        write!(
+            f,
+            "{:08x}-{:04x}-{:04x}-{}-{}",
+            u32::from_le_bytes([g[0], g[1], g[2], g[3]]),
+            u16::from_le_bytes([g[4], g[5]]),
+            u16::from_le_bytes([g[6], g[7]]),
+            hex::encode(&g[8..10]),
+            hex::encode(&g[10..16])
+        )
+    }
+}
+
+/// UEFI variable measured by EV_EFI_VARIABLE_* events (UEFI_VARIABLE_DATA)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UefiVariable {
+    pub guid: EfiGuid,
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+impl UefiVariable {
+    fn parse(data: &[u8]) -> Result<Self> {
+        let mut reader = ByteReader::new(data);
+        let guid = EfiGuid::read(&mut reader)?;
+        let name_length = reader.read_u64()? as usize;
+        let data_length = reader.read_u64()? as usize;
+        let name = utf16_string(reader.read_bytes(name_length.saturating_mul(2))?);
+        let data = reader.read_bytes(data_length)?.to_vec();
+        Ok(Self { guid, name, data })
+    }
+
+    /// Variable value: boot order and load options decoded, short values as hex
+    pub fn value_text(&self) -> String {
+        if self.guid == EfiGuid::GLOBAL_VARIABLE {
+            if self.name == "BootOrder" {
+                let entries: Vec<String> = self.data
+                    .chunks_exact(2)
+                    .map(|entry| format!("Boot{:04X}", u16::from_le_bytes([entry[0], entry[1]])))
+                    .collect();
+                return entries.join(", ");
+            }
+            if is_load_option_name(&self.name) {
+                if let Ok(load_option) = load_option_text(&self.data) {
+                    return load_option;
+                }
+            }
+        }
+        match self.data.len() {
+            0 => "(empty)".to_string(),
+            1..=32 => format!("0x{}", hex::encode(&self.data)),
+            len => format!("{} bytes", len),
+        }
+    }
+}
+
+/// Decoded data of a PC Client UEFI event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UefiEvent {
+    /// EV_EFI_VARIABLE_DRIVER_CONFIG, EV_EFI_VARIABLE_BOOT(2) and EV_EFI_VARIABLE_AUTHORITY
+    Variable(UefiVariable),
+    /// EV_EFI_BOOT_SERVICES_APPLICATION and the driver events (UEFI_IMAGE_LOAD_EVENT)
+    ImageLoad {
+        image_location: u64,
+        image_length: u64,
+        link_time_address: u64,
+        device_path: String,
+    },
+    /// EV_EFI_PLATFORM_FIRMWARE_BLOB(2)
+    FirmwareBlob {
+        description: Option<String>,
+        base: u64,
+        length: u64,
+    },
+    /// EV_SEPARATOR
+    Separator(Vec<u8>),
+    /// EV_IPL, EV_ACTION and EV_EFI_ACTION
+    Text(String),
+}
+
+impl UefiEvent {
+    /// Decode the event data of an event type; None for types that are not decoded.
+    /// `uintn_size` is the firmware's UINTN width in bytes (4 or 8)
+    pub fn decode(event_type: u32, data: &[u8], uintn_size: usize) -> Option<Result<Self>> {
+        let decoded = match event_type {
+            EV_EFI_VARIABLE_DRIVER_CONFIG | EV_EFI_VARIABLE_BOOT | EV_EFI_VARIABLE_BOOT2 | EV_EFI_VARIABLE_AUTHORITY => {
+                UefiVariable::parse(data).map(UefiEvent::Variable)
+            },
+            EV_EFI_BOOT_SERVICES_APPLICATION | EV_EFI_BOOT_SERVICES_DRIVER | EV_EFI_RUNTIME_SERVICES_DRIVER => {
+                Self::parse_image_load(data, uintn_size)
+            },
+            EV_EFI_PLATFORM_FIRMWARE_BLOB | EV_EFI_PLATFORM_FIRMWARE_BLOB2 => {
+                Self::parse_firmware_blob(data, event_type == EV_EFI_PLATFORM_FIRMWARE_BLOB2)
+            },
+            EV_SEPARATOR => Ok(UefiEvent::Separator(data.to_vec())),
+            EV_IPL | EV_ACTION | EV_EFI_ACTION => Ok(UefiEvent::Text(event_text(data))),
+            _ => return None,
+        };
+        Some(decoded)
+    }
+
+    fn parse_image_load(data: &[u8], uintn_size: usize) -> Result<Self> {
+        let mut reader = ByteReader::new(data);
+        // EFI_PHYSICAL_ADDRESS is always 64 bits, the rest are UINTN
+        let image_location = reader.read_u64()?;
+        let image_length = reader.read_uintn(uintn_size)?;
+        let link_time_address = reader.read_uintn(uintn_size)?;
+        let path_length = reader.read_uintn(uintn_size)? as usize;
+        let device_path = device_path_text(reader.read_bytes(path_length)?)?;
+        Ok(UefiEvent::ImageLoad { image_location, image_length, link_time_address, device_path })
+    }
+
+    fn parse_firmware_blob(data: &[u8], with_description: bool) -> Result<Self> {
+        let mut reader = ByteReader::new(data);
+        let description = if with_description {
+            let size = reader.read_u8()? as usize;
+            Some(event_text(reader.read_bytes(size)?))
+        } else {
+            None
+        };
+        Ok(UefiEvent::FirmwareBlob {
+            description,
+            base: reader.read_u64()?,
+            length: reader.read_u64()?,
+        })
+    }
+
+    /// One-line summary, e.g. the variable name or the image's device path
+    pub fn summary(&self) -> String {
+        match self {
+            UefiEvent::Variable(variable) => variable.name.clone(),
+            UefiEvent::ImageLoad { device_path, .. } => device_path.clone(),
+            UefiEvent::FirmwareBlob { description: Some(description), .. } => description.clone(),
+            UefiEvent::FirmwareBlob { base, length, .. } => format!("0x{:x} ({} bytes)", base, length),
+            UefiEvent::Separator(value) => separator_text(value),
+            UefiEvent::Text(text) => text.clone(),
+        }
+    }
+
+    /// Labelled fields of the decoded data
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        match self {
+            UefiEvent::Variable(variable) => vec![
+                ("GUID", match variable.guid.name() {
+                    Some(name) => format!("{} ({})", variable.guid, name),
+                    None => variable.guid.to_string(),
+                }),
+                ("Name", variable.name.clone()),
+                ("Value", variable.value_text()),
+            ],
+            UefiEvent::ImageLoad { image_location, image_length, link_time_address, device_path } => vec![
+                ("Image", format!("0x{:x} ({} bytes)", image_location, image_length)),
+                ("Link Time Address", format!("0x{:x}", link_time_address)),
+                ("Device Path", device_path.clone()),
+            ],
+            UefiEvent::FirmwareBlob { description, base, length } => {
+                let mut fields = vec![("Base", format!("0x{:x}", base)), ("Length", length.to_string())];
+                if let Some(description) = description {
+                    fields.insert(0, ("Description", description.clone()));
+                }
+                fields
+            },
+            UefiEvent::Separator(value) => vec![("Separator", separator_text(value))],
+            UefiEvent::Text(text) => vec![("Text", text.clone())],
+        }
+    }
+}
+
+/// Data an event's digest is computed over, for the event types whose digest the PC Client
+/// spec defines over the event data
+///
+/// EV_EFI_VARIABLE_BOOT digests cover only the variable value, but some firmware hashes the
+/// whole UEFI_VARIABLE_DATA, so both are returned as candidates.
+pub fn measured_data(event_type: u32, data: &[u8]) -> Option<Vec<Vec<u8>>> {
+    match event_type {
+        EV_SEPARATOR | EV_ACTION | EV_EFI_ACTION | EV_EFI_GPT_EVENT
+        | EV_EFI_VARIABLE_DRIVER_CONFIG | EV_EFI_VARIABLE_BOOT2 | EV_EFI_VARIABLE_AUTHORITY => Some(vec![data.to_vec()]),
+        EV_EFI_VARIABLE_BOOT => match UefiVariable::parse(data) {
+            Ok(variable) => Some(vec![variable.data, data.to_vec()]),
+            Err(_) => Some(vec![data.to_vec()]),
+        },
+        _ => None,
+    }
+}
+
+/// Text form of a UEFI device path, e.g. `PciRoot(0x0)/Pci(0x1d,0x0)/NVMe(0x1,...)/HD(1,GPT,...)/\EFI\BOOT\BOOTX64.EFI`
+pub fn device_path_text(data: &[u8]) -> Result<String> {
+    let mut reader = ByteReader::new(data);
+    let mut text = String::new();
+    while !reader.is_at_end() {
+        let node_type = reader.read_u8()?;
+        let sub_type = reader.read_u8()?;
+        let length = reader.read_u16()? as usize;
+        if length < 4 {
+            return Err(Error::Parse(format!("Invalid device path node length {}", length)));
+        }
+        let node = reader.read_bytes(length - 4)?;
+        match (node_type, sub_type) {
+            (0x7f, 0xff) => break,
+            (0x7f, _) => text.push(','),
+            _ => {
+                if !text.is_empty() && !text.ends_with(',') {
+                    text.push('/');
+                }
+                text.push_str(&device_path_node_text(node_type, sub_type, node)?);
+            },
+        }
+    }
+    Ok(text)
+}
+
+/// Text form of one device path node, following the UEFI spec where known
+fn device_path_node_text(node_type: u8, sub_type: u8, node: &[u8]) -> Result<String> {
+    let mut reader = ByteReader::new(node);
+    let text = match (node_type, sub_type) {
+        // Hardware
+        (0x01, 0x01) => {
+            let function = reader.read_u8()?;
+            format!("Pci(0x{:x},0x{:x})", reader.read_u8()?, function)
+        },
+        (0x01, 0x03) => format!(
+            "MemoryMapped(0x{:x},0x{:x},0x{:x})",
+            reader.read_u32()?,
+            reader.read_u64()?,
+            reader.read_u64()?
+        ),
+        (0x01, 0x04) => format!("VenHw({})", EfiGuid::read(&mut reader)?),
+        // ACPI
+        (0x02, 0x01) => match (reader.read_u32()?, reader.read_u32()?) {
+            (0x0a03_41d0, uid) => format!("PciRoot(0x{:x})", uid),
+            (0x0a08_41d0, uid) => format!("PcieRoot(0x{:x})", uid),
+            (hid, uid) => format!("Acpi(0x{:08x},0x{:x})", hid, uid),
+        },
+        // Messaging
+        (0x03, 0x02) => format!("Scsi(0x{:x},0x{:x})", reader.read_u16()?, reader.read_u16()?),
+        (0x03, 0x05) => format!("USB(0x{:x},0x{:x})", reader.read_u8()?, reader.read_u8()?),
+        (0x03, 0x0a) => format!("VenMsg({})", EfiGuid::read(&mut reader)?),
+        (0x03, 0x0b) => {
+            let mac = reader.read_bytes(32)?;
+            format!("MAC({},0x{:x})", hex::encode(&mac[..6]), reader.read_u8()?)
+        },
+        (0x03, 0x12) => format!(
+            "Sata(0x{:x},0x{:x},0x{:x})",
+            reader.read_u16()?,
+            reader.read_u16()?,
+            reader.read_u16()?
+        ),
+        (0x03, 0x17) => {
+            let namespace = reader.read_u32()?;
+            let eui: Vec<String> = reader.read_bytes(8)?.iter().rev().map(|byte| format!("{:02x}", byte)).collect();
+            format!("NVMe(0x{:x},{})", namespace, eui.join("-"))
+        },
+        (0x03, 0x18) => format!("Uri({})", String::from_utf8_lossy(node)),
+        // Media
+        (0x04, 0x01) => {
+            let partition = reader.read_u32()?;
+            let start = reader.read_u64()?;
+            let size = reader.read_u64()?;
+            let signature = reader.read_bytes(16)?;
+            let _mbr_type = reader.read_u8()?;
+            match reader.read_u8()? {
+                0x01 => format!(
+                    "HD({},MBR,0x{:08x},0x{:x},0x{:x})",
+                    partition,
+                    u32::from_le_bytes([signature[0], signature[1], signature[2], signature[3]]),
+                    start,
+                    size
+                ),
+                0x02 => {
+                    let mut guid = [0u8; 16];
+                    guid.copy_from_slice(signature);
+                    format!("HD({},GPT,{},0x{:x},0x{:x})", partition, EfiGuid(guid), start, size)
+                },
+                _ => format!("HD({},0x{:x},0x{:x})", partition, start, size),
+            }
+        },
+        (0x04, 0x02) => format!(
+            "CDROM(0x{:x},0x{:x},0x{:x})",
+            reader.read_u32()?,
+            reader.read_u64()?,
+            reader.read_u64()?
+        ),
+        (0x04, 0x03) => format!("VenMedia({})", EfiGuid::read(&mut reader)?),
+        (0x04, 0x04) => utf16_string(node),
+        (0x04, 0x06) => format!("FvFile({})", EfiGuid::read(&mut reader)?),
+        (0x04, 0x07) => format!("Fv({})", EfiGuid::read(&mut reader)?),
+        (0x04, 0x08) => {
+            let _reserved = reader.read_u32()?;
+            format!("Offset(0x{:x},0x{:x})", reader.read_u64()?, reader.read_u64()?)
+        },
+        _ => format!("Path({},{},{})", node_type, sub_type, hex::encode(node)),
+    };
+    Ok(text)
+}
+
+/// EFI_LOAD_OPTION of a Boot#### variable as `Description: device path`
+fn load_option_text(data: &[u8]) -> Result<String> {
+    let mut reader = ByteReader::new(data);
+    let attributes = reader.read_u32()?;
+    let path_length = reader.read_u16()? as usize;
+
+    let mut description = Vec::new();
+    loop {
+        match reader.read_u16()? {
+            0 => break,
+            unit => description.push(unit),
+        }
+    }
+    let device_path = device_path_text(reader.read_bytes(path_length)?)?;
+
+    // LOAD_OPTION_ACTIVE
+    let inactive = if attributes & 0x1 == 0 { " (inactive)" } else { "" };
+    Ok(format!("{}: {}{}", String::from_utf16_lossy(&description), device_path, inactive))
+}
+
+/// Boot#### and the other load option variables, e.g. Driver0001
+fn is_load_option_name(name: &str) -> bool {
+    ["Boot", "Driver", "SysPrep", "PlatformRecovery"].iter().any(|prefix| {
+        name.strip_prefix(prefix)
+            .is_some_and(|number| number.len() == 4 && number.chars().all(|c| c.is_ascii_hexdigit()))
+    })
+}
+
+/// UTF-16LE string, up to the first NUL
+fn utf16_string(data: &[u8]) -> String {
+    let units: Vec<u16> = data
+        .chunks_exact(2)
+        .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+        .take_while(|&unit| unit != 0)
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// Event text: UTF-8 (or ASCII) unless it looks like UTF-16LE, without trailing NULs
+fn event_text(data: &[u8]) -> String {
+    if data.len() >= 2 && data.len().is_multiple_of(2) && data[0] != 0 && data[1] == 0 {
+        return utf16_string(data);
+    }
+    String::from_utf8_lossy(data).trim_end_matches('\0').to_string()
+}
+
+fn separator_text(value: &[u8]) -> String {
+    if !value.is_empty() && value.iter().all(|&byte| byte == 0xFF) {
+        format!("0x{} (error)", hex::encode(value))
+    } else {
+        format!("0x{}", hex::encode(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::tcg_event_log::{EV_EFI_HANDOFF_TABLES, EV_POST_CODE};
+
+    fn utf16(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    /// UEFI_VARIABLE_DATA
+    fn variable(guid: EfiGuid, name: &str, data: &[u8]) -> Vec<u8> {
+        let mut bytes = guid.0.to_vec();
+        bytes.extend_from_slice(&(name.encode_utf16().count() as u64).to_le_bytes());
+        bytes.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        bytes.extend(utf16(name));
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    fn node(node_type: u8, sub_type: u8, data: &[u8]) -> Vec<u8> {
+        [vec![node_type, sub_type], ((data.len() + 4) as u16).to_le_bytes().to_vec(), data.to_vec()].concat()
+    }
+
+    const PARTITION_GUID: EfiGuid = EfiGuid::new(0x12345678, 0x9abc, 0xdef0, [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08]);
+
+    /// PciRoot(0x0)/Pci(0x1d,0x2)/NVMe(...)/HD(1,GPT,...)/\EFI\BOOT\BOOTX64.EFI
+    fn device_path() -> Vec<u8> {
+        let mut hard_drive = 1u32.to_le_bytes().to_vec();
+        hard_drive.extend_from_slice(&2048u64.to_le_bytes());
+        hard_drive.extend_from_slice(&0x100000u64.to_le_bytes());
+        hard_drive.extend_from_slice(&PARTITION_GUID.0);
+        hard_drive.extend_from_slice(&[0x02, 0x02]); // GPT
+        let mut nvme = 1u32.to_le_bytes().to_vec();
+        nvme.extend_from_slice(&[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08]);
+        [
+            node(0x02, 0x01, &[0xd0, 0x41, 0x03, 0x0a, 0, 0, 0, 0]),
+            node(0x01, 0x01, &[0x02, 0x1d]), // function, then device
+            node(0x03, 0x17, &nvme),
+            node(0x04, 0x01, &hard_drive),
+            node(0x04, 0x04, &[utf16("\\EFI\\BOOT\\BOOTX64.EFI"), vec![0, 0]].concat()),
+            node(0x7f, 0xff, &[]),
+        ]
+        .concat()
+    }
+
+    const DEVICE_PATH_TEXT: &str = "PciRoot(0x0)/Pci(0x1d,0x2)/NVMe(0x1,08-07-06-05-04-03-02-01)/\
+        HD(1,GPT,12345678-9abc-def0-0102-030405060708,0x800,0x100000)/\\EFI\\BOOT\\BOOTX64.EFI";
+
+    /// UEFI_IMAGE_LOAD_EVENT with a UINTN of `uintn_size` bytes
+    fn image_load(uintn_size: usize) -> Vec<u8> {
+        let uintn = |value: u64| value.to_le_bytes()[..uintn_size].to_vec();
+        let path = device_path();
+        [
+            0x7e00_0000u64.to_le_bytes().to_vec(),
+            uintn(0x1_2000),
+            uintn(0),
+            uintn(path.len() as u64),
+            path,
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn decodes_variables() {
+        let data = variable(EfiGuid::GLOBAL_VARIABLE, "SecureBoot", &[1]);
+        let decoded = UefiEvent::decode(EV_EFI_VARIABLE_DRIVER_CONFIG, &data, 8).unwrap().unwrap();
+        assert_eq!(decoded.summary(), "SecureBoot");
+        assert_eq!(decoded.fields(), [
+            ("GUID", "8be4df61-93ca-11d2-aa0d-00e098032b8c (EFI_GLOBAL_VARIABLE)".to_string()),
+            ("Name", "SecureBoot".to_string()),
+            ("Value", "0x01".to_string()),
+        ]);
+
+        let boot_order = variable(EfiGuid::GLOBAL_VARIABLE, "BootOrder", &[1, 0, 0, 0]);
+        let UefiEvent::Variable(boot_order) = UefiEvent::decode(EV_EFI_VARIABLE_BOOT, &boot_order, 8).unwrap().unwrap() else {
+            panic!("not a variable");
+        };
+        assert_eq!(boot_order.value_text(), "Boot0001, Boot0000");
+
+        let path = device_path();
+        let mut load_option = 1u32.to_le_bytes().to_vec(); // LOAD_OPTION_ACTIVE
+        load_option.extend_from_slice(&(path.len() as u16).to_le_bytes());
+        load_option.extend([utf16("ubuntu"), vec![0, 0]].concat());
+        load_option.extend(path);
+        let boot0001 = variable(EfiGuid::GLOBAL_VARIABLE, "Boot0001", &load_option);
+        let UefiEvent::Variable(boot0001) = UefiEvent::decode(EV_EFI_VARIABLE_BOOT2, &boot0001, 8).unwrap().unwrap() else {
+            panic!("not a variable");
+        };
+        assert_eq!(boot0001.value_text(), format!("ubuntu: {}", DEVICE_PATH_TEXT));
+
+        let truncated = &data[..data.len() - 1];
+        assert!(UefiEvent::decode(EV_EFI_VARIABLE_AUTHORITY, truncated, 8).unwrap().is_err());
+    }
+
+    #[test]
+    fn decodes_image_load_with_either_uintn_size() {
+        for uintn_size in [4, 8] {
+            let decoded = UefiEvent::decode(EV_EFI_BOOT_SERVICES_APPLICATION, &image_load(uintn_size), uintn_size)
+                .unwrap()
+                .unwrap();
+            assert_eq!(decoded, UefiEvent::ImageLoad {
+                image_location: 0x7e00_0000,
+                image_length: 0x1_2000,
+                link_time_address: 0,
+                device_path: DEVICE_PATH_TEXT.to_string(),
+            });
+        }
+
+        // A 32-bit event read with a 64-bit UINTN runs into the device path
+        assert!(UefiEvent::decode(EV_EFI_BOOT_SERVICES_DRIVER, &image_load(4), 8).unwrap().is_err());
+    }
+
+    #[test]
+    fn decodes_device_path_nodes() {
+        let path = [
+            node(0x01, 0x04, &EfiGuid::SHIM_LOCK.0),
+            node(0x7f, 0x01, &[]),
+            node(0x03, 0x12, &[0x01, 0x00, 0xff, 0xff, 0x00, 0x00]),
+            node(0x05, 0x06, &[0xab]),
+            node(0x7f, 0xff, &[]),
+        ]
+        .concat();
+        assert_eq!(
+            device_path_text(&path).unwrap(),
+            "VenHw(605dab50-e046-4300-abb6-3dd810dd8b23),Sata(0x1,0xffff,0x0)/Path(5,6,ab)"
+        );
+        assert!(device_path_text(&[0x01, 0x01, 0x02, 0x00]).is_err());
+    }
+
+    #[test]
+    fn decodes_firmware_blobs() {
+        let blob = [0xff00_0000u64.to_le_bytes(), 0x10_0000u64.to_le_bytes()].concat();
+        let decoded = UefiEvent::decode(EV_EFI_PLATFORM_FIRMWARE_BLOB, &blob, 8).unwrap().unwrap();
+        assert_eq!(decoded.summary(), "0xff000000 (1048576 bytes)");
+
+        let blob2 = [vec![4], b"DXEA".to_vec(), blob].concat();
+        let decoded = UefiEvent::decode(EV_EFI_PLATFORM_FIRMWARE_BLOB2, &blob2, 8).unwrap().unwrap();
+        assert_eq!(decoded.summary(), "DXEA");
+        assert_eq!(decoded.fields()[0], ("Description", "DXEA".to_string()));
+    }
+
+    #[test]
+    fn decodes_separators_and_text() {
+        let separator = |value: &[u8]| UefiEvent::decode(EV_SEPARATOR, value, 8).unwrap().unwrap().summary();
+        assert_eq!(separator(&[0; 4]), "0x00000000");
+        assert_eq!(separator(&[0xff; 4]), "0xffffffff (error)");
+
+        let text = |event_type, data: &[u8]| UefiEvent::decode(event_type, data, 8).unwrap().unwrap().summary();
+        assert_eq!(text(EV_EFI_ACTION, b"Calling EFI Application from Boot Option"), "Calling EFI Application from Boot Option");
+        assert_eq!(text(EV_IPL, b"grub_cmd: linux /vmlinuz\0"), "grub_cmd: linux /vmlinuz");
+        assert_eq!(text(EV_ACTION, &[utf16("Exit Boot Services"), vec![0, 0]].concat()), "Exit Boot Services");
+
+        assert!(UefiEvent::decode(EV_POST_CODE, b"ACPI DATA", 8).is_none());
+    }
+
+    #[test]
+    fn measured_data_of_each_event_type() {
+        assert_eq!(measured_data(EV_SEPARATOR, &[0; 4]), Some(vec![vec![0; 4]]));
+        assert_eq!(measured_data(EV_EFI_ACTION, b"action"), Some(vec![b"action".to_vec()]));
+
+        let secure_boot = variable(EfiGuid::GLOBAL_VARIABLE, "SecureBoot", &[1]);
+        assert_eq!(measured_data(EV_EFI_VARIABLE_DRIVER_CONFIG, &secure_boot), Some(vec![secure_boot.clone()]));
+
+        // EV_EFI_VARIABLE_BOOT: the value as the spec says, or the whole UEFI_VARIABLE_DATA
+        let boot_order = variable(EfiGuid::GLOBAL_VARIABLE, "BootOrder", &[1, 0, 0, 0]);
+        assert_eq!(measured_data(EV_EFI_VARIABLE_BOOT, &boot_order), Some(vec![vec![1, 0, 0, 0], boot_order.clone()]));
+        assert_eq!(measured_data(EV_EFI_VARIABLE_BOOT, &boot_order[..20]), Some(vec![boot_order[..20].to_vec()]));
+        assert_eq!(measured_data(EV_EFI_VARIABLE_BOOT2, &boot_order), Some(vec![boot_order]));
+
+        // Images and tables are measured from memory, not from the event data
+        assert_eq!(measured_data(EV_EFI_BOOT_SERVICES_APPLICATION, &image_load(8)), None);
+        assert_eq!(measured_data(EV_EFI_HANDOFF_TABLES, &[0; 16]), None);
+    }
+}